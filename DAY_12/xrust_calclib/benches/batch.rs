@@ -0,0 +1,96 @@
+//! Slice operations against a loop of scalar calls.
+//!
+//! Run with `cargo bench --bench batch`; add
+//! `RUSTFLAGS="-C target-cpu=native"` to let LLVM use AVX where available.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use xrust_calclib::batch;
+
+const LEN: usize = 4096;
+
+fn samples() -> (Vec<f64>, Vec<f64>) {
+    let a = (0..LEN).map(|i| i as f64 * 0.25).collect();
+    let b = (0..LEN).map(|i| (i % 97) as f64 + 1.0).collect();
+    (a, b)
+}
+
+fn bench_elementwise(c: &mut Criterion) {
+    let (a, b) = samples();
+    let mut out = vec![0.0; LEN];
+    let mut group = c.benchmark_group("elementwise");
+    group.throughput(Throughput::Elements(LEN as u64));
+
+    group.bench_function("add/scalar", |bench| {
+        bench.iter(|| {
+            let (a, b) = (black_box(&a), black_box(&b));
+            for i in 0..LEN {
+                out[i] = xrust_calclib::add(a[i], b[i]);
+            }
+        })
+    });
+    group.bench_function("add/slices", |bench| {
+        bench.iter(|| batch::add_slices(black_box(&a), black_box(&b), &mut out))
+    });
+
+    group.bench_function("mul_scalar/scalar", |bench| {
+        bench.iter(|| {
+            let a = black_box(&a);
+            for i in 0..LEN {
+                out[i] = xrust_calclib::mul(a[i], 3.3 / 4095.0);
+            }
+        })
+    });
+    group.bench_function("mul_scalar/slices", |bench| {
+        bench.iter(|| batch::mul_scalar(black_box(&a), 3.3 / 4095.0, &mut out))
+    });
+
+    group.bench_function("sqrt/scalar", |bench| {
+        bench.iter(|| {
+            let a = black_box(&a);
+            for i in 0..LEN {
+                out[i] = xrust_calclib::sqrt(a[i]).unwrap_or(f64::NAN);
+            }
+        })
+    });
+    group.bench_function("sqrt/slices", |bench| {
+        bench.iter(|| batch::sqrt_slice(black_box(&a), &mut out))
+    });
+    group.finish();
+}
+
+fn bench_reductions(c: &mut Criterion) {
+    let (a, b) = samples();
+    let mut group = c.benchmark_group("reductions");
+    group.throughput(Throughput::Elements(LEN as u64));
+
+    group.bench_function("sum/scalar", |bench| {
+        bench.iter(|| {
+            black_box(&a)
+                .iter()
+                .fold(0.0, |acc, &x| xrust_calclib::add(acc, x))
+        })
+    });
+    group.bench_function("sum/slices", |bench| {
+        bench.iter(|| batch::sum(black_box(&a)))
+    });
+
+    group.bench_function("dot/scalar", |bench| {
+        bench.iter(|| {
+            black_box(&a)
+                .iter()
+                .zip(black_box(&b))
+                .fold(0.0, |acc, (&x, &y)| {
+                    xrust_calclib::add(acc, xrust_calclib::mul(x, y))
+                })
+        })
+    });
+    group.bench_function("dot/slices", |bench| {
+        bench.iter(|| batch::dot(black_box(&a), black_box(&b)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_elementwise, bench_reductions);
+criterion_main!(benches);
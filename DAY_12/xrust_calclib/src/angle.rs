@@ -0,0 +1,211 @@
+//! Angle units for the trigonometric functions.
+//!
+//! An [`AngleMode`] is passed to whatever evaluates trig, rather than kept as
+//! global state: each [`Session`](crate::session::Session) and
+//! [`Program`](crate::program::Program) carries its own, and the one-shot
+//! evaluators such as [`eval`](crate::eval) use radians.
+//!
+//! ```
+//! use xrust_calclib::AngleMode;
+//!
+//! assert_eq!(AngleMode::Degrees.sin(90.0), 1.0);
+//! assert_eq!(AngleMode::Gradians.cos(200.0), -1.0);
+//! assert_eq!("deg".parse(), Ok(AngleMode::Degrees));
+//! ```
+
+use core::f64::consts::PI;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{CalcError, math};
+
+/// The unit angles are measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleMode {
+    /// A quarter turn (right angle) in this unit.
+    fn quarter_turn(self) -> f64 {
+        match self {
+            AngleMode::Radians => PI / 2.0,
+            AngleMode::Degrees => 90.0,
+            AngleMode::Gradians => 100.0,
+        }
+    }
+
+    /// Converts an angle in this unit to radians.
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle * (PI / 2.0) / self.quarter_turn(),
+        }
+    }
+
+    /// Converts an angle in radians to this unit.
+    pub fn from_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle * self.quarter_turn() / (PI / 2.0),
+        }
+    }
+
+    /// For degrees and gradians, the number of whole quarter turns in `angle`
+    /// (mod 4) when it is an exact right-angle multiple. Used to return exact
+    /// results such as `sin(180°) = 0` and to detect the poles of `tan`.
+    fn exact_quarter(self, angle: f64) -> Option<u8> {
+        if self == AngleMode::Radians {
+            return None;
+        }
+        let q = angle / self.quarter_turn();
+        if math::fract(q) == 0.0 && q.abs() < 1e15 {
+            Some((q as i64).rem_euclid(4) as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Reduces `angle` by whole turns before converting, to keep precision
+    /// for large degree and gradian inputs.
+    fn reduced_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => self.to_radians(angle % (4.0 * self.quarter_turn())),
+        }
+    }
+
+    /// Sine of `angle` measured in this unit.
+    pub fn sin(self, angle: f64) -> f64 {
+        match self.exact_quarter(angle) {
+            Some(k) => [0.0, 1.0, 0.0, -1.0][k as usize],
+            None => math::sin(self.reduced_radians(angle)),
+        }
+    }
+
+    /// Cosine of `angle` measured in this unit.
+    pub fn cos(self, angle: f64) -> f64 {
+        match self.exact_quarter(angle) {
+            Some(k) => [1.0, 0.0, -1.0, 0.0][k as usize],
+            None => math::cos(self.reduced_radians(angle)),
+        }
+    }
+
+    /// Tangent of `angle` measured in this unit; errors at odd right angles.
+    pub fn tan(self, angle: f64) -> Result<f64, CalcError> {
+        match self.exact_quarter(angle) {
+            Some(k) if k % 2 == 1 => Err(CalcError::Domain(
+                "Tangent is undefined at odd multiples of a right angle",
+            )),
+            Some(_) => Ok(0.0),
+            None => Ok(math::tan(self.reduced_radians(angle))),
+        }
+    }
+
+    /// Arcsine in this unit; errors outside `[-1, 1]`.
+    pub fn asin(self, x: f64) -> Result<f64, CalcError> {
+        if !(-1.0..=1.0).contains(&x) {
+            return Err(CalcError::Domain("Arcsine is only defined on [-1, 1]"));
+        }
+        Ok(self.from_radians(math::asin(x)))
+    }
+
+    /// Arccosine in this unit; errors outside `[-1, 1]`.
+    pub fn acos(self, x: f64) -> Result<f64, CalcError> {
+        if !(-1.0..=1.0).contains(&x) {
+            return Err(CalcError::Domain("Arccosine is only defined on [-1, 1]"));
+        }
+        Ok(self.from_radians(math::acos(x)))
+    }
+
+    /// Arctangent in this unit.
+    pub fn atan(self, x: f64) -> f64 {
+        self.from_radians(math::atan(x))
+    }
+
+    /// Four-quadrant arctangent of `y / x` in this unit.
+    pub fn atan2(self, y: f64, x: f64) -> f64 {
+        self.from_radians(math::atan2(y, x))
+    }
+}
+
+/// Prints `radians`, `degrees` or `gradians`.
+impl fmt::Display for AngleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AngleMode::Radians => "radians",
+            AngleMode::Degrees => "degrees",
+            AngleMode::Gradians => "gradians",
+        })
+    }
+}
+
+/// Parses `rad`, `deg` or `grad`, or the full names, ignoring ASCII case.
+impl FromStr for AngleMode {
+    type Err = CalcError;
+
+    fn from_str(s: &str) -> Result<Self, CalcError> {
+        let is = |names: [&str; 2]| names.iter().any(|n| n.eq_ignore_ascii_case(s));
+        if is(["rad", "radians"]) {
+            Ok(AngleMode::Radians)
+        } else if is(["deg", "degrees"]) {
+            Ok(AngleMode::Degrees)
+        } else if is(["grad", "gradians"]) {
+            Ok(AngleMode::Gradians)
+        } else {
+            Err(CalcError::Domain(
+                "Angle mode must be radians, degrees or gradians",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::ToString;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_trig_in_each_mode() {
+        let deg = AngleMode::Degrees;
+        assert_eq!(deg.sin(180.0), 0.0);
+        assert_eq!(deg.cos(-90.0), 0.0);
+        assert_eq!(deg.sin(450.0), 1.0);
+        assert!(close(deg.sin(30.0), 0.5));
+        assert!(matches!(deg.tan(90.0), Err(CalcError::Domain(_))));
+        assert!(close(deg.tan(45.0).unwrap(), 1.0));
+        assert!(close(deg.asin(0.5).unwrap(), 30.0));
+        assert!(close(deg.atan2(1.0, -1.0), 135.0));
+
+        let grad = AngleMode::Gradians;
+        assert_eq!(grad.cos(200.0), -1.0);
+        assert!(close(grad.acos(0.0).unwrap(), 100.0));
+
+        let rad = AngleMode::Radians;
+        assert!(close(rad.sin(PI / 6.0), 0.5));
+        assert!(matches!(rad.asin(1.5), Err(CalcError::Domain(_))));
+    }
+
+    #[test]
+    fn test_names() {
+        for mode in [AngleMode::Radians, AngleMode::Degrees, AngleMode::Gradians] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+        assert_eq!("GRAD".parse(), Ok(AngleMode::Gradians));
+        assert!("turns".parse::<AngleMode>().is_err());
+    }
+}
@@ -0,0 +1,468 @@
+//! Numerical methods over closures: root finding, integration and
+//! differentiation.
+//!
+//! Every method takes an `Fn(f64) -> f64`, a configurable [`Options`] and
+//! returns an [`Estimate`] carrying the result together with its error
+//! estimate, iteration and evaluation counts, and whether the tolerance was
+//! met. Running out of iterations is not an error: the best value so far is
+//! returned with `converged == false`. Invalid input (a bracket without a sign
+//! change, a vanishing derivative in Newton's method) is a [`CalcError`].
+//!
+//! ```
+//! use xrust_calclib::calculus::{self, Options};
+//!
+//! let root = calculus::brent(|x| x * x - 2.0, 0.0, 2.0, Options::default()).unwrap();
+//! assert!(root.converged);
+//! assert!((root.value - 2f64.sqrt()).abs() < 1e-10);
+//! ```
+
+use crate::CalcError;
+
+/// Stopping criteria shared by all methods.
+///
+/// The tolerance is absolute for values near zero and relative once the
+/// magnitude exceeds one, i.e. a method stops when its error estimate is at
+/// most `tolerance * max(1, |value|)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Options {
+    /// Builds options with the given tolerance and iteration limit.
+    pub const fn new(tolerance: f64, max_iterations: usize) -> Self {
+        Options {
+            tolerance,
+            max_iterations,
+        }
+    }
+
+    fn accepts(&self, error: f64, value: f64) -> bool {
+        error <= self.tolerance * value.abs().max(1.0)
+    }
+}
+
+/// A tolerance of `1e-10` and at most 100 iterations.
+impl Default for Options {
+    fn default() -> Self {
+        Options::new(1e-10, 100)
+    }
+}
+
+/// The result of a numerical method, with convergence diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// The root, integral or derivative.
+    pub value: f64,
+    /// An estimate of the absolute error in `value`.
+    pub error: f64,
+    /// Iterations (refinements for integrals) performed.
+    pub iterations: usize,
+    /// Calls made to the function.
+    pub evaluations: usize,
+    /// Whether `error` met the requested tolerance.
+    pub converged: bool,
+}
+
+/// Wraps a closure and counts how often it is called.
+struct Counted<F> {
+    f: F,
+    calls: usize,
+}
+
+impl<F: Fn(f64) -> f64> Counted<F> {
+    fn new(f: F) -> Self {
+        Counted { f, calls: 0 }
+    }
+
+    fn call(&mut self, x: f64) -> f64 {
+        self.calls += 1;
+        (self.f)(x)
+    }
+
+    fn estimate(&self, value: f64, error: f64, iterations: usize, converged: bool) -> Estimate {
+        Estimate {
+            value,
+            error,
+            iterations,
+            evaluations: self.calls,
+            converged,
+        }
+    }
+}
+
+fn check_finite(values: &[f64]) -> Result<(), CalcError> {
+    if values.iter().all(|x| x.is_finite()) {
+        Ok(())
+    } else {
+        Err(CalcError::Domain("Bounds must be finite"))
+    }
+}
+
+fn no_sign_change() -> CalcError {
+    CalcError::Domain("Root must be bracketed by a sign change")
+}
+
+/// Finds a root of `f` in `[a, b]` by repeated halving. `f(a)` and `f(b)`
+/// must have opposite signs. Slow but guaranteed to converge.
+pub fn bisection<F: Fn(f64) -> f64>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: Options,
+) -> Result<Estimate, CalcError> {
+    check_finite(&[a, b])?;
+    let mut f = Counted::new(f);
+    let (mut a, mut b) = (a, b);
+    let (fa, fb) = (f.call(a), f.call(b));
+    if fa == 0.0 {
+        return Ok(f.estimate(a, 0.0, 0, true));
+    }
+    if fb == 0.0 {
+        return Ok(f.estimate(b, 0.0, 0, true));
+    }
+    if fa.signum() == fb.signum() {
+        return Err(no_sign_change());
+    }
+    let mut fa = fa;
+    let (mut mid, mut half) = (0.5 * (a + b), (b - a).abs());
+    for iter in 1..=opts.max_iterations {
+        mid = a + 0.5 * (b - a);
+        half = 0.5 * (b - a).abs();
+        let fm = f.call(mid);
+        // No representable point is left between the bounds.
+        if fm == 0.0 || opts.accepts(half, mid) || mid == a || mid == b {
+            return Ok(f.estimate(mid, half, iter, true));
+        }
+        if fm.signum() == fa.signum() {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+    Ok(f.estimate(mid, half, opts.max_iterations, false))
+}
+
+/// Finds a root of `f` near `x0` with Newton-Raphson steps using the
+/// derivative `df`. Converges quadratically near a simple root but may
+/// wander for poor starting points.
+pub fn newton<F, D>(f: F, df: D, x0: f64, opts: Options) -> Result<Estimate, CalcError>
+where
+    F: Fn(f64) -> f64,
+    D: Fn(f64) -> f64,
+{
+    check_finite(&[x0])?;
+    let mut f = Counted::new(f);
+    let mut x = x0;
+    let mut step = f64::INFINITY;
+    for iter in 1..=opts.max_iterations {
+        let fx = f.call(x);
+        if fx == 0.0 {
+            return Ok(f.estimate(x, 0.0, iter, true));
+        }
+        let d = df(x);
+        if d == 0.0 || !d.is_finite() {
+            return Err(CalcError::Domain(
+                "Derivative vanished during Newton iteration",
+            ));
+        }
+        step = fx / d;
+        x -= step;
+        if !x.is_finite() {
+            return Err(CalcError::Domain("Newton iteration diverged"));
+        }
+        if opts.accepts(step.abs(), x) {
+            return Ok(f.estimate(x, step.abs(), iter, true));
+        }
+    }
+    Ok(f.estimate(x, step.abs(), opts.max_iterations, false))
+}
+
+/// Finds a root of `f` in `[a, b]` with Brent's method, which combines
+/// bisection's guarantee with inverse quadratic interpolation's speed.
+/// `f(a)` and `f(b)` must have opposite signs.
+pub fn brent<F: Fn(f64) -> f64>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: Options,
+) -> Result<Estimate, CalcError> {
+    check_finite(&[a, b])?;
+    let mut f = Counted::new(f);
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (f.call(a), f.call(b));
+    if fa == 0.0 {
+        return Ok(f.estimate(a, 0.0, 0, true));
+    }
+    if fb == 0.0 {
+        return Ok(f.estimate(b, 0.0, 0, true));
+    }
+    if fa.signum() == fb.signum() {
+        return Err(no_sign_change());
+    }
+    // `b` is the best estimate, `c` the other end of the bracket, and `d`/`e`
+    // the last two step sizes.
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    let mut xm = 0.5 * (c - b);
+    for iter in 1..=opts.max_iterations {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            (a, b, c) = (b, c, b);
+            (fa, fb, fc) = (fb, fc, fb);
+        }
+        let tol = 2.0 * f64::EPSILON * b.abs() + 0.5 * opts.tolerance * b.abs().max(1.0);
+        xm = 0.5 * (c - b);
+        if xm.abs() <= tol || fb == 0.0 {
+            return Ok(f.estimate(b, xm.abs(), iter, true));
+        }
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            // Try interpolation: secant when only two points are distinct,
+            // inverse quadratic otherwise.
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * xm * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * xm * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            let limit = (3.0 * xm * q - (tol * q).abs()).min((e * q).abs());
+            if 2.0 * p < limit {
+                e = d;
+                d = p / q;
+            } else {
+                d = xm;
+                e = d;
+            }
+        } else {
+            d = xm;
+            e = d;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol { d } else { tol.copysign(xm) };
+        fb = f.call(b);
+    }
+    Ok(f.estimate(b, xm.abs(), opts.max_iterations, false))
+}
+
+/// Integrators never refine more than this many times, since each refinement
+/// doubles the number of function evaluations.
+pub const MAX_REFINEMENTS: usize = 24;
+
+/// Integrates `f` over `[a, b]` with composite Simpson's rule, doubling the
+/// number of panels until successive results agree. The error estimate is
+/// Richardson's `|S(2n) - S(n)| / 15`.
+pub fn simpson<F: Fn(f64) -> f64>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: Options,
+) -> Result<Estimate, CalcError> {
+    check_finite(&[a, b])?;
+    let mut f = Counted::new(f);
+    let ends = f.call(a) + f.call(b);
+    let mut n = 2;
+    let mut h = (b - a) / 2.0;
+    let mut evens = 0.0;
+    let mut odds = f.call(a + h);
+    let mut prev = h / 3.0 * (ends + 4.0 * odds);
+    let mut error = f64::INFINITY;
+    let max = opts.max_iterations.min(MAX_REFINEMENTS);
+    for iter in 1..=max {
+        // The old odd points become even points of the finer grid.
+        evens += odds;
+        n *= 2;
+        h /= 2.0;
+        odds = (0..n / 2).map(|k| f.call(a + (2 * k + 1) as f64 * h)).sum();
+        let s = h / 3.0 * (ends + 4.0 * odds + 2.0 * evens);
+        error = (s - prev).abs() / 15.0;
+        prev = s;
+        if opts.accepts(error, s) {
+            return Ok(f.estimate(s, error, iter, true));
+        }
+    }
+    Ok(f.estimate(prev, error, max, false))
+}
+
+/// Nodes and weights of the 5-point Gauss-Legendre rule on `[-1, 1]`.
+const GL5: [(f64, f64); 5] = [
+    (0.0, 0.568_888_888_888_888_9),
+    (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+    (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+];
+
+/// Integrates `f` over `[a, b]` with the composite 5-point Gauss-Legendre
+/// rule, doubling the number of panels until successive results agree.
+/// Exact for polynomials up to degree 9 and very efficient for smooth `f`.
+pub fn gauss_legendre<F: Fn(f64) -> f64>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: Options,
+) -> Result<Estimate, CalcError> {
+    check_finite(&[a, b])?;
+    let mut f = Counted::new(f);
+    let mut rule = |panels: usize| {
+        let h = (b - a) / panels as f64;
+        (0..panels)
+            .map(|p| {
+                let mid = a + (p as f64 + 0.5) * h;
+                GL5.iter()
+                    .map(|&(x, w)| w * f.call(mid + 0.5 * h * x))
+                    .sum::<f64>()
+                    * 0.5
+                    * h
+            })
+            .sum::<f64>()
+    };
+    let mut prev = rule(1);
+    let mut error = f64::INFINITY;
+    let max = opts.max_iterations.min(MAX_REFINEMENTS);
+    for iter in 1..=max {
+        let g = rule(1 << iter);
+        error = (g - prev).abs();
+        prev = g;
+        if opts.accepts(error, g) {
+            return Ok(f.estimate(g, error, iter, true));
+        }
+    }
+    Ok(f.estimate(prev, error, max, false))
+}
+
+/// The first derivative of `f` at `x` by Ridders' method: central differences
+/// with shrinking steps, extrapolated to zero step size. Stops early once the
+/// error estimate meets the tolerance or starts growing.
+pub fn derivative<F: Fn(f64) -> f64>(f: F, x: f64, opts: Options) -> Result<Estimate, CalcError> {
+    const TABLE: usize = 10;
+    const SHRINK: f64 = 1.4;
+    check_finite(&[x])?;
+    let mut f = Counted::new(f);
+    let mut h = 0.1 * x.abs().max(1.0);
+    let mut central = |h: f64| (f.call(x + h) - f.call(x - h)) / (2.0 * h);
+    // `t[j][i]`: step `i`, extrapolated `j` times.
+    let mut t = [[0.0; TABLE]; TABLE];
+    t[0][0] = central(h);
+    let (mut best, mut error) = (t[0][0], f64::INFINITY);
+    let max = opts.max_iterations.clamp(1, TABLE);
+    let mut iterations = 1;
+    for i in 1..max {
+        iterations = i + 1;
+        h /= SHRINK;
+        t[0][i] = central(h);
+        let mut fac = SHRINK * SHRINK;
+        for j in 1..=i {
+            t[j][i] = (t[j - 1][i] * fac - t[j - 1][i - 1]) / (fac - 1.0);
+            fac *= SHRINK * SHRINK;
+            let e = (t[j][i] - t[j - 1][i])
+                .abs()
+                .max((t[j][i] - t[j - 1][i - 1]).abs());
+            if e <= error {
+                error = e;
+                best = t[j][i];
+            }
+        }
+        // Rounding noise now dominates; higher orders only get worse.
+        if (t[i][i] - t[i - 1][i - 1]).abs() >= 2.0 * error || opts.accepts(error, best) {
+            break;
+        }
+    }
+    let converged = opts.accepts(error, best);
+    Ok(f.estimate(best, error, iterations, converged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math;
+
+    #[test]
+    fn test_roots() {
+        let f = |x: f64| x * x * x - 2.0 * x - 5.0;
+        let expected = 2.094_551_481_542_326_5;
+        let opts = Options::default();
+
+        let bi = bisection(f, 2.0, 3.0, opts).unwrap();
+        assert!(bi.converged);
+        assert!((bi.value - expected).abs() < 1e-9);
+
+        let nr = newton(f, |x| 3.0 * x * x - 2.0, 2.0, opts).unwrap();
+        assert!(nr.converged);
+        assert!((nr.value - expected).abs() < 1e-12);
+
+        let br = brent(f, 2.0, 3.0, opts).unwrap();
+        assert!(br.converged);
+        assert!((br.value - expected).abs() < 1e-10);
+        // Brent needs far fewer evaluations than bisection.
+        assert!(br.evaluations < bi.evaluations / 2);
+
+        assert_eq!(bisection(f, 0.0, 1.0, opts), Err(no_sign_change()));
+        assert_eq!(brent(f, 3.0, 4.0, opts), Err(no_sign_change()));
+        assert_eq!(
+            brent(f, 2.0, f64::NAN, opts),
+            Err(CalcError::Domain("Bounds must be finite"))
+        );
+        assert!(matches!(
+            newton(|x| x * x + 1.0, |x| 2.0 * x, 0.0, opts),
+            Err(CalcError::Domain(_))
+        ));
+    }
+
+    #[test]
+    fn test_iteration_limit() {
+        let few = Options::new(1e-12, 3);
+        let r = bisection(|x| x - 0.3, 0.0, 1.0, few).unwrap();
+        assert!(!r.converged);
+        assert_eq!(r.iterations, 3);
+        assert_eq!(r.evaluations, 5);
+        assert!((r.value - 0.3).abs() <= r.error);
+    }
+
+    #[test]
+    fn test_integration() {
+        let opts = Options::default();
+        let s = simpson(math::sin, 0.0, core::f64::consts::PI, opts).unwrap();
+        assert!(s.converged);
+        assert!((s.value - 2.0).abs() < 1e-9);
+
+        let g = gauss_legendre(math::exp, 0.0, 1.0, opts).unwrap();
+        assert!(g.converged);
+        assert!((g.value - (core::f64::consts::E - 1.0)).abs() < 1e-12);
+        assert!(g.evaluations < s.evaluations);
+
+        // Simpson is exact for cubics; reversed bounds flip the sign.
+        let c = simpson(|x| x * x * x, 2.0, 0.0, opts).unwrap();
+        assert_eq!(c.value, -4.0);
+
+        let limited = simpson(math::exp, 0.0, 1.0, Options::new(1e-15, 1)).unwrap();
+        assert!(!limited.converged);
+        assert_eq!(limited.evaluations, 5);
+    }
+
+    #[test]
+    fn test_derivative() {
+        let d = derivative(math::sin, 1.0, Options::default()).unwrap();
+        assert!(d.converged);
+        assert!((d.value - math::cos(1.0)).abs() < 1e-10);
+
+        let d = derivative(math::exp, 10.0, Options::default()).unwrap();
+        assert!((d.value - math::exp(10.0)).abs() / math::exp(10.0) < 1e-10);
+    }
+}
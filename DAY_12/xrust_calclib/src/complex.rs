@@ -0,0 +1,311 @@
+//! Complex numbers.
+//!
+//! [`Complex`] supports the usual arithmetic, polar form, principal-branch
+//! `sqrt`, `exp`, `ln` and `pow`. The `*_real` functions take ordinary `f64`
+//! arguments but promote domain errors to complex results instead of failing,
+//! which is the opt-in counterpart to the crate-level [`sqrt`](crate::sqrt):
+//!
+//! ```
+//! use xrust_calclib::complex::{self, Complex};
+//!
+//! assert!(xrust_calclib::sqrt(-1.0).is_err());
+//! assert_eq!(complex::sqrt_real(-1.0), Complex::I);
+//! ```
+//!
+//! [`eval_complex`](crate::eval_complex) applies the same promotion to whole
+//! expressions, where `i` names the imaginary unit.
+
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::{CalcError, math};
+
+/// A complex number `re + im·i`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    /// Zero.
+    pub const ZERO: Self = Complex::new(0.0, 0.0);
+    /// One.
+    pub const ONE: Self = Complex::new(1.0, 0.0);
+    /// The imaginary unit.
+    pub const I: Self = Complex::new(0.0, 1.0);
+
+    /// Builds `re + im·i`.
+    pub const fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    /// Builds a number from its modulus and argument (in radians).
+    pub fn from_polar(r: f64, theta: f64) -> Self {
+        Complex::new(r * math::cos(theta), r * math::sin(theta))
+    }
+
+    /// Returns `(abs, arg)`.
+    pub fn to_polar(self) -> (f64, f64) {
+        (self.abs(), self.arg())
+    }
+
+    /// The modulus `|z|`.
+    pub fn abs(self) -> f64 {
+        math::hypot(self.re, self.im)
+    }
+
+    /// The argument in radians, in `(-π, π]`.
+    pub fn arg(self) -> f64 {
+        math::atan2(self.im, self.re)
+    }
+
+    /// The complex conjugate.
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// Whether the imaginary part is zero.
+    pub fn is_real(self) -> bool {
+        self.im == 0.0
+    }
+
+    /// `self / rhs`, or an error if `rhs` is zero.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, CalcError> {
+        if rhs == Complex::ZERO {
+            return Err(CalcError::DivisionByZero);
+        }
+        let d = rhs.re * rhs.re + rhs.im * rhs.im;
+        Ok(Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / d,
+            (self.im * rhs.re - self.re * rhs.im) / d,
+        ))
+    }
+
+    /// The principal square root, with a non-negative real part.
+    pub fn sqrt(self) -> Self {
+        if self.im == 0.0 {
+            return if self.re >= 0.0 {
+                Complex::new(math::sqrt(self.re), 0.0)
+            } else {
+                Complex::new(0.0, math::sqrt(-self.re))
+            };
+        }
+        let t = math::sqrt((self.abs() + self.re.abs()) / 2.0);
+        if self.re >= 0.0 {
+            Complex::new(t, self.im / (2.0 * t))
+        } else {
+            Complex::new(self.im.abs() / (2.0 * t), t.copysign(self.im))
+        }
+    }
+
+    /// `e` raised to `self`.
+    pub fn exp(self) -> Self {
+        Complex::from_polar(math::exp(self.re), self.im)
+    }
+
+    /// The principal natural logarithm; errors at zero.
+    pub fn ln(self) -> Result<Self, CalcError> {
+        if self == Complex::ZERO {
+            return Err(CalcError::Domain("Logarithm of zero is undefined"));
+        }
+        Ok(Complex::new(math::ln(self.abs()), self.arg()))
+    }
+
+    /// `self` raised to an integer power by repeated squaring, which keeps
+    /// results like `i^2 = -1` exact. Errors with [`CalcError::Overflow`]
+    /// once a part no longer fits in an `f64`.
+    pub fn powi(self, exp: i32) -> Result<Self, CalcError> {
+        let base = if exp < 0 {
+            Complex::ONE.checked_div(self)?
+        } else {
+            self
+        };
+        let finite = |z: Complex| z.re.is_finite() && z.im.is_finite();
+        let mut e = exp.unsigned_abs();
+        let (mut acc, mut sq) = (Complex::ONE, base);
+        while e > 0 {
+            if e & 1 == 1 {
+                acc = acc * sq;
+            }
+            e >>= 1;
+            if e > 0 {
+                sq = sq * sq;
+            }
+            // Past here the parts are infinite or NaN, e.g. `inf + NaN i`.
+            if !finite(acc) || !finite(sq) {
+                return Err(CalcError::Overflow);
+            }
+        }
+        Ok(acc)
+    }
+
+    /// `self` raised to a complex power, `exp(w · ln self)` on the principal branch.
+    pub fn pow(self, w: Self) -> Result<Self, CalcError> {
+        if w.is_real() && math::fract(w.re) == 0.0 && w.re.abs() <= i32::MAX as f64 {
+            return self.powi(w.re as i32);
+        }
+        if self == Complex::ZERO {
+            return if w.re > 0.0 {
+                Ok(Complex::ZERO)
+            } else {
+                Err(CalcError::DivisionByZero)
+            };
+        }
+        Ok((w * self.ln()?).exp())
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex::new(re, 0.0)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Self) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Self) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Self) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+/// Formats as `3 + 4i`, `3 - 4i`, `2i` or `3`.
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (re, im) = (self.re, self.im);
+        if im == 0.0 {
+            write!(f, "{}", re)
+        } else if re == 0.0 {
+            write!(f, "{}i", im)
+        } else if im < 0.0 {
+            write!(f, "{} - {}i", re, -im)
+        } else {
+            write!(f, "{} + {}i", re, im)
+        }
+    }
+}
+
+/// Square root of a real number, promoting negatives to an imaginary result.
+pub fn sqrt_real(x: f64) -> Complex {
+    Complex::from(x).sqrt()
+}
+
+/// Natural log of a real number; negatives promote to `ln|x| + πi`.
+pub fn ln_real(x: f64) -> Result<Complex, CalcError> {
+    Complex::from(x).ln()
+}
+
+/// `a` raised to `b`; a negative base with a fractional exponent promotes to
+/// the principal complex value.
+pub fn pow_real(a: f64, b: f64) -> Result<Complex, CalcError> {
+    Complex::from(a).pow(Complex::from(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::format;
+
+    fn close(a: Complex, b: Complex) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Complex::new(3.0, 4.0);
+        let b = Complex::new(1.0, -2.0);
+        assert_eq!(a + b, Complex::new(4.0, 2.0));
+        assert_eq!(a - b, Complex::new(2.0, 6.0));
+        assert_eq!(a * b, Complex::new(11.0, -2.0));
+        assert!(close(a.checked_div(b).unwrap(), Complex::new(-1.0, 2.0)));
+        assert_eq!(a.checked_div(Complex::ZERO), Err(CalcError::DivisionByZero));
+        assert_eq!(-a, Complex::new(-3.0, -4.0));
+        assert_eq!(a.conj(), Complex::new(3.0, -4.0));
+    }
+
+    #[test]
+    fn test_polar() {
+        let z = Complex::new(3.0, 4.0);
+        assert_eq!(z.abs(), 5.0);
+        let (r, theta) = z.to_polar();
+        assert!(close(Complex::from_polar(r, theta), z));
+        assert_eq!(Complex::new(-1.0, 0.0).arg(), core::f64::consts::PI);
+    }
+
+    #[test]
+    fn test_sqrt_exp_ln_pow() {
+        assert_eq!(sqrt_real(-1.0), Complex::I);
+        assert_eq!(sqrt_real(-4.0), Complex::new(0.0, 2.0));
+        assert!(close(Complex::new(3.0, 4.0).sqrt(), Complex::new(2.0, 1.0)));
+        assert!(close(
+            Complex::new(-3.0, -4.0).sqrt(),
+            Complex::new(1.0, -2.0)
+        ));
+
+        // Euler: e^(iπ) = -1
+        let e_ipi = Complex::new(0.0, core::f64::consts::PI).exp();
+        assert!(close(e_ipi, Complex::new(-1.0, 0.0)));
+        assert!(close(
+            ln_real(-1.0).unwrap(),
+            Complex::new(0.0, core::f64::consts::PI)
+        ));
+        assert!(Complex::ZERO.ln().is_err());
+
+        assert_eq!(Complex::I.powi(2), Ok(Complex::new(-1.0, 0.0)));
+        assert_eq!(Complex::I.powi(-1), Ok(Complex::new(0.0, -1.0)));
+        assert_eq!(Complex::from(2.0).powi(2000), Err(CalcError::Overflow));
+        assert_eq!(Complex::new(1.0, 1.0).powi(5000), Err(CalcError::Overflow));
+        assert_eq!(Complex::from(2.0).powi(-2000), Ok(Complex::ZERO));
+        assert!(close(
+            pow_real(-8.0, 1.0 / 3.0).unwrap(),
+            Complex::new(1.0, math::sqrt(3.0))
+        ));
+        // i^i is real: e^(-π/2)
+        let ii = Complex::I.pow(Complex::I).unwrap();
+        assert!(close(
+            ii,
+            Complex::new(math::exp(-core::f64::consts::FRAC_PI_2), 0.0)
+        ));
+        assert_eq!(
+            Complex::ZERO.pow(Complex::new(-0.5, 0.0)),
+            Err(CalcError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Complex::new(3.0, -4.0)), "3 - 4i");
+        assert_eq!(format!("{}", Complex::new(1.5, 2.0)), "1.5 + 2i");
+        assert_eq!(format!("{}", Complex::I), "1i");
+        assert_eq!(format!("{}", Complex::from(7.0)), "7");
+    }
+}
@@ -0,0 +1,73 @@
+//! The error type shared by every fallible calculator function.
+
+use core::fmt;
+
+/// Why a calculation could not produce a result.
+///
+/// The `Display` text of each variant matches the `String` message the
+/// function returned in 0.1.x, so logging and user-facing output are unchanged.
+/// New variants may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CalcError {
+    /// The divisor of a division was zero.
+    DivisionByZero,
+    /// The divisor of a modulo was zero.
+    ModuloByZero,
+    /// Square root of a negative number.
+    NegativeSqrt,
+    /// Factorial of a negative number.
+    NegativeFactorial,
+    /// Factorial of a value with a fractional part.
+    NonIntegerFactorial,
+    /// The result does not fit in the output type.
+    Overflow,
+    /// An argument lies outside the domain of the function.
+    Domain(&'static str),
+    /// A function over a data set was given no data.
+    EmptyInput,
+    /// Paired data sets have different lengths.
+    LengthMismatch,
+    /// A matrix has no inverse (its determinant is zero).
+    SingularMatrix,
+    /// Matrix or vector dimensions do not fit the operation.
+    ShapeMismatch,
+    /// Quantities with different physical dimensions were combined.
+    DimensionMismatch,
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalcError::DivisionByZero => f.write_str("Division by zero is not allowed"),
+            CalcError::ModuloByZero => f.write_str("Modulo by zero is not allowed"),
+            CalcError::NegativeSqrt => {
+                f.write_str("Cannot calculate square root of a negative number")
+            }
+            CalcError::NegativeFactorial => {
+                f.write_str("Cannot calculate factorial of a negative number")
+            }
+            CalcError::NonIntegerFactorial => {
+                f.write_str("Factorial is not defined for non-integer values")
+            }
+            CalcError::Overflow => f.write_str("Result is too large to represent"),
+            CalcError::Domain(msg) => f.write_str(msg),
+            CalcError::EmptyInput => f.write_str("Input contains no values"),
+            CalcError::LengthMismatch => f.write_str("Inputs have different lengths"),
+            CalcError::SingularMatrix => f.write_str("Matrix is singular"),
+            CalcError::ShapeMismatch => f.write_str("Matrix dimensions do not match"),
+            CalcError::DimensionMismatch => f.write_str("Units have incompatible dimensions"),
+        }
+    }
+}
+
+impl core::error::Error for CalcError {}
+
+/// Eases migration from the 0.1.x `Result<f64, String>` signatures: `?` keeps
+/// working inside functions that still return `String` errors.
+#[cfg(feature = "alloc")]
+impl From<CalcError> for alloc::string::String {
+    fn from(err: CalcError) -> alloc::string::String {
+        alloc::string::ToString::to_string(&err)
+    }
+}
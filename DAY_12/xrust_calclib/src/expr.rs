@@ -0,0 +1,1223 @@
+//! Infix expression parsing and evaluation on top of the calculator functions.
+//!
+//! Expressions such as `sqrt(9) + 2^3 * fact(4)` are tokenized, parsed into an
+//! [`Expr`] tree with the usual precedence rules and then evaluated by calling
+//! into [`add`](crate::add), [`div`](crate::div), [`fact`](crate::fact) and
+//! friends. Every error carries the 1-based column of the offending token.
+//!
+//! Parsing and evaluation recurse over the tree, so an expression may nest at
+//! most [`MAX_DEPTH`] levels deep; anything deeper is a syntax error rather
+//! than a stack overflow.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::units::{self, Quantity};
+use crate::{AngleMode, CalcError, Complex, Rational};
+
+/// The deepest an expression may nest. Parentheses, unary signs, function
+/// calls, `^` and every operator in a chain such as `1 + 2 + 3` each count
+/// one level.
+pub const MAX_DEPTH: usize = 256;
+
+/// What went wrong while parsing or evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalErrorKind {
+    /// The input could not be tokenized or parsed, or named an unknown function.
+    Syntax(String),
+    /// A calculator function rejected its operands.
+    Calc(CalcError),
+}
+
+/// An error produced while parsing or evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalError {
+    /// 1-based column (in characters) of the token that caused the error.
+    pub column: usize,
+    /// The underlying cause.
+    pub kind: EvalErrorKind,
+}
+
+impl EvalError {
+    pub(crate) fn new(column: usize, message: impl Into<String>) -> Self {
+        EvalError {
+            column,
+            kind: EvalErrorKind::Syntax(message.into()),
+        }
+    }
+
+    pub(crate) fn calc(column: usize, err: CalcError) -> Self {
+        EvalError {
+            column,
+            kind: EvalErrorKind::Calc(err),
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            EvalErrorKind::Syntax(msg) => write!(f, "{} (column {})", msg, self.column),
+            EvalErrorKind::Calc(err) => write!(f, "{} (column {})", err, self.column),
+        }
+    }
+}
+
+impl core::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match &self.kind {
+            EvalErrorKind::Calc(err) => Some(err),
+            EvalErrorKind::Syntax(_) => None,
+        }
+    }
+}
+
+/// A binary operator appearing in an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+impl BinOp {
+    /// The operator as it is written in source text.
+    pub fn symbol(self) -> char {
+        match self {
+            BinOp::Add => '+',
+            BinOp::Sub => '-',
+            BinOp::Mul => '*',
+            BinOp::Div => '/',
+            BinOp::Rem => '%',
+            BinOp::Pow => '^',
+        }
+    }
+
+    /// Applies the operator to two real numbers using the crate functions.
+    pub(crate) fn apply(self, a: f64, b: f64) -> Result<f64, CalcError> {
+        match self {
+            BinOp::Add => Ok(crate::add(a, b)),
+            BinOp::Sub => Ok(crate::sub(a, b)),
+            BinOp::Mul => Ok(crate::mul(a, b)),
+            BinOp::Div => crate::div(a, b),
+            BinOp::Rem => crate::mode(a, b),
+            BinOp::Pow => Ok(crate::pow(a, b)),
+        }
+    }
+}
+
+/// A parsed expression tree.
+///
+/// Nodes that can fail at evaluation time remember the column of their
+/// operator or function name so the error can point back at the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A numeric literal.
+    Num(f64),
+    /// A decimal literal that does not survive the trip through `f64`, such
+    /// as `9007199254740993` (which rounds to `...992`) or `1e400`: `value` is
+    /// the nearest `f64` and `text` the literal as written, which
+    /// [`Expr::eval_exact`] reads instead. Other literals are [`Expr::Num`].
+    Decimal { value: f64, text: String },
+    /// Unary minus.
+    Neg(Box<Expr>),
+    /// A binary operation.
+    Binary {
+        op: BinOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        column: usize,
+    },
+    /// A bare identifier such as `x`.
+    Var { name: String, column: usize },
+    /// A function call such as `sqrt(9)`.
+    Call {
+        name: String,
+        args: Vec<Expr>,
+        column: usize,
+    },
+    /// A value followed by a unit symbol, such as `3 ms`.
+    WithUnit {
+        value: Box<Expr>,
+        unit: String,
+        column: usize,
+    },
+    /// A conversion of the whole expression, such as `50 Hz -> ms`.
+    Convert {
+        value: Box<Expr>,
+        unit: String,
+        column: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(f64),
+    Decimal(f64, String),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+    Arrow,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    column: usize,
+}
+
+/// Whether the shortest spelling of `value` is the decimal `text`, so exact
+/// evaluation loses nothing by starting from the `f64`.
+fn spells_exactly(text: &str, value: f64) -> bool {
+    matches!(
+        (text.parse::<Rational>(), Rational::from_f64(value)),
+        (Ok(written), Ok(nearest)) if written == nearest
+    )
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, EvalError> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // Integer literals in another base: `0xFF`, `0b1010`, `0o17`.
+        if c == '0' && matches!(chars.get(i + 1), Some('x' | 'X' | 'b' | 'B' | 'o' | 'O')) {
+            let start = i;
+            i += 2;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = crate::programmer::parse_int_f64(&text)
+                .map_err(|_| EvalError::new(column, format!("Invalid number '{}'", text)))?;
+            tokens.push(Token {
+                tok: Tok::Num(value),
+                column,
+            });
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Only treat `e` as an exponent marker when digits follow it.
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| EvalError::new(column, format!("Invalid number '{}'", text)))?;
+            let tok = if spells_exactly(&text, value) {
+                Tok::Num(value)
+            } else {
+                Tok::Decimal(value, text)
+            };
+            tokens.push(Token { tok, column });
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token {
+                tok: Tok::Ident(chars[start..i].iter().collect()),
+                column,
+            });
+            continue;
+        }
+
+        if c == '-' && chars.get(i + 1) == Some(&'>') {
+            tokens.push(Token {
+                tok: Tok::Arrow,
+                column,
+            });
+            i += 2;
+            continue;
+        }
+
+        let tok = match c {
+            '+' | '-' | '*' | '/' | '%' | '^' => Tok::Op(c),
+            '(' => Tok::LParen,
+            ')' => Tok::RParen,
+            ',' => Tok::Comma,
+            _ => {
+                return Err(EvalError::new(
+                    column,
+                    format!("Unexpected character '{}'", c),
+                ));
+            }
+        };
+        tokens.push(Token { tok, column });
+        i += 1;
+    }
+
+    tokens.push(Token {
+        tok: Tok::End,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    /// Goes one level deeper, failing at the token in `column` past
+    /// [`MAX_DEPTH`]. Callers restore `depth` when they return.
+    fn descend(&mut self, column: usize) -> Result<(), EvalError> {
+        if self.depth == MAX_DEPTH {
+            return Err(EvalError::new(column, "Expression is nested too deeply"));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, tok: Tok, what: &str) -> Result<Token, EvalError> {
+        let token = self.next();
+        if token.tok == tok {
+            Ok(token)
+        } else {
+            Err(unexpected(&token, what))
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr, EvalError> {
+        let depth = self.depth;
+        let mut lhs = self.term()?;
+        while let Tok::Op(c @ ('+' | '-')) = self.peek().tok {
+            let column = self.next().column;
+            // Each operator makes the left-leaning tree one level deeper.
+            self.descend(column)?;
+            let rhs = self.term()?;
+            let op = if c == '+' { BinOp::Add } else { BinOp::Sub };
+            lhs = binary(op, lhs, rhs, column);
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    // term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Expr, EvalError> {
+        let depth = self.depth;
+        let mut lhs = self.unary()?;
+        while let Tok::Op(c @ ('*' | '/' | '%')) = self.peek().tok {
+            let column = self.next().column;
+            self.descend(column)?;
+            let rhs = self.unary()?;
+            let op = match c {
+                '*' => BinOp::Mul,
+                '/' => BinOp::Div,
+                _ => BinOp::Rem,
+            };
+            lhs = binary(op, lhs, rhs, column);
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | '+' unary | power
+    fn unary(&mut self) -> Result<Expr, EvalError> {
+        // Every recursive rule passes through here.
+        self.descend(self.peek().column)?;
+        let expr = match self.peek().tok {
+            Tok::Op('-') => {
+                self.next();
+                Expr::Neg(Box::new(self.unary()?))
+            }
+            Tok::Op('+') => {
+                self.next();
+                self.unary()?
+            }
+            _ => self.power()?,
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    // power := primary unit? ('^' unary)?   -- right associative, binds tighter than unary minus
+    fn power(&mut self) -> Result<Expr, EvalError> {
+        let base = self.primary()?;
+        let base = self.unit_suffix(base)?;
+        if let Tok::Op('^') = self.peek().tok {
+            let column = self.next().column;
+            let exp = self.unary()?;
+            return Ok(binary(BinOp::Pow, base, exp, column));
+        }
+        Ok(base)
+    }
+
+    // unit := a unit symbol from `units::UNITS` not followed by '(', with an optional '^' unary
+    fn unit_suffix(&mut self, value: Expr) -> Result<Expr, EvalError> {
+        let Tok::Ident(name) = &self.peek().tok else {
+            return Ok(value);
+        };
+        let is_call = self.tokens.get(self.pos + 1).map(|t| &t.tok) == Some(&Tok::LParen);
+        if units::unit(name).is_none() || is_call {
+            return Ok(value);
+        }
+        let token = self.next();
+        let Tok::Ident(unit) = token.tok else {
+            unreachable!("peeked an identifier")
+        };
+        // `16 m^2` is sixteen square metres, not (16 m) squared.
+        if let Tok::Op('^') = self.peek().tok {
+            let column = self.next().column;
+            let exp = self.unary()?;
+            let unit = Expr::Var {
+                name: unit,
+                column: token.column,
+            };
+            return Ok(binary(
+                BinOp::Mul,
+                value,
+                binary(BinOp::Pow, unit, exp, column),
+                token.column,
+            ));
+        }
+        Ok(Expr::WithUnit {
+            value: Box::new(value),
+            unit,
+            column: token.column,
+        })
+    }
+
+    // primary := number | ident | ident '(' args ')' | '(' expr ')'
+    fn primary(&mut self) -> Result<Expr, EvalError> {
+        let token = self.next();
+        match token.tok {
+            Tok::Num(n) => Ok(Expr::Num(n)),
+            Tok::Decimal(value, text) => Ok(Expr::Decimal { value, text }),
+            Tok::LParen => {
+                let inner = self.expr()?;
+                self.expect(Tok::RParen, "')'")?;
+                Ok(inner)
+            }
+            Tok::Ident(name) => {
+                if self.peek().tok != Tok::LParen {
+                    return Ok(Expr::Var {
+                        name,
+                        column: token.column,
+                    });
+                }
+                self.next();
+                let mut args = Vec::new();
+                if self.peek().tok != Tok::RParen {
+                    args.push(self.expr()?);
+                    while self.peek().tok == Tok::Comma {
+                        self.next();
+                        args.push(self.expr()?);
+                    }
+                }
+                self.expect(Tok::RParen, "')' or ','")?;
+                Ok(Expr::Call {
+                    name,
+                    args,
+                    column: token.column,
+                })
+            }
+            _ => Err(unexpected(&token, "a number, name or '('")),
+        }
+    }
+}
+
+pub(crate) fn binary(op: BinOp, lhs: Expr, rhs: Expr, column: usize) -> Expr {
+    Expr::Binary {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+        column,
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> EvalError {
+    let found = match &token.tok {
+        Tok::Num(n) => format!("number {}", n),
+        Tok::Decimal(_, text) => format!("number {}", text),
+        Tok::Ident(name) => format!("'{}'", name),
+        Tok::Op(c) => format!("'{}'", c),
+        Tok::LParen => "'('".into(),
+        Tok::RParen => "')'".into(),
+        Tok::Comma => "','".into(),
+        Tok::Arrow => "'->'".into(),
+        Tok::End => "end of input".into(),
+    };
+    EvalError::new(
+        token.column,
+        format!("Expected {}, found {}", expected, found),
+    )
+}
+
+/// Parses an infix expression into an [`Expr`] tree without evaluating it.
+pub fn parse(src: &str) -> Result<Expr, EvalError> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        depth: 0,
+    };
+    let mut expr = parser.expr()?;
+    // convert := expr '->' ident   -- only at the top level
+    if parser.peek().tok == Tok::Arrow {
+        let arrow = parser.next();
+        let target = parser.next();
+        let Tok::Ident(unit) = target.tok else {
+            return Err(unexpected(&target, "a unit after '->'"));
+        };
+        expr = Expr::Convert {
+            value: Box::new(expr),
+            unit,
+            column: arrow.column,
+        };
+    }
+    let rest = parser.next();
+    if rest.tok != Tok::End {
+        return Err(unexpected(&rest, "an operator or end of input"));
+    }
+    Ok(expr)
+}
+
+pub(crate) fn unknown_identifier(name: &str, column: usize) -> EvalError {
+    EvalError::new(column, format!("Unknown identifier '{}'", name))
+}
+
+pub(crate) fn units_unsupported(column: usize) -> EvalError {
+    EvalError::new(column, "Units are only supported by eval_units")
+}
+
+/// A function from the real function table, resolved once by name.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Function {
+    Unary(fn(f64) -> Result<f64, CalcError>),
+    Binary(fn(f64, f64) -> Result<f64, CalcError>),
+    /// A trig function, whose angles are in the caller's [`AngleMode`].
+    #[cfg_attr(not(feature = "fancy"), allow(dead_code))]
+    Trig(fn(AngleMode, f64) -> Result<f64, CalcError>),
+    /// `atan2`.
+    #[cfg_attr(not(feature = "fancy"), allow(dead_code))]
+    Trig2(fn(AngleMode, f64, f64) -> Result<f64, CalcError>),
+}
+
+impl Function {
+    pub(crate) fn lookup(name: &str) -> Option<Function> {
+        #[cfg(feature = "fancy")]
+        use crate::scientific as sci;
+        use Function::{Binary, Unary};
+        #[cfg(feature = "fancy")]
+        use Function::{Trig, Trig2};
+
+        Some(match name {
+            "add" => Binary(|a, b| Ok(crate::add(a, b))),
+            "sub" => Binary(|a, b| Ok(crate::sub(a, b))),
+            "mul" => Binary(|a, b| Ok(crate::mul(a, b))),
+            "div" => Binary(crate::div),
+            "mode" | "mod" => Binary(crate::mode),
+            "pow" => Binary(|a, b| Ok(crate::pow(a, b))),
+            "sqrt" => Unary(crate::sqrt),
+            "abs" => Unary(|a| Ok(crate::abs(a))),
+            "fact" => Unary(crate::fact),
+            "gamma" => Unary(crate::gamma),
+            "lgamma" => Unary(crate::lgamma),
+            #[cfg(feature = "fancy")]
+            "sin" => Trig(|mode, a| Ok(mode.sin(a))),
+            #[cfg(feature = "fancy")]
+            "cos" => Trig(|mode, a| Ok(mode.cos(a))),
+            #[cfg(feature = "fancy")]
+            "tan" => Trig(AngleMode::tan),
+            #[cfg(feature = "fancy")]
+            "asin" => Trig(AngleMode::asin),
+            #[cfg(feature = "fancy")]
+            "acos" => Trig(AngleMode::acos),
+            #[cfg(feature = "fancy")]
+            "atan" => Trig(|mode, a| Ok(mode.atan(a))),
+            #[cfg(feature = "fancy")]
+            "atan2" => Trig2(|mode, y, x| Ok(mode.atan2(y, x))),
+            #[cfg(feature = "fancy")]
+            "sinh" => Unary(sci::sinh),
+            #[cfg(feature = "fancy")]
+            "cosh" => Unary(sci::cosh),
+            #[cfg(feature = "fancy")]
+            "tanh" => Unary(|a| Ok(sci::tanh(a))),
+            #[cfg(feature = "fancy")]
+            "asinh" => Unary(|a| Ok(sci::asinh(a))),
+            #[cfg(feature = "fancy")]
+            "acosh" => Unary(sci::acosh),
+            #[cfg(feature = "fancy")]
+            "atanh" => Unary(sci::atanh),
+            #[cfg(feature = "fancy")]
+            "ln" => Unary(sci::ln),
+            #[cfg(feature = "fancy")]
+            "log10" => Unary(sci::log10),
+            #[cfg(feature = "fancy")]
+            "log_b" => Binary(sci::log_b),
+            #[cfg(feature = "fancy")]
+            "exp" => Unary(sci::exp),
+            _ => return None,
+        })
+    }
+
+    /// Looks up `name` and checks it accepts `argc` arguments.
+    pub(crate) fn resolve(name: &str, argc: usize, column: usize) -> Result<Function, EvalError> {
+        let Some(function) = Function::lookup(name) else {
+            return Err(EvalError::new(
+                column,
+                format!("Unknown function '{}'", name),
+            ));
+        };
+        if argc != function.arity() {
+            return Err(EvalError::new(
+                column,
+                format!(
+                    "Function '{}' expects {} argument(s), got {}",
+                    name,
+                    function.arity(),
+                    argc
+                ),
+            ));
+        }
+        Ok(function)
+    }
+
+    pub(crate) fn arity(self) -> usize {
+        match self {
+            Function::Unary(_) | Function::Trig(_) => 1,
+            Function::Binary(_) | Function::Trig2(_) => 2,
+        }
+    }
+
+    /// Whether the result depends on the [`AngleMode`].
+    pub(crate) fn uses_angle_mode(self) -> bool {
+        matches!(self, Function::Trig(_) | Function::Trig2(_))
+    }
+
+    /// Calls the function; `args` must hold exactly [`arity`](Self::arity) values.
+    pub(crate) fn apply(self, args: &[f64], mode: AngleMode) -> Result<f64, CalcError> {
+        match self {
+            Function::Unary(f) => f(args[0]),
+            Function::Binary(f) => f(args[0], args[1]),
+            Function::Trig(f) => f(mode, args[0]),
+            Function::Trig2(f) => f(mode, args[0], args[1]),
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64], column: usize, mode: AngleMode) -> Result<f64, EvalError> {
+    Function::resolve(name, args.len(), column)?
+        .apply(args, mode)
+        .map_err(|err| EvalError::calc(column, err))
+}
+
+impl Expr {
+    /// Evaluates the tree, dispatching operators and calls to the crate
+    /// functions. Trig functions take and return radians.
+    pub fn eval(&self) -> Result<f64, EvalError> {
+        self.eval_in(AngleMode::Radians)
+    }
+
+    /// Evaluates the tree with trig functions in `mode`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "fancy")] {
+    /// use xrust_calclib::{AngleMode, expr};
+    ///
+    /// let e = expr::parse("asin(1) * 2").unwrap();
+    /// assert_eq!(e.eval_in(AngleMode::Degrees), Ok(180.0));
+    /// # }
+    /// ```
+    pub fn eval_in(&self, mode: AngleMode) -> Result<f64, EvalError> {
+        match self {
+            Expr::Num(n) | Expr::Decimal { value: n, .. } => Ok(*n),
+            Expr::Neg(inner) => Ok(-inner.eval_in(mode)?),
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => {
+                let a = lhs.eval_in(mode)?;
+                let b = rhs.eval_in(mode)?;
+                op.apply(a, b).map_err(|err| EvalError::calc(*column, err))
+            }
+            Expr::Var { name, column } => Err(unknown_identifier(name, *column)),
+            Expr::Call { name, args, column } => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.eval_in(mode))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(name, &values, *column, mode)
+            }
+            Expr::WithUnit { column, .. } | Expr::Convert { column, .. } => {
+                Err(units_unsupported(*column))
+            }
+        }
+    }
+}
+
+fn call_exact(name: &str, args: &[Rational], column: usize) -> Result<Rational, EvalError> {
+    let result = match (name, args) {
+        ("add", [a, b]) => a.checked_add(*b),
+        ("sub", [a, b]) => a.checked_sub(*b),
+        ("mul", [a, b]) => a.checked_mul(*b),
+        ("div", [a, b]) => a.checked_div(*b),
+        ("mode" | "mod", [a, b]) => a.checked_rem(*b),
+        ("pow", [a, b]) => crate::numeric::pow(*a, *b),
+        ("sqrt", [a]) => a.sqrt(),
+        ("abs", [a]) => a.checked_abs(),
+        ("fact", [a]) => crate::numeric::fact(*a),
+        _ => {
+            // Reuse the floating-point table to tell unknown names and bad
+            // arities apart from functions that simply have no exact form.
+            let probe = [0.0; 2];
+            return match call(
+                name,
+                &probe[..args.len().min(2)],
+                column,
+                AngleMode::Radians,
+            ) {
+                Err(
+                    err @ EvalError {
+                        kind: EvalErrorKind::Syntax(_),
+                        ..
+                    },
+                ) if args.len() <= 2 => Err(err),
+                _ => Err(EvalError::calc(
+                    column,
+                    CalcError::Domain("Function has no exact rational result"),
+                )),
+            };
+        }
+    };
+    result.map_err(|err| EvalError::calc(column, err))
+}
+
+impl Expr {
+    /// Evaluates the tree exactly over [`Rational`] numbers.
+    ///
+    /// Parsed literals are taken as written, so `2.3` is `23/10` and
+    /// `9007199254740993` keeps its last digit; [`Expr::Num`] nodes built
+    /// in code are read at their shortest decimal spelling. Powers need
+    /// integer exponents and only functions with exact rational results are
+    /// allowed. Errors in a literal are reported at the column of the
+    /// operator or call that uses it.
+    pub fn eval_exact(&self) -> Result<Rational, EvalError> {
+        self.exact(1)
+    }
+
+    fn exact(&self, column: usize) -> Result<Rational, EvalError> {
+        let at = |column: usize| move |err| EvalError::calc(column, err);
+        match self {
+            Expr::Num(n) => Rational::from_f64(*n).map_err(at(column)),
+            Expr::Decimal { text, .. } => text.parse().map_err(at(column)),
+            Expr::Neg(inner) => inner.exact(column)?.checked_neg().map_err(at(column)),
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => {
+                let a = lhs.exact(*column)?;
+                let b = rhs.exact(*column)?;
+                let result = match op {
+                    BinOp::Add => a.checked_add(b),
+                    BinOp::Sub => a.checked_sub(b),
+                    BinOp::Mul => a.checked_mul(b),
+                    BinOp::Div => a.checked_div(b),
+                    BinOp::Rem => a.checked_rem(b),
+                    BinOp::Pow => crate::numeric::pow(a, b),
+                };
+                result.map_err(at(*column))
+            }
+            Expr::Var { name, column } => Err(unknown_identifier(name, *column)),
+            Expr::Call { name, args, column } => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.exact(*column))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call_exact(name, &values, *column)
+            }
+            Expr::WithUnit { column, .. } | Expr::Convert { column, .. } => {
+                Err(units_unsupported(*column))
+            }
+        }
+    }
+}
+
+fn call_complex(name: &str, args: &[Complex], column: usize) -> Result<Complex, EvalError> {
+    let result = match (name, args) {
+        ("add", [a, b]) => Ok(*a + *b),
+        ("sub", [a, b]) => Ok(*a - *b),
+        ("mul", [a, b]) => Ok(*a * *b),
+        ("div", [a, b]) => a.checked_div(*b),
+        ("pow", [a, b]) => a.pow(*b),
+        ("sqrt", [a]) => Ok(a.sqrt()),
+        ("abs", [a]) => Ok(Complex::from(a.abs())),
+        ("arg", [a]) => Ok(Complex::from(a.arg())),
+        ("re", [a]) => Ok(Complex::from(a.re)),
+        ("im", [a]) => Ok(Complex::from(a.im)),
+        ("conj", [a]) => Ok(a.conj()),
+        ("exp", [a]) => Ok(a.exp()),
+        ("ln", [a]) => a.ln(),
+        _ => {
+            // Anything else is evaluated by the real function table, which
+            // also produces the unknown-name and arity errors.
+            let mut real = Vec::with_capacity(args.len());
+            for arg in args {
+                if !arg.is_real() {
+                    return Err(EvalError::calc(
+                        column,
+                        CalcError::Domain("Function is only defined for real arguments"),
+                    ));
+                }
+                real.push(arg.re);
+            }
+            return call(name, &real, column, AngleMode::Radians).map(Complex::from);
+        }
+    };
+    result.map_err(|err| EvalError::calc(column, err))
+}
+
+impl Expr {
+    /// Evaluates the tree over [`Complex`] numbers.
+    ///
+    /// The identifier `i` is the imaginary unit, and operations whose real
+    /// versions fail with a domain error, such as `sqrt(-1)`, `ln(-1)` or
+    /// `(-8)^(1/3)`, return their principal complex value instead.
+    pub fn eval_complex(&self) -> Result<Complex, EvalError> {
+        match self {
+            Expr::Num(n) | Expr::Decimal { value: n, .. } => Ok(Complex::from(*n)),
+            // Subtract rather than negate so `-8` keeps a +0 imaginary part and
+            // stays on the principal branch.
+            Expr::Neg(inner) => Ok(Complex::ZERO - inner.eval_complex()?),
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => {
+                let a = lhs.eval_complex()?;
+                let b = rhs.eval_complex()?;
+                let result = match op {
+                    BinOp::Add => Ok(a + b),
+                    BinOp::Sub => Ok(a - b),
+                    BinOp::Mul => Ok(a * b),
+                    BinOp::Div => a.checked_div(b),
+                    BinOp::Rem if a.is_real() && b.is_real() => {
+                        crate::mode(a.re, b.re).map(Complex::from)
+                    }
+                    BinOp::Rem => Err(CalcError::Domain("Modulo is only defined for real numbers")),
+                    BinOp::Pow => a.pow(b),
+                };
+                result.map_err(|err| EvalError::calc(*column, err))
+            }
+            Expr::Var { name, .. } if name == "i" => Ok(Complex::I),
+            Expr::Var { name, column } => Err(unknown_identifier(name, *column)),
+            Expr::Call { name, args, column } => {
+                let values = args
+                    .iter()
+                    .map(Expr::eval_complex)
+                    .collect::<Result<Vec<_>, _>>()?;
+                call_complex(name, &values, *column)
+            }
+            Expr::WithUnit { column, .. } | Expr::Convert { column, .. } => {
+                Err(units_unsupported(*column))
+            }
+        }
+    }
+}
+
+fn call_units(name: &str, args: &[Quantity], column: usize) -> Result<Quantity, EvalError> {
+    let result = match (name, args) {
+        ("sqrt", [a]) => a.sqrt(),
+        ("abs", [a]) if a.si_value() < 0.0 => Ok(-*a),
+        ("abs", [a]) => Ok(*a),
+        _ => {
+            // Every other function works on plain numbers only.
+            let mut plain = Vec::with_capacity(args.len());
+            for arg in args {
+                if !arg.dimension().is_none() {
+                    return Err(EvalError::calc(column, CalcError::DimensionMismatch));
+                }
+                plain.push(arg.si_value());
+            }
+            return call(name, &plain, column, AngleMode::Radians).map(Quantity::number);
+        }
+    };
+    result.map_err(|err| EvalError::calc(column, err))
+}
+
+impl Expr {
+    /// Evaluates the tree over dimension-checked [`Quantity`] values.
+    ///
+    /// Unit symbols from [`units::UNITS`] may follow any operand (`3 ms`) or
+    /// stand alone as one of that unit (`100 m / s`), and a trailing `-> unit`
+    /// converts the result. Adding mismatched dimensions, or passing a
+    /// quantity with units to a function other than `sqrt` and `abs`, is a
+    /// [`CalcError::DimensionMismatch`] at the operator or call.
+    pub fn eval_units(&self) -> Result<Quantity, EvalError> {
+        match self {
+            Expr::Num(n) | Expr::Decimal { value: n, .. } => Ok(Quantity::number(*n)),
+            Expr::Neg(inner) => Ok(-inner.eval_units()?),
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => {
+                let a = lhs.eval_units()?;
+                let b = rhs.eval_units()?;
+                let result = match op {
+                    BinOp::Add => a.checked_add(b),
+                    BinOp::Sub => a.checked_sub(b),
+                    BinOp::Mul => a.checked_mul(b),
+                    BinOp::Div => a.checked_div(b),
+                    BinOp::Rem => a.checked_rem(b),
+                    BinOp::Pow if !b.dimension().is_none() => Err(CalcError::DimensionMismatch),
+                    BinOp::Pow if a.dimension().is_none() => {
+                        Ok(Quantity::number(crate::pow(a.si_value(), b.si_value())))
+                    }
+                    BinOp::Pow => {
+                        let n = b.si_value();
+                        if crate::math::fract(n) != 0.0 || n.abs() > i32::MAX as f64 {
+                            Err(CalcError::Domain(
+                                "Quantities with units need integer exponents",
+                            ))
+                        } else {
+                            a.checked_powi(n as i32)
+                        }
+                    }
+                };
+                result.map_err(|err| EvalError::calc(*column, err))
+            }
+            Expr::Var { name, column } => units::unit(name)
+                .map(|unit| Quantity::of(1.0, unit))
+                .ok_or_else(|| unknown_identifier(name, *column)),
+            Expr::Call { name, args, column } => {
+                let values = args
+                    .iter()
+                    .map(Expr::eval_units)
+                    .collect::<Result<Vec<_>, _>>()?;
+                call_units(name, &values, *column)
+            }
+            Expr::WithUnit {
+                value,
+                unit,
+                column,
+            } => {
+                let q = value.eval_units()?;
+                if !q.dimension().is_none() {
+                    return Err(EvalError::calc(*column, CalcError::DimensionMismatch));
+                }
+                let unit = units::unit(unit).ok_or_else(|| unknown_unit(unit, *column))?;
+                Ok(Quantity::of(q.si_value(), unit))
+            }
+            Expr::Convert {
+                value,
+                unit,
+                column,
+            } => {
+                let q = value.eval_units()?;
+                if units::unit(unit).is_none() {
+                    return Err(unknown_unit(unit, *column));
+                }
+                q.to(unit).map_err(|err| EvalError::calc(*column, err))
+            }
+        }
+    }
+}
+
+fn unknown_unit(name: &str, column: usize) -> EvalError {
+    EvalError::new(column, format!("Unknown unit '{}'", name))
+}
+
+/// Parses and evaluates an infix expression with physical units.
+///
+/// ```
+/// use xrust_calclib::eval_units;
+///
+/// assert_eq!(eval_units("3 ms + 200 us").unwrap().to_string(), "3.2 ms");
+/// assert_eq!(eval_units("50 Hz -> ms").unwrap().to_string(), "20 ms");
+/// assert!(eval_units("3 ms + 2 m").is_err());
+/// ```
+pub fn eval_units(src: &str) -> Result<Quantity, EvalError> {
+    parse(src)?.eval_units()
+}
+
+/// Parses and evaluates an infix expression over complex numbers, promoting
+/// real domain errors to complex results.
+///
+/// ```
+/// use xrust_calclib::{Complex, eval_complex};
+///
+/// assert_eq!(eval_complex("sqrt(-4) + 1"), Ok(Complex::new(1.0, 2.0)));
+/// assert_eq!(eval_complex("(2 + 3*i) * (2 - 3*i)"), Ok(Complex::from(13.0)));
+/// ```
+pub fn eval_complex(src: &str) -> Result<Complex, EvalError> {
+    parse(src)?.eval_complex()
+}
+
+/// Parses and evaluates an infix expression exactly, as a [`Rational`].
+///
+/// ```
+/// use xrust_calclib::{Rational, eval_exact};
+///
+/// assert_eq!(eval_exact("7 - 2.3"), Ok(Rational::new(47, 10).unwrap()));
+/// assert_eq!(eval_exact("1/3 + 1/6").unwrap().to_string(), "1/2");
+/// ```
+pub fn eval_exact(src: &str) -> Result<Rational, EvalError> {
+    parse(src)?.eval_exact()
+}
+
+/// Parses and evaluates an infix expression.
+///
+/// Supports `+ - * / % ^`, parentheses, unary minus and calls to the crate
+/// functions (`add`, `sub`, `mul`, `div`, `mode`, `pow`, `sqrt`, `abs`, `fact`,
+/// `gamma`, `lgamma`). With the `fancy` feature the [`scientific`](crate::scientific)
+/// functions are available too, with angles in radians; see [`Expr::eval_in`]
+/// and [`Session`](crate::Session) for degrees and gradians.
+///
+/// ```
+/// assert_eq!(xrust_calclib::eval("sqrt(9) + 2^3 * fact(4)"), Ok(195.0));
+/// ```
+pub fn eval(src: &str) -> Result<f64, EvalError> {
+    parse(src)?.eval()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn test_precedence_and_calls() {
+        assert_eq!(eval("sqrt(9) + 2^3 * fact(4)"), Ok(195.0));
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(eval("10 % 4 - 8 / 2"), Ok(-2.0));
+        assert_eq!(eval("pow(2, 10) + abs(-1.5e1)"), Ok(1039.0));
+    }
+
+    #[test]
+    fn test_unit_evaluation() {
+        let pulse = eval_units("1 ms + 500 us").unwrap();
+        assert_eq!(pulse.to_string(), "1.5 ms");
+        assert_eq!(eval_units("50 Hz -> ms").unwrap().value(), 20.0);
+        assert_eq!(eval_units("2 * 3 ms -> us").unwrap().to_string(), "6000 us");
+        assert_eq!(eval_units("100 m / 10 s").unwrap().to_string(), "10 m/s");
+        assert_eq!(eval_units("(3 m)^2 -> m").unwrap_err().column, 9);
+        assert_eq!(eval_units("sqrt(16 m^2) -> cm").unwrap().value(), 400.0);
+        assert_eq!(eval_units("(4 m)^2").unwrap().to_string(), "16 m^2");
+        let fahrenheit = eval_units("-40 degC -> degF").unwrap();
+        assert!((fahrenheit.value() + 40.0).abs() < 1e-9);
+        let warmer = eval_units("20 degC + 5 degC").unwrap();
+        assert!((warmer.value() - 25.0).abs() < 1e-9);
+        let cooler = eval_units("30 degC - 20 degC").unwrap();
+        assert!((cooler.value() - 10.0).abs() < 1e-9);
+        let cooler = eval_units("(30 degC - 20 degC) -> K").unwrap();
+        assert!((cooler.value() - 10.0).abs() < 1e-9);
+        let warmer = eval_units("5 K + 20 degC").unwrap();
+        assert_eq!(warmer, eval_units("20 degC + 5 K").unwrap());
+        assert_eq!(warmer.unit().unwrap().symbol, "degC");
+        assert_eq!(eval_units("20 degC + 2 * 5 degC").unwrap_err().column, 13);
+        let doubled = eval_units("20 degC + 2 * (5 degC - 0 degC)").unwrap();
+        assert!((doubled.value() - 30.0).abs() < 1e-9);
+        assert_eq!(eval_units("1 s * 20 Hz").unwrap().to_string(), "20");
+        assert_eq!(eval_units("sqrt(9) + 1").unwrap().to_string(), "4");
+
+        let err = eval_units("3 ms + 2 m").unwrap_err();
+        assert_eq!(err.column, 6);
+        assert_eq!(err.kind, EvalErrorKind::Calc(CalcError::DimensionMismatch));
+        assert_eq!(eval_units("fact(3 s)").unwrap_err().column, 1);
+        assert_eq!(eval_units("1 m -> parsec").unwrap_err().column, 5);
+        assert_eq!(eval_units("x + 1").unwrap_err().column, 1);
+
+        // The plain evaluators parse units but refuse them.
+        assert_eq!(eval("3 ms").unwrap_err().column, 3);
+        assert_eq!(eval("1 -> s").unwrap_err().column, 3);
+    }
+
+    #[test]
+    fn test_base_prefixed_literals() {
+        assert_eq!(eval("0xFF + 0b1010 - 0o17"), Ok(250.0));
+        assert_eq!(eval("0x1_00 * 2"), Ok(512.0));
+        // Too many significant bits for an f64 is an error, not a rounding.
+        assert_eq!(eval("0xFFFF_FFFF_FFFF_FFFF").unwrap_err().column, 1);
+        let err = eval("1 + 0b102").unwrap_err();
+        assert_eq!(err.column, 5);
+        assert!(matches!(err.kind, EvalErrorKind::Syntax(_)));
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        assert_eq!(eval("-3 + 5"), Ok(2.0));
+        assert_eq!(eval("-2^2"), Ok(-4.0));
+        assert_eq!(eval("2 * -(3 - 1)"), Ok(-4.0));
+    }
+
+    #[test]
+    fn test_error_columns() {
+        let err = eval("1 + 2 / 0").unwrap_err();
+        assert_eq!(err.column, 7);
+        assert_eq!(err.kind, EvalErrorKind::Calc(CalcError::DivisionByZero));
+
+        assert_eq!(eval("2 + $").unwrap_err().column, 5);
+        assert_eq!(eval("(1 + 2").unwrap_err().column, 7);
+        assert_eq!(eval("1 + foo(2)").unwrap_err().column, 5);
+        assert_eq!(eval("  sqrt(-4)").unwrap_err().column, 3);
+        assert_eq!(eval("sqrt(1, 2)").unwrap_err().column, 1);
+        assert_eq!(eval("3 4").unwrap_err().column, 3);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |n: usize| "(".repeat(n) + "1" + &")".repeat(n);
+        assert_eq!(eval(&nested(MAX_DEPTH - 1)), Ok(1.0));
+        let err = eval(&nested(MAX_DEPTH)).unwrap_err();
+        assert_eq!(err.column, MAX_DEPTH + 1);
+        assert!(matches!(err.kind, EvalErrorKind::Syntax(_)));
+
+        // Hostile inputs fail cleanly instead of overflowing the stack.
+        assert!(eval(&nested(200_000)).is_err());
+        assert!(eval(&("-".repeat(200_000) + "1")).is_err());
+        assert!(eval(&("2^".repeat(200_000) + "1")).is_err());
+        assert!(eval(&("1+".repeat(200_000) + "1")).is_err());
+        assert_eq!(
+            eval(&("1+".repeat(MAX_DEPTH / 2) + "1")),
+            Ok((MAX_DEPTH / 2 + 1) as f64)
+        );
+    }
+
+    #[test]
+    fn test_exact_evaluation() {
+        let q = |s: &str| s.parse::<Rational>().unwrap();
+        assert_eq!(eval_exact("7 - 2.3"), Ok(q("4.7")));
+        assert_eq!(eval_exact("0.1 + 0.2"), Ok(q("3/10")));
+        assert_eq!(eval_exact("(2/3)^-2 * sqrt(4/9)"), Ok(q("3/2")));
+        assert_eq!(eval_exact("fact(5) / 7 % 1"), Ok(q("1/7")));
+        assert_eq!(eval_exact("-abs(-1/4)"), Ok(q("-1/4")));
+        // Literals an f64 would round are read as written.
+        assert_eq!(
+            eval_exact("9007199254740993 - 9007199254740992"),
+            Ok(Rational::ONE)
+        );
+        assert_eq!(
+            eval_exact("0.1000000000000000055511151231257827")
+                .unwrap_err()
+                .kind,
+            EvalErrorKind::Calc(CalcError::Overflow)
+        );
+        assert_eq!(
+            parse("9007199254740993 * x").unwrap().to_string(),
+            "9007199254740993 * x"
+        );
+        assert_eq!(eval("9007199254740993 - 9007199254740992"), Ok(0.0));
+
+        let err = eval_exact("1 + 2^0.5").unwrap_err();
+        assert_eq!(err.column, 6);
+        assert!(matches!(
+            err.kind,
+            EvalErrorKind::Calc(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            eval_exact("gamma(3)").unwrap_err().kind,
+            EvalErrorKind::Calc(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            eval_exact("nope(3)").unwrap_err().kind,
+            EvalErrorKind::Syntax(_)
+        ));
+        assert_eq!(
+            eval_exact("1 / (3 - 3)").unwrap_err().kind,
+            EvalErrorKind::Calc(CalcError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_complex_evaluation() {
+        assert_eq!(eval_complex("sqrt(-1)"), Ok(Complex::I));
+        assert_eq!(eval_complex("i^2"), Ok(Complex::from(-1.0)));
+        assert_eq!(eval_complex("abs(3 + 4*i)"), Ok(Complex::from(5.0)));
+        assert_eq!(eval_complex("conj(1 + i) * 2"), Ok(Complex::new(2.0, -2.0)));
+        assert_eq!(eval_complex("fact(4) + i"), Ok(Complex::new(24.0, 1.0)));
+        let cube_root = eval_complex("(-8)^(1/3)").unwrap();
+        assert!((cube_root - Complex::new(1.0, crate::math::sqrt(3.0))).abs() < 1e-12);
+
+        assert_eq!(eval_complex("1 / (i - i)").unwrap_err().column, 3);
+        assert_eq!(
+            eval_complex("(1 + i)^5000").unwrap_err().kind,
+            EvalErrorKind::Calc(CalcError::Overflow)
+        );
+        assert!(matches!(
+            eval_complex("fact(i)").unwrap_err().kind,
+            EvalErrorKind::Calc(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            eval_complex("j + 1").unwrap_err().kind,
+            EvalErrorKind::Syntax(_)
+        ));
+        // The real evaluator still rejects what the complex one promotes.
+        assert!(eval("sqrt(-1)").is_err());
+        assert_eq!(eval("1 + x").unwrap_err().column, 5);
+    }
+
+    #[cfg(feature = "fancy")]
+    #[test]
+    fn test_scientific_calls() {
+        assert_eq!(eval("log10(1000) + log_b(8, 2)"), Ok(6.0));
+        assert_eq!(eval("exp(0) * 2"), Ok(2.0));
+        let err = eval("1 + ln(-1)").unwrap_err();
+        assert_eq!(err.column, 5);
+        assert!(matches!(
+            err.kind,
+            EvalErrorKind::Calc(CalcError::Domain(_))
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+//! Time-value-of-money functions: compound interest, present and future value,
+//! annuity payments, NPV, IRR and loan amortization.
+//!
+//! [`fv`], [`pv`] and [`pmt`] follow the spreadsheet sign convention: money
+//! paid out is negative and money received is positive, so borrowing 1000 now
+//! (`pv = 1000`) means paying a negative `pmt`. Rates are per period, e.g.
+//! `0.06 / 12` for 6% a year paid monthly.
+//!
+//! ```
+//! use xrust_calclib::finance::{self, Timing};
+//!
+//! // Monthly payment on a 200 000 loan at 6% a year over 30 years.
+//! let payment = finance::pmt(0.06 / 12.0, 360.0, 200_000.0, 0.0, Timing::End).unwrap();
+//! assert_eq!(finance::round_money(payment, 2), -1199.10);
+//! ```
+
+use crate::calculus::{self, Options};
+use crate::{CalcError, math};
+
+/// When payments fall within each period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timing {
+    /// At the end of each period (an ordinary annuity, e.g. loan repayments).
+    #[default]
+    End,
+    /// At the start of each period (an annuity due, e.g. rent).
+    Begin,
+}
+
+impl Timing {
+    fn factor(self, rate: f64) -> f64 {
+        match self {
+            Timing::End => 1.0,
+            Timing::Begin => 1.0 + rate,
+        }
+    }
+}
+
+fn check_rate(rate: f64) -> Result<(), CalcError> {
+    if rate.is_finite() && rate > -1.0 {
+        Ok(())
+    } else {
+        Err(CalcError::Domain("Rate must be greater than -100%"))
+    }
+}
+
+fn check_periods(nper: f64) -> Result<(), CalcError> {
+    if nper.is_finite() && nper >= 0.0 {
+        Ok(())
+    } else {
+        Err(CalcError::Domain("Number of periods must not be negative"))
+    }
+}
+
+fn check_amounts(amounts: &[f64]) -> Result<(), CalcError> {
+    if amounts.iter().all(|x| x.is_finite()) {
+        Ok(())
+    } else {
+        Err(CalcError::Domain("Amounts must be finite"))
+    }
+}
+
+fn finite(x: f64) -> Result<f64, CalcError> {
+    if x.is_finite() {
+        Ok(x)
+    } else {
+        Err(CalcError::Overflow)
+    }
+}
+
+/// `principal` grown at `annual_rate` compounded `per_year` times a year for
+/// `years` years.
+pub fn compound_interest(
+    principal: f64,
+    annual_rate: f64,
+    per_year: u32,
+    years: f64,
+) -> Result<f64, CalcError> {
+    if per_year == 0 {
+        return Err(CalcError::Domain("Compounding frequency must be positive"));
+    }
+    let per_year = f64::from(per_year);
+    check_rate(annual_rate / per_year)?;
+    check_periods(years)?;
+    check_amounts(&[principal])?;
+    finite(principal * math::powf(1.0 + annual_rate / per_year, per_year * years))
+}
+
+/// The value after `nper` periods of an investment of `pv` plus `pmt` each
+/// period.
+pub fn fv(rate: f64, nper: f64, pmt: f64, pv: f64, when: Timing) -> Result<f64, CalcError> {
+    check_rate(rate)?;
+    check_periods(nper)?;
+    check_amounts(&[pmt, pv])?;
+    if rate == 0.0 {
+        return finite(-(pv + pmt * nper));
+    }
+    let growth = math::powf(1.0 + rate, nper);
+    finite(-(pv * growth + pmt * when.factor(rate) / rate * (growth - 1.0)))
+}
+
+/// The amount now that is equivalent to `pmt` each period for `nper` periods
+/// plus `fv` at the end.
+pub fn pv(rate: f64, nper: f64, pmt: f64, fv: f64, when: Timing) -> Result<f64, CalcError> {
+    check_rate(rate)?;
+    check_periods(nper)?;
+    check_amounts(&[pmt, fv])?;
+    if rate == 0.0 {
+        return finite(-(fv + pmt * nper));
+    }
+    let growth = math::powf(1.0 + rate, nper);
+    finite(-(fv + pmt * when.factor(rate) / rate * (growth - 1.0)) / growth)
+}
+
+/// The payment per period that pays off `pv` (leaving `fv`) in `nper` periods.
+pub fn pmt(rate: f64, nper: f64, pv: f64, fv: f64, when: Timing) -> Result<f64, CalcError> {
+    check_rate(rate)?;
+    check_periods(nper)?;
+    if nper == 0.0 {
+        return Err(CalcError::Domain("Number of periods must be positive"));
+    }
+    check_amounts(&[pv, fv])?;
+    if rate == 0.0 {
+        return finite(-(fv + pv) / nper);
+    }
+    let growth = math::powf(1.0 + rate, nper);
+    finite(-(fv + pv * growth) * rate / (when.factor(rate) * (growth - 1.0)))
+}
+
+/// Net present value of `cash_flows`, the first of which happens now (period
+/// 0) and is not discounted.
+pub fn npv(rate: f64, cash_flows: &[f64]) -> Result<f64, CalcError> {
+    check_rate(rate)?;
+    check_amounts(cash_flows)?;
+    if cash_flows.is_empty() {
+        return Err(CalcError::EmptyInput);
+    }
+    // Horner's scheme from the last flow back to period 0.
+    let discount = 1.0 / (1.0 + rate);
+    finite(
+        cash_flows
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &c| acc * discount + c),
+    )
+}
+
+/// Internal rate of return: the rate at which [`npv`] of `cash_flows` is zero.
+///
+/// Needs at least one inflow and one outflow. Flows that change sign several
+/// times can have more than one IRR; the one nearest 10% is returned.
+pub fn irr(cash_flows: &[f64]) -> Result<f64, CalcError> {
+    check_amounts(cash_flows)?;
+    if cash_flows.is_empty() {
+        return Err(CalcError::EmptyInput);
+    }
+    if !cash_flows.iter().any(|&c| c > 0.0) || !cash_flows.iter().any(|&c| c < 0.0) {
+        return Err(CalcError::Domain(
+            "IRR needs both positive and negative cash flows",
+        ));
+    }
+    // Refine every sign change on the grid and keep the root closest to a
+    // typical 10%.
+    const GRID: [f64; 14] = [
+        -0.99, -0.9, -0.5, -0.2, -0.05, 0.0, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0,
+    ];
+    let f = |r: f64| npv(r, cash_flows).unwrap_or(f64::NAN);
+    let mut bracketed = false;
+    let mut best: Option<f64> = None;
+    for w in GRID.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let (fa, fb) = (f(a), f(b));
+        if fa.is_finite() && fb.is_finite() && fa.signum() != fb.signum() {
+            bracketed = true;
+            let root = calculus::brent(f, a, b, Options::new(1e-12, 200))?;
+            if root.converged && best.is_none_or(|r| (root.value - 0.1).abs() < (r - 0.1).abs()) {
+                best = Some(root.value);
+            }
+        }
+    }
+    match best {
+        Some(rate) => Ok(rate),
+        None if bracketed => Err(CalcError::Domain("IRR did not converge")),
+        None => Err(CalcError::Domain("IRR could not be bracketed")),
+    }
+}
+
+/// One period of a loan amortization schedule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmortizationRow {
+    /// 1-based period number.
+    pub period: u32,
+    /// The total paid this period.
+    pub payment: f64,
+    /// The part of the payment that is interest.
+    pub interest: f64,
+    /// The part of the payment that reduces the balance.
+    pub principal: f64,
+    /// The balance remaining after this payment.
+    pub balance: f64,
+}
+
+/// The repayment schedule of a `principal` loan at `rate` per period over
+/// `periods` equal end-of-period payments. Amounts are positive; the last row
+/// absorbs rounding so the balance ends at exactly zero.
+#[cfg(feature = "alloc")]
+pub fn amortization(
+    principal: f64,
+    rate: f64,
+    periods: u32,
+) -> Result<alloc::vec::Vec<AmortizationRow>, CalcError> {
+    if principal < 0.0 {
+        return Err(CalcError::Domain("Principal must not be negative"));
+    }
+    let payment = -pmt(rate, f64::from(periods), principal, 0.0, Timing::End)?;
+    let mut balance = principal;
+    let mut rows = alloc::vec::Vec::new();
+    for period in 1..=periods {
+        let interest = balance * rate;
+        let mut principal_part = payment - interest;
+        if period == periods {
+            principal_part = balance;
+        }
+        balance -= principal_part;
+        rows.push(AmortizationRow {
+            period,
+            payment: principal_part + interest,
+            interest,
+            principal: principal_part,
+            balance,
+        });
+    }
+    Ok(rows)
+}
+
+/// Rounds `x` to `decimals` places the way currency is usually rounded
+/// (half away from zero), treating values that are a tie in decimal, such as
+/// `1.005`, as ties even though their binary value is slightly below.
+pub fn round_money(x: f64, decimals: u32) -> f64 {
+    math::round_decimal(x, places(decimals), math::round)
+}
+
+/// Like [`round_money`] but ties go to the even digit ("banker's rounding"),
+/// which avoids an upward bias when summing many rounded amounts.
+pub fn round_bankers(x: f64, decimals: u32) -> f64 {
+    math::round_decimal(x, places(decimals), math::round_half_even)
+}
+
+/// `decimals` as a place count; past `i32::MAX` no `f64` has digits left.
+fn places(decimals: u32) -> i32 {
+    i32::try_from(decimals).unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_time_value() {
+        assert!(close(
+            compound_interest(1000.0, 0.05, 12, 10.0).unwrap(),
+            1_647.009_497
+        ));
+        assert!(close(
+            fv(0.05 / 12.0, 120.0, -100.0, -100.0, Timing::End).unwrap(),
+            15_692.928_894
+        ));
+        assert!(close(
+            pv(0.05 / 12.0, 120.0, -100.0, 0.0, Timing::End).unwrap(),
+            9_428.135_033
+        ));
+        let p = pmt(0.06 / 12.0, 360.0, 200_000.0, 0.0, Timing::End).unwrap();
+        assert!(close(p, -1_199.101_050));
+        let due = pmt(0.06 / 12.0, 360.0, 200_000.0, 0.0, Timing::Begin).unwrap();
+        assert!(close(due, p / 1.005));
+        // Round trip: the payment exactly repays the loan.
+        assert!(close(
+            pv(0.06 / 12.0, 360.0, p, 0.0, Timing::End).unwrap(),
+            200_000.0
+        ));
+
+        assert_eq!(fv(0.0, 10.0, -100.0, -1000.0, Timing::End), Ok(2000.0));
+        assert_eq!(pmt(0.0, 4.0, 1000.0, 0.0, Timing::End), Ok(-250.0));
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(matches!(
+            pmt(-1.0, 12.0, 1000.0, 0.0, Timing::End),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            pmt(0.01, 0.0, 1000.0, 0.0, Timing::End),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            fv(0.01, -3.0, 0.0, 1.0, Timing::End),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            compound_interest(1.0, 0.05, 0, 1.0),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            pv(f64::NAN, 1.0, 0.0, 1.0, Timing::End),
+            Err(CalcError::Domain(_))
+        ));
+        assert_eq!(
+            fv(1e10, 1e10, 0.0, 1.0, Timing::End),
+            Err(CalcError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_npv_irr() {
+        let flows = [-100.0, 39.0, 59.0, 55.0, 20.0];
+        assert!(close(npv(0.1, &flows).unwrap(), 39.197_459));
+        let r = irr(&flows).unwrap();
+        assert!(close(r, 0.280_948_42));
+        assert!(npv(r, &flows).unwrap().abs() < 1e-9);
+
+        assert!(close(irr(&[-1000.0, 0.0, 0.0, 800.0]).unwrap(), -0.071_682));
+        // Two IRRs, 21% and -6%; the grid bracket of -6% has the midpoint
+        // nearer 10%, but 21% is the nearer root.
+        let two = [1.0, -2.15, 1.21 * 0.94];
+        assert!(close(irr(&two).unwrap(), 0.21));
+        assert!(npv(-0.06, &two).unwrap().abs() < 1e-12);
+        assert_eq!(npv(0.1, &[]), Err(CalcError::EmptyInput));
+        assert!(matches!(irr(&[100.0, 50.0]), Err(CalcError::Domain(_))));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_amortization() {
+        let rows = amortization(1000.0, 0.01, 12).unwrap();
+        assert_eq!(rows.len(), 12);
+        let payment = -pmt(0.01, 12.0, 1000.0, 0.0, Timing::End).unwrap();
+        assert!(close(rows[0].payment, payment));
+        assert!(close(rows[0].interest, 10.0));
+        assert!(close(rows[0].principal, payment - 10.0));
+        assert_eq!(rows[11].balance, 0.0);
+        let repaid: f64 = rows.iter().map(|r| r.principal).sum();
+        assert!(close(repaid, 1000.0));
+        assert!(rows.windows(2).all(|w| w[1].interest < w[0].interest));
+
+        assert!(matches!(
+            amortization(1000.0, 0.01, 0),
+            Err(CalcError::Domain(_))
+        ));
+    }
+
+    #[test]
+    fn test_currency_rounding() {
+        assert_eq!(round_money(1.005, 2), 1.01);
+        assert_eq!(round_money(-1.005, 2), -1.01);
+        assert_eq!(round_money(2.675, 2), 2.68);
+        assert_eq!(round_money(1_199.101_05, 2), 1199.10);
+        assert_eq!(round_bankers(0.125, 2), 0.12);
+        assert_eq!(round_bankers(0.135, 2), 0.14);
+        assert_eq!(round_bankers(2.5, 0), 2.0);
+        assert_eq!(round_money(1e300, 10), 1e300);
+        assert_eq!(round_money(1.5, 400), 1.5);
+        assert_eq!(round_bankers(1.5, u32::MAX), 1.5);
+    }
+}
@@ -0,0 +1,464 @@
+//! Fixed-point Q-format numbers for targets without an FPU.
+//!
+//! `Fixed<I, FRAC>` stores a value as a signed integer `I` scaled by
+//! `2^FRAC`, so [`Q16_16`] has 16 integer and 16 fractional bits. All
+//! arithmetic is integer-only; multiplication and division go through the
+//! next wider integer type and round to the nearest representable value.
+//!
+//! ```
+//! use xrust_calclib::fixed::Q16_16;
+//!
+//! // Servo pulse for 90° between 1600 and 4400 timer ticks, no floats.
+//! let span = Q16_16::from_int(4400 - 1600).unwrap();
+//! let ratio = Q16_16::from_int(90).unwrap().checked_div(Q16_16::from_int(180).unwrap()).unwrap();
+//! let ticks = span.checked_mul(ratio).unwrap().to_int() + 1600;
+//! assert_eq!(ticks, 3000);
+//! ```
+
+use core::fmt;
+
+use crate::{CalcError, math};
+
+/// A signed fixed-point number with `FRAC` fractional bits stored in `I`.
+///
+/// Implemented for `i8`, `i16`, `i32` and `i64` storage; `FRAC` must be less
+/// than the bit width of `I`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed<I, const FRAC: u32> {
+    bits: I,
+}
+
+/// Q8.8: 8 integer bits (including sign) and 8 fractional bits.
+pub type Q8_8 = Fixed<i16, 8>;
+/// Q1.15: a value in `[-1, 1)` with 15 fractional bits, common for DSP and PWM duty.
+pub type Q1_15 = Fixed<i16, 15>;
+/// Q16.16: 16 integer bits (including sign) and 16 fractional bits.
+pub type Q16_16 = Fixed<i32, 16>;
+/// Q32.32: 32 integer bits (including sign) and 32 fractional bits.
+pub type Q32_32 = Fixed<i64, 32>;
+
+/// Decimal digits needed to tell adjacent values with `frac` bits apart.
+fn default_digits(frac: u32) -> usize {
+    (frac as usize * 30_103).div_ceil(100_000)
+}
+
+/// Small stack buffer the decimal text is rendered into, so the same digits
+/// can be emitted through `core::fmt` or `ufmt` without allocating.
+struct DecimalBuf {
+    buf: [u8; 64],
+    len: usize,
+}
+
+impl DecimalBuf {
+    fn push(&mut self, b: u8) {
+        self.buf[self.len] = b;
+        self.len += 1;
+    }
+
+    fn push_digits(&mut self, mut n: u128, min_width: usize) {
+        let mut tmp = [0u8; 40];
+        let mut i = 0;
+        while n > 0 || i < min_width.max(1) {
+            tmp[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            i += 1;
+        }
+        while i > 0 {
+            i -= 1;
+            self.push(tmp[i]);
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        // Only ASCII digits, '-' and '.' are ever pushed.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+/// Renders `±mag / 2^frac` with `precision` fractional digits (rounded half
+/// up), or the shortest form that keeps `default_digits` when `None`.
+fn render(negative: bool, mag: u128, frac: u32, precision: Option<usize>) -> DecimalBuf {
+    let mut p = precision.unwrap_or_else(|| default_digits(frac)).min(19);
+    let half = if frac == 0 { 0 } else { 1u128 << (frac - 1) };
+    let scaled = (mag * 10u128.pow(p as u32) + half) >> frac;
+    let int = scaled / 10u128.pow(p as u32);
+    let mut fraction = scaled % 10u128.pow(p as u32);
+    if precision.is_none() {
+        while p > 0 && fraction.is_multiple_of(10) {
+            fraction /= 10;
+            p -= 1;
+        }
+    }
+    if p == 0 {
+        fraction = 0;
+    }
+
+    let mut out = DecimalBuf {
+        buf: [0; 64],
+        len: 0,
+    };
+    if negative && scaled != 0 {
+        out.push(b'-');
+    }
+    out.push_digits(int, 1);
+    if p > 0 {
+        out.push(b'.');
+        out.push_digits(fraction, p);
+    }
+    out
+}
+
+macro_rules! impl_fixed {
+    ($($t:ty => $wide:ty),* $(,)?) => {$(
+        impl<const FRAC: u32> Fixed<$t, FRAC> {
+            const CHECK: () = assert!(FRAC < <$t>::BITS, "FRAC must be less than the storage width");
+
+            /// Zero.
+            pub const ZERO: Self = Self::from_bits(0);
+            /// One. Not available for formats without an integer bit, such as [`Q1_15`].
+            pub const ONE: Self = {
+                assert!(FRAC < <$t>::BITS - 1, "one is not representable in this format");
+                Self::from_bits(1 << FRAC)
+            };
+            /// The smallest representable value.
+            pub const MIN: Self = Self::from_bits(<$t>::MIN);
+            /// The largest representable value.
+            pub const MAX: Self = Self::from_bits(<$t>::MAX);
+            /// The gap between adjacent values, `2^-FRAC`.
+            pub const DELTA: Self = Self::from_bits(1);
+
+            /// Wraps a raw scaled integer.
+            pub const fn from_bits(bits: $t) -> Self {
+                #[allow(clippy::let_unit_value)]
+                let () = Self::CHECK;
+                Fixed { bits }
+            }
+
+            /// Returns the raw scaled integer, e.g. to write into a timer register.
+            pub const fn to_bits(self) -> $t {
+                self.bits
+            }
+
+            /// Converts an integer, erroring if it does not fit the integer bits.
+            pub fn from_int(n: $t) -> Result<Self, CalcError> {
+                <$t>::try_from((n as $wide) << FRAC)
+                    .map(Self::from_bits)
+                    .map_err(|_| CalcError::Overflow)
+            }
+
+            /// The integer part, truncated towards zero.
+            pub fn to_int(self) -> $t {
+                (self.bits as $wide / (1 << FRAC)) as $t
+            }
+
+            /// Converts from `f64`, rounding to the nearest representable value.
+            pub fn from_f64(x: f64) -> Result<Self, CalcError> {
+                if x.is_nan() {
+                    return Err(CalcError::Domain("Cannot convert NaN to fixed point"));
+                }
+                let scaled = math::round(x * (1u128 << FRAC) as f64);
+                if scaled < <$t>::MIN as f64 || scaled > <$t>::MAX as f64 {
+                    return Err(CalcError::Overflow);
+                }
+                Ok(Self::from_bits(scaled as $t))
+            }
+
+            /// Converts from `f64`, clamping out-of-range values and mapping NaN to zero.
+            pub fn saturating_from_f64(x: f64) -> Self {
+                // `as` saturates and maps NaN to zero.
+                Self::from_bits(math::round(x * (1u128 << FRAC) as f64) as $t)
+            }
+
+            /// Converts to `f64`. Exact whenever `I` has at most 53 significant bits.
+            pub fn to_f64(self) -> f64 {
+                self.bits as f64 / (1u128 << FRAC) as f64
+            }
+
+            /// `self + rhs`, or [`CalcError::Overflow`].
+            pub fn checked_add(self, rhs: Self) -> Result<Self, CalcError> {
+                self.bits
+                    .checked_add(rhs.bits)
+                    .map(Self::from_bits)
+                    .ok_or(CalcError::Overflow)
+            }
+
+            /// `self + rhs`, clamped to [`MIN`](Self::MIN)..=[`MAX`](Self::MAX).
+            pub fn saturating_add(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.saturating_add(rhs.bits))
+            }
+
+            /// `self + rhs`, wrapping around on overflow.
+            pub fn wrapping_add(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.wrapping_add(rhs.bits))
+            }
+
+            /// `self - rhs`, or [`CalcError::Overflow`].
+            pub fn checked_sub(self, rhs: Self) -> Result<Self, CalcError> {
+                self.bits
+                    .checked_sub(rhs.bits)
+                    .map(Self::from_bits)
+                    .ok_or(CalcError::Overflow)
+            }
+
+            /// `self - rhs`, clamped to [`MIN`](Self::MIN)..=[`MAX`](Self::MAX).
+            pub fn saturating_sub(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.saturating_sub(rhs.bits))
+            }
+
+            /// `self - rhs`, wrapping around on overflow.
+            pub fn wrapping_sub(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.wrapping_sub(rhs.bits))
+            }
+
+            /// Full-width product, rescaled and rounded to nearest.
+            fn wide_mul(self, rhs: Self) -> $wide {
+                let product = self.bits as $wide * rhs.bits as $wide;
+                if FRAC == 0 {
+                    product
+                } else {
+                    (product + (1 << (FRAC - 1))) >> FRAC
+                }
+            }
+
+            /// `self * rhs`, or [`CalcError::Overflow`].
+            pub fn checked_mul(self, rhs: Self) -> Result<Self, CalcError> {
+                <$t>::try_from(self.wide_mul(rhs))
+                    .map(Self::from_bits)
+                    .map_err(|_| CalcError::Overflow)
+            }
+
+            /// `self * rhs`, clamped to [`MIN`](Self::MIN)..=[`MAX`](Self::MAX).
+            pub fn saturating_mul(self, rhs: Self) -> Self {
+                let wide = self.wide_mul(rhs);
+                Self::from_bits(wide.clamp(<$t>::MIN as $wide, <$t>::MAX as $wide) as $t)
+            }
+
+            /// `self * rhs`, wrapping around on overflow.
+            pub fn wrapping_mul(self, rhs: Self) -> Self {
+                Self::from_bits(self.wide_mul(rhs) as $t)
+            }
+
+            /// Full-width quotient, rounded half away from zero. `rhs` must be non-zero.
+            fn wide_div(self, rhs: Self) -> $wide {
+                let num = (self.bits as $wide) << FRAC;
+                let den = rhs.bits as $wide;
+                let (q, r) = (num / den, num % den);
+                if 2 * r.abs() >= den.abs() {
+                    q + if (num < 0) == (den < 0) { 1 } else { -1 }
+                } else {
+                    q
+                }
+            }
+
+            /// `self / rhs`, or an error for a zero divisor or overflow.
+            pub fn checked_div(self, rhs: Self) -> Result<Self, CalcError> {
+                if rhs.bits == 0 {
+                    return Err(CalcError::DivisionByZero);
+                }
+                <$t>::try_from(self.wide_div(rhs))
+                    .map(Self::from_bits)
+                    .map_err(|_| CalcError::Overflow)
+            }
+
+            /// `self / rhs`, clamped to [`MIN`](Self::MIN)..=[`MAX`](Self::MAX).
+            /// Division by zero saturates towards the sign of `self` (and `0 / 0` is zero).
+            pub fn saturating_div(self, rhs: Self) -> Self {
+                if rhs.bits == 0 {
+                    return match self.bits {
+                        0 => Self::ZERO,
+                        b if b < 0 => Self::MIN,
+                        _ => Self::MAX,
+                    };
+                }
+                let wide = self.wide_div(rhs);
+                Self::from_bits(wide.clamp(<$t>::MIN as $wide, <$t>::MAX as $wide) as $t)
+            }
+
+            /// `self / rhs`, wrapping around on overflow.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `rhs` is zero, like integer `wrapping_div`.
+            pub fn wrapping_div(self, rhs: Self) -> Self {
+                assert!(rhs.bits != 0, "attempt to divide by zero");
+                Self::from_bits(self.wide_div(rhs) as $t)
+            }
+
+            /// Square root, rounded down to the nearest representable value.
+            pub fn sqrt(self) -> Result<Self, CalcError> {
+                if self.bits < 0 {
+                    return Err(CalcError::NegativeSqrt);
+                }
+                // sqrt(bits / 2^F) = sqrt(bits * 2^F) / 2^F
+                let root = ((self.bits as $wide) << FRAC).isqrt();
+                Ok(Self::from_bits(root as $t))
+            }
+
+            /// Writes the decimal value to a `ufmt` writer without pulling in `core::fmt`.
+            #[cfg(feature = "ufmt")]
+            pub fn write_ufmt<W: ufmt_write::uWrite + ?Sized>(
+                &self,
+                w: &mut W,
+            ) -> Result<(), W::Error> {
+                w.write_str(self.render(None).as_str())
+            }
+
+            fn render(&self, precision: Option<usize>) -> DecimalBuf {
+                render(self.bits < 0, self.bits.unsigned_abs() as u128, FRAC, precision)
+            }
+        }
+
+        /// Prints the decimal value, so `uwrite!(w, "{}", x)` works like `{}`.
+        #[cfg(feature = "ufmt")]
+        impl<const FRAC: u32> ufmt::uDisplay for Fixed<$t, FRAC> {
+            fn fmt<W: ufmt::uWrite + ?Sized>(
+                &self,
+                f: &mut ufmt::Formatter<'_, W>,
+            ) -> Result<(), W::Error> {
+                f.write_str(self.render(None).as_str())
+            }
+        }
+
+        /// Also prints the decimal value: on a log line the raw bits are
+        /// rarely what you want.
+        #[cfg(feature = "ufmt")]
+        impl<const FRAC: u32> ufmt::uDebug for Fixed<$t, FRAC> {
+            fn fmt<W: ufmt::uWrite + ?Sized>(
+                &self,
+                f: &mut ufmt::Formatter<'_, W>,
+            ) -> Result<(), W::Error> {
+                ufmt::uDisplay::fmt(self, f)
+            }
+        }
+
+        impl<const FRAC: u32> fmt::Display for Fixed<$t, FRAC> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let text = self.render(f.precision());
+                match text.as_str().strip_prefix('-') {
+                    Some(digits) => f.pad_integral(false, "", digits),
+                    None => f.pad_integral(true, "", text.as_str()),
+                }
+            }
+        }
+    )*};
+}
+
+impl_fixed!(i8 => i16, i16 => i32, i32 => i64, i64 => i128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::format;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Q16_16::ONE.to_bits(), 65536);
+        assert_eq!(Q16_16::from_f64(1.5).unwrap().to_bits(), 98304);
+        assert_eq!(Q16_16::from_f64(-0.25).unwrap().to_f64(), -0.25);
+        assert_eq!(Q8_8::from_f64(200.0), Err(CalcError::Overflow));
+        assert!(matches!(
+            Q8_8::from_f64(f64::NAN),
+            Err(CalcError::Domain(_))
+        ));
+        assert_eq!(Q8_8::saturating_from_f64(200.0), Q8_8::MAX);
+        assert_eq!(Q8_8::from_int(127).unwrap().to_int(), 127);
+        assert_eq!(Q8_8::from_int(128), Err(CalcError::Overflow));
+        assert_eq!(Q16_16::from_f64(-2.75).unwrap().to_int(), -2);
+        assert_eq!(Q1_15::from_int(-1).unwrap().to_int(), -1);
+        assert_eq!(Q1_15::from_int(1), Err(CalcError::Overflow));
+        assert_eq!(Q1_15::from_f64(-0.5).unwrap().to_int(), 0);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Q16_16::from_f64(2.5).unwrap();
+        let b = Q16_16::from_f64(-1.25).unwrap();
+        assert_eq!(a.checked_add(b).unwrap().to_f64(), 1.25);
+        assert_eq!(a.checked_sub(b).unwrap().to_f64(), 3.75);
+        assert_eq!(a.checked_mul(b).unwrap().to_f64(), -3.125);
+        assert_eq!(a.checked_div(b).unwrap().to_f64(), -2.0);
+        assert_eq!(a.checked_div(Q16_16::ZERO), Err(CalcError::DivisionByZero));
+
+        // 1/3 rounds to the nearest step.
+        let third = Q16_16::ONE
+            .checked_div(Q16_16::from_int(3).unwrap())
+            .unwrap();
+        assert_eq!(third.to_bits(), 21845);
+        let two_thirds = Q16_16::from_int(2)
+            .unwrap()
+            .checked_div(Q16_16::from_int(3).unwrap());
+        assert_eq!(two_thirds.unwrap().to_bits(), 43691);
+    }
+
+    #[test]
+    fn test_overflow_variants() {
+        let big = Q8_8::from_int(100).unwrap();
+        assert_eq!(big.checked_add(big), Err(CalcError::Overflow));
+        assert_eq!(big.saturating_add(big), Q8_8::MAX);
+        assert_eq!(big.wrapping_add(big).to_f64(), -56.0);
+        assert_eq!(big.checked_mul(big), Err(CalcError::Overflow));
+        assert_eq!(big.saturating_mul(Q8_8::from_int(-2).unwrap()), Q8_8::MIN);
+        assert_eq!(
+            big.wrapping_mul(Q8_8::from_int(2).unwrap()),
+            big.wrapping_add(big)
+        );
+        assert_eq!(big.saturating_div(Q8_8::ZERO), Q8_8::MAX);
+        assert_eq!(
+            big.checked_div(Q8_8::from_f64(0.5).unwrap()),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(Q8_8::MIN.saturating_sub(Q8_8::ONE), Q8_8::MIN);
+        assert_eq!(Q8_8::MIN.wrapping_sub(Q8_8::DELTA), Q8_8::MAX);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(Q16_16::from_int(9).unwrap().sqrt().unwrap().to_f64(), 3.0);
+        let root2 = Q16_16::from_int(2).unwrap().sqrt().unwrap().to_f64();
+        assert!((root2 - core::f64::consts::SQRT_2).abs() < 1.0 / 65536.0);
+        assert_eq!(
+            Q16_16::from_int(-1).unwrap().sqrt(),
+            Err(CalcError::NegativeSqrt)
+        );
+        assert_eq!(Q1_15::from_f64(0.25).unwrap().sqrt().unwrap().to_f64(), 0.5);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Q16_16::from_f64(1.5).unwrap()), "1.5");
+        assert_eq!(format!("{}", Q16_16::from_int(-3).unwrap()), "-3");
+        assert_eq!(format!("{}", Q16_16::DELTA), "0.00002");
+        assert_eq!(
+            format!("{:.3}", Q16_16::from_f64(-2.0625).unwrap()),
+            "-2.063"
+        );
+        assert_eq!(format!("{:.2}", Q16_16::from_f64(-0.001).unwrap()), "0.00");
+        assert_eq!(format!("{:>6}", Q8_8::from_f64(0.5).unwrap()), "   0.5");
+        assert_eq!(format!("{}", Q32_32::MIN), "-2147483648");
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn test_write_ufmt() {
+        struct Buf(std::string::String);
+        impl ufmt_write::uWrite for Buf {
+            type Error = ();
+            fn write_str(&mut self, s: &str) -> Result<(), ()> {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+        let mut out = Buf(std::string::String::new());
+        Q8_8::from_f64(-12.75)
+            .unwrap()
+            .write_ufmt(&mut out)
+            .unwrap();
+        assert_eq!(out.0, "-12.75");
+
+        out.0.clear();
+        let duty = Q1_15::from_f64(0.5).unwrap();
+        ufmt::uwrite!(out, "duty={} min={:?}", duty, Q16_16::MIN).unwrap();
+        assert_eq!(out.0, "duty=0.5 min=-32768");
+    }
+}
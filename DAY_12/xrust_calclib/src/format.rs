@@ -0,0 +1,730 @@
+//! Configurable number output: fixed decimals, significant figures,
+//! scientific, engineering and SI-prefix notation, with locale separators.
+//!
+//! A [`Format`] is built once and then displays any number of values through
+//! [`Format::display`]. Nothing is allocated, so it also works on `no_std`
+//! targets. [`Format::parse`] reads every notation back in the same locale.
+//!
+//! ```
+//! use xrust_calclib::format::{Format, Locale};
+//!
+//! assert_eq!(Format::default().display(2.3 + 2.4).to_string(), "4.7");
+//! assert_eq!(Format::significant(3).display(4.699999999999999).to_string(), "4.70");
+//! assert_eq!(Format::si(2).display(4700.0).to_string(), "4.7 k");
+//! assert_eq!(Format::si(3).trim_zeros(true).display(220e-6).to_string(), "220 µ");
+//!
+//! let de = Format::fixed(2).locale(Locale::DE);
+//! assert_eq!(de.display(1234567.891).to_string(), "1.234.567,89");
+//! assert_eq!(de.parse("1.234.567,89"), Ok(1234567.89));
+//! assert_eq!(Format::default().parse("220 µ"), Ok(220e-6));
+//! ```
+
+use core::fmt::{self, Write};
+
+use crate::CalcError;
+
+/// The most significant digits worth printing for an `f64`.
+pub const MAX_DIGITS: usize = 17;
+
+/// SI prefixes from yocto (10^-24) to yotta (10^24); the middle entry is the
+/// unprefixed unit.
+const PREFIXES: [char; 17] = [
+    'y', 'z', 'a', 'f', 'p', 'n', 'µ', 'm', ' ', 'k', 'M', 'G', 'T', 'P', 'E', 'Z', 'Y',
+];
+
+const INVALID: CalcError = CalcError::Domain("Not a number in the expected format");
+
+/// The significant digits [`Format::parse`] keeps. `str::parse` rounds
+/// correctly from this many digits plus a flag for the rest.
+const PARSE_DIGITS: usize = 768;
+
+/// Decimal and digit-grouping separators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Locale {
+    /// Separates the integer part from the fraction.
+    pub decimal: char,
+    /// Inserted between groups of three integer digits, if any.
+    pub grouping: Option<char>,
+}
+
+impl Locale {
+    /// `1234.5`, as Rust's `{}` prints it.
+    pub const PLAIN: Locale = Locale {
+        decimal: '.',
+        grouping: None,
+    };
+    /// `1,234.5`: English, Chinese, Japanese, Korean.
+    pub const EN: Locale = Locale {
+        decimal: '.',
+        grouping: Some(','),
+    };
+    /// `1.234,5`: German, Spanish, Italian, Dutch, Portuguese, Turkish.
+    pub const DE: Locale = Locale {
+        decimal: ',',
+        grouping: Some('.'),
+    };
+    /// `1 234,5` with a narrow no-break space: French, Russian, Polish,
+    /// Swedish and most other European languages.
+    pub const FR: Locale = Locale {
+        decimal: ',',
+        grouping: Some('\u{202F}'),
+    };
+    /// `1'234.5`: Switzerland.
+    pub const CH: Locale = Locale {
+        decimal: '.',
+        grouping: Some('\''),
+    };
+
+    /// The separators for a language tag such as `"de-DE"` or `"fr"`, or a
+    /// POSIX locale name such as `"en_US.UTF-8"` from `LANG`.
+    ///
+    /// ```
+    /// use xrust_calclib::format::Locale;
+    ///
+    /// assert_eq!(Locale::from_tag("de_AT.UTF-8"), Some(Locale::DE));
+    /// assert_eq!(Locale::from_tag("it-CH"), Some(Locale::CH));
+    /// assert_eq!(Locale::from_tag("C"), Some(Locale::PLAIN));
+    /// assert_eq!(Locale::from_tag("tlh"), None);
+    /// ```
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        const EN: &[&str] = &["en", "zh", "ja", "ko", "th", "he", "ms", "hi"];
+        const DE: &[&str] = &[
+            "de", "es", "it", "nl", "pt", "tr", "id", "da", "el", "ro", "hr", "sl", "sr",
+        ];
+        const FR: &[&str] = &[
+            "fr", "ru", "pl", "cs", "sk", "sv", "nb", "nn", "no", "fi", "uk", "hu", "bg", "et",
+            "lv", "lt",
+        ];
+        let tag = tag.split(['.', '@']).next().unwrap_or("");
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or("");
+        let region = parts.next().unwrap_or("");
+        let is = |names: &[&str]| names.iter().any(|n| n.eq_ignore_ascii_case(language));
+        if is(&["c", "posix"]) {
+            Some(Locale::PLAIN)
+        } else if region.eq_ignore_ascii_case("CH") || region.eq_ignore_ascii_case("LI") {
+            Some(Locale::CH)
+        } else if is(EN) {
+            Some(Locale::EN)
+        } else if is(DE) {
+            Some(Locale::DE)
+        } else if is(FR) {
+            Some(Locale::FR)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::PLAIN
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Notation {
+    Fixed,
+    Significant,
+    Scientific,
+    Engineering,
+    Si,
+}
+
+/// How to print a number; see the [module docs](self).
+///
+/// The default prints 12 significant digits without trailing zeros, which
+/// hides binary rounding noise: `2.3 + 2.4` prints as `4.7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    notation: Notation,
+    digits: usize,
+    locale: Locale,
+    trim_zeros: bool,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::significant(12).trim_zeros(true)
+    }
+}
+
+impl Format {
+    fn new(notation: Notation, digits: usize) -> Self {
+        let digits = match notation {
+            Notation::Fixed => digits,
+            _ => digits.clamp(1, MAX_DIGITS),
+        };
+        Format {
+            notation,
+            digits,
+            locale: Locale::PLAIN,
+            trim_zeros: false,
+        }
+    }
+
+    /// `decimals` digits after the decimal point: `fixed(2)` prints `3.14`.
+    pub fn fixed(decimals: usize) -> Self {
+        Format::new(Notation::Fixed, decimals)
+    }
+
+    /// `digits` significant figures in positional form: `significant(3)`
+    /// prints `0.000123`, `4.70` and `12300`.
+    ///
+    /// Like the other notations below, `digits` is clamped to
+    /// `1..=`[`MAX_DIGITS`].
+    pub fn significant(digits: usize) -> Self {
+        Format::new(Notation::Significant, digits)
+    }
+
+    /// `digits` significant figures with one integer digit and an exponent:
+    /// `scientific(3)` prints `1.23e4`.
+    pub fn scientific(digits: usize) -> Self {
+        Format::new(Notation::Scientific, digits)
+    }
+
+    /// `digits` significant figures with an exponent that is a multiple of
+    /// three: `engineering(3)` prints `12.3e3`.
+    pub fn engineering(digits: usize) -> Self {
+        Format::new(Notation::Engineering, digits)
+    }
+
+    /// Engineering notation with an SI prefix instead of the exponent:
+    /// `si(3)` prints `12.3 k` and `470 n`. Values beyond yocto and yotta
+    /// fall back to [`engineering`](Format::engineering).
+    pub fn si(digits: usize) -> Self {
+        Format::new(Notation::Si, digits)
+    }
+
+    /// Uses the separators of `locale`.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Drops trailing zeros after the decimal point, and the point itself if
+    /// no digits are left.
+    pub fn trim_zeros(mut self, trim: bool) -> Self {
+        self.trim_zeros = trim;
+        self
+    }
+
+    /// A value that prints `value` in this format.
+    pub fn display(&self, value: f64) -> Formatted {
+        Formatted {
+            format: *self,
+            value,
+        }
+    }
+
+    /// Parses a number in any notation this type prints, using this
+    /// format's separators.
+    ///
+    /// Grouping separators are optional, `u` and `μ` are accepted for micro
+    /// and the space before an SI prefix may be left out. Anything else is a
+    /// [`CalcError::Domain`] error.
+    ///
+    /// ```
+    /// use xrust_calclib::format::{Format, Locale};
+    ///
+    /// let fr = Format::default().locale(Locale::FR);
+    /// assert_eq!(fr.parse("1 234,5"), Ok(1234.5));
+    /// assert_eq!(fr.parse("4,7k"), Ok(4700.0));
+    /// assert_eq!(fr.parse("1,5e-3"), Ok(0.0015));
+    /// assert!(fr.parse("1.5").is_err());
+    /// ```
+    pub fn parse(&self, s: &str) -> Result<f64, CalcError> {
+        let s = s.trim();
+        match s {
+            "NaN" => return Ok(f64::NAN),
+            "inf" | "+inf" => return Ok(f64::INFINITY),
+            "-inf" => return Ok(f64::NEG_INFINITY),
+            _ => {}
+        }
+        let (number, prefix) = split_prefix(s);
+        let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+            Some(_) if prefix.is_some() => return Err(INVALID),
+            Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+            None => (number, None),
+        };
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+
+        // Rewrite the number as `<digits>e<exp>` for `str::parse`, one
+        // character at a time, keeping as many significant digits as it
+        // reads and a final `1` that stands for any nonzero digits dropped.
+        let spaced = self.locale.grouping.is_some_and(char::is_whitespace);
+        let mut text = Buf::<{ PARSE_DIGITS + 16 }>::new();
+        let (mut any_digit, mut integer, mut sticky) = (false, true, false);
+        let mut exp = i64::from(prefix.unwrap_or(0));
+        for c in mantissa.chars() {
+            match c {
+                c if integer && c == self.locale.decimal => integer = false,
+                c if integer && Some(c) == self.locale.grouping => {}
+                c if integer && spaced && c.is_whitespace() => {}
+                '0'..='9' => {
+                    any_digit = true;
+                    if text.len == PARSE_DIGITS {
+                        sticky |= c != '0';
+                        exp += i64::from(integer);
+                        continue;
+                    }
+                    // Leading zeros are not significant.
+                    if text.len > 0 || c != '0' {
+                        text.write_char(c).map_err(|_| INVALID)?;
+                    }
+                    exp -= i64::from(!integer);
+                }
+                _ => return Err(INVALID),
+            }
+        }
+        if let Some(exponent) = exponent {
+            exp += parse_exponent(exponent).ok_or(INVALID)?;
+        }
+        if !any_digit {
+            return Err(INVALID);
+        }
+        if text.len == 0 {
+            return Ok(if negative { -0.0 } else { 0.0 });
+        }
+        if sticky {
+            text.write_char('1').map_err(|_| INVALID)?;
+            exp -= 1;
+        }
+        // Far beyond the range of `f64`, so the result is still 0 or infinite.
+        let exp = exp.clamp(-100_000, 100_000);
+        write!(text, "e{}", exp).map_err(|_| INVALID)?;
+        let value: f64 = text.as_str().parse().map_err(|_| INVALID)?;
+        Ok(if negative { -value } else { value })
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, value: f64) -> fmt::Result {
+        if !value.is_finite() {
+            return fmt::Display::fmt(&value, f);
+        }
+        if self.notation == Notation::Fixed {
+            return self.positional(f, format_args!("{:.*}", self.digits, value));
+        }
+        let digits = Digits::round(value.abs(), self.digits);
+        let sign = if value.is_sign_negative() { "-" } else { "" };
+        let exp = digits.exp;
+        let shift = match self.notation {
+            Notation::Scientific => 0,
+            Notation::Engineering | Notation::Si => exp.rem_euclid(3),
+            _ => {
+                let number = Positional {
+                    digits: &digits,
+                    point: exp + 1,
+                };
+                return self.positional(f, format_args!("{}{}", sign, number));
+            }
+        };
+        let mantissa = Positional {
+            digits: &digits,
+            point: shift + 1,
+        };
+        self.positional(f, format_args!("{}{}", sign, mantissa))?;
+        let exp = exp - shift;
+        let prefix = usize::try_from(exp / 3 + 8)
+            .ok()
+            .and_then(|i| PREFIXES.get(i));
+        match (self.notation, prefix) {
+            (Notation::Si, Some(' ')) => Ok(()),
+            (Notation::Si, Some(prefix)) => write!(f, " {}", prefix),
+            _ => write!(f, "e{}", exp),
+        }
+    }
+
+    /// Writes a number in `{}` form (`-1234.50`) with this format's
+    /// separators, trimming trailing zeros if asked to.
+    fn positional(&self, f: &mut fmt::Formatter<'_>, number: fmt::Arguments<'_>) -> fmt::Result {
+        let mut measure = Measure::default();
+        fmt::write(&mut measure, number)?;
+        let frac = if self.trim_zeros {
+            measure.significant_frac
+        } else {
+            measure.frac
+        };
+        let mut out = Localize {
+            out: f,
+            locale: self.locale,
+            int_left: measure.int,
+            frac_left: frac,
+            in_frac: false,
+        };
+        fmt::write(&mut out, number)
+    }
+}
+
+/// `value` printed in a [`Format`]; see [`Format::display`].
+#[derive(Debug, Clone, Copy)]
+pub struct Formatted {
+    format: Format,
+    value: f64,
+}
+
+impl fmt::Display for Formatted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.format.write(f, self.value)
+    }
+}
+
+/// Splits a trailing SI prefix from a number, returning its exponent.
+fn split_prefix(s: &str) -> (&str, Option<i32>) {
+    let mut chars = s.chars();
+    let exponent = match chars.next_back() {
+        Some('u' | 'μ') => Some(-6),
+        Some(' ') | None => None,
+        Some(c) => PREFIXES
+            .iter()
+            .position(|&prefix| prefix == c)
+            .map(|i| (i as i32 - 8) * 3),
+    };
+    let number = chars.as_str().trim_end();
+    match exponent {
+        Some(exponent) if number.ends_with(|c: char| c.is_ascii_digit()) => {
+            (number, Some(exponent))
+        }
+        _ => (s, None),
+    }
+}
+
+/// Reads an explicit exponent such as `-3` or `+12`, saturating huge ones.
+fn parse_exponent(s: &str) -> Option<i64> {
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let magnitude = digits.bytes().fold(0i64, |acc, b| {
+        acc.saturating_mul(10).saturating_add(i64::from(b - b'0'))
+    });
+    Some(if s.starts_with('-') {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+/// A small stack buffer to format into.
+struct Buf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Buf<N> {
+    fn new() -> Self {
+        Buf {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        // Only whole `str`s are ever copied in.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl<const N: usize> fmt::Write for Buf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > N {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// A non-negative value rounded to a number of significant digits:
+/// `d1.d2d3... × 10^exp`.
+struct Digits {
+    buf: [u8; MAX_DIGITS],
+    len: usize,
+    exp: i32,
+}
+
+impl Digits {
+    fn round(value: f64, digits: usize) -> Digits {
+        let mut text = Buf::<32>::new();
+        // At most 17 digits plus "." and "e-324" fit.
+        let _ = write!(text, "{:.*e}", digits - 1, value);
+        let (mantissa, exp) = text.as_str().split_once('e').unwrap_or(("0", "0"));
+        let mut out = Digits {
+            buf: [b'0'; MAX_DIGITS],
+            len: 0,
+            exp: exp.parse().unwrap_or(0),
+        };
+        for b in mantissa.bytes().filter(u8::is_ascii_digit) {
+            out.buf[out.len] = b;
+            out.len += 1;
+        }
+        out
+    }
+}
+
+/// Digits printed with the decimal point after `point` of them, padding with
+/// zeros on either side: `123` with `point` -1 is `0.0123`, with 5 `12300`.
+struct Positional<'a> {
+    digits: &'a Digits,
+    point: i32,
+}
+
+impl fmt::Display for Positional<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = &self.digits.buf[..self.digits.len];
+        let digit = |i: i32| {
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| digits.get(i))
+                .map_or('0', |&d| d as char)
+        };
+        if self.point <= 0 {
+            f.write_char('0')?;
+        }
+        for i in 0..self.point {
+            f.write_char(digit(i))?;
+        }
+        let frac_end = digits.len() as i32;
+        if frac_end > self.point {
+            f.write_char('.')?;
+            for i in self.point..frac_end {
+                f.write_char(digit(i))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Counts the digits of a number in `{}` form.
+#[derive(Default)]
+struct Measure {
+    int: usize,
+    frac: usize,
+    /// Fractional digits up to the last non-zero one.
+    significant_frac: usize,
+    in_frac: bool,
+}
+
+impl fmt::Write for Measure {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for b in s.bytes() {
+            match b {
+                b'.' => self.in_frac = true,
+                b'0'..=b'9' if self.in_frac => {
+                    self.frac += 1;
+                    if b != b'0' {
+                        self.significant_frac = self.frac;
+                    }
+                }
+                b'0'..=b'9' => self.int += 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copies a number in `{}` form with the locale's separators, keeping
+/// `frac_left` fractional digits.
+struct Localize<'a, 'b> {
+    out: &'a mut fmt::Formatter<'b>,
+    locale: Locale,
+    int_left: usize,
+    frac_left: usize,
+    in_frac: bool,
+}
+
+impl fmt::Write for Localize<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '.' => {
+                    self.in_frac = true;
+                    if self.frac_left > 0 {
+                        self.out.write_char(self.locale.decimal)?;
+                    }
+                }
+                '0'..='9' if self.in_frac => {
+                    if self.frac_left > 0 {
+                        self.out.write_char(c)?;
+                        self.frac_left -= 1;
+                    }
+                }
+                '0'..='9' => {
+                    self.out.write_char(c)?;
+                    self.int_left -= 1;
+                    if let Some(group) = self.locale.grouping
+                        && self.int_left > 0
+                        && self.int_left.is_multiple_of(3)
+                    {
+                        self.out.write_char(group)?;
+                    }
+                }
+                _ => self.out.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+    use std::string::{String, ToString};
+
+    fn show(format: Format, value: f64) -> String {
+        format.display(value).to_string()
+    }
+
+    #[test]
+    fn test_fixed_and_significant() {
+        assert_eq!(show(Format::fixed(2), 2.499), "2.50");
+        assert_eq!(show(Format::fixed(0), 2.5), "2");
+        assert_eq!(show(Format::fixed(3), -0.5), "-0.500");
+        assert_eq!(show(Format::fixed(3).trim_zeros(true), -0.5), "-0.5");
+        assert_eq!(show(Format::fixed(2).trim_zeros(true), 7.001), "7");
+
+        assert_eq!(show(Format::significant(3), 4.699999999999999), "4.70");
+        assert_eq!(show(Format::significant(3), 0.000123456), "0.000123");
+        assert_eq!(show(Format::significant(3), 123456.0), "123000");
+        assert_eq!(show(Format::significant(3), 9.996), "10.0");
+        assert_eq!(show(Format::significant(2), -0.0), "-0.0");
+        assert_eq!(show(Format::default(), 0.1 + 0.2), "0.3");
+        assert_eq!(show(Format::default(), 1e-9), "0.000000001");
+        assert_eq!(show(Format::significant(99), 0.1), "0.10000000000000001");
+        assert_eq!(show(Format::fixed(2), f64::NAN), "NaN");
+        assert_eq!(show(Format::si(2), f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn test_exponent_notations() {
+        assert_eq!(show(Format::scientific(3), 12345.0), "1.23e4");
+        assert_eq!(show(Format::scientific(3), -0.00012345), "-1.23e-4");
+        assert_eq!(show(Format::scientific(1), 0.0), "0e0");
+
+        assert_eq!(show(Format::engineering(3), 12345.0), "12.3e3");
+        assert_eq!(show(Format::engineering(2), 470e3), "470e3");
+        assert_eq!(show(Format::engineering(3), 0.0047), "4.70e-3");
+        // Rounding can carry into the next power of 1000.
+        assert_eq!(show(Format::engineering(3), 999.7), "1.00e3");
+
+        assert_eq!(show(Format::si(2), 4700.0), "4.7 k");
+        assert_eq!(show(Format::si(3), 220e-6), "220 µ");
+        assert_eq!(show(Format::si(3), 3.3), "3.30");
+        assert_eq!(show(Format::si(3).trim_zeros(true), 2.2e9), "2.2 G");
+        assert_eq!(show(Format::si(2), 1e-30), "1.0e-30");
+        assert_eq!(show(Format::si(2), 5e27), "5.0e27");
+    }
+
+    #[test]
+    fn test_locales() {
+        let value = -1234567.891;
+        assert_eq!(show(Format::fixed(2), value), "-1234567.89");
+        assert_eq!(
+            show(Format::fixed(2).locale(Locale::EN), value),
+            "-1,234,567.89"
+        );
+        assert_eq!(
+            show(Format::fixed(2).locale(Locale::DE), value),
+            "-1.234.567,89"
+        );
+        assert_eq!(
+            show(Format::fixed(1).locale(Locale::FR), value),
+            "-1\u{202F}234\u{202F}567,9"
+        );
+        assert_eq!(
+            show(Format::significant(2).locale(Locale::CH), 123456.0),
+            "120'000"
+        );
+        assert_eq!(show(Format::fixed(0).locale(Locale::EN), 999.0), "999");
+        assert_eq!(show(Format::si(2).locale(Locale::DE), 4700.0), "4,7 k");
+        assert_eq!(Locale::from_tag("en_US.UTF-8"), Some(Locale::EN));
+        assert_eq!(Locale::from_tag("PT-br"), Some(Locale::DE));
+        assert_eq!(Locale::from_tag(""), None);
+    }
+
+    #[test]
+    fn test_parse_round_trips() {
+        let formats = [
+            Format::fixed(4),
+            Format::significant(17),
+            Format::scientific(17),
+            Format::engineering(17),
+            Format::si(17),
+        ];
+        let locales = [
+            Locale::PLAIN,
+            Locale::EN,
+            Locale::DE,
+            Locale::FR,
+            Locale::CH,
+        ];
+        for value in [0.0, 1.0, -2.5, 1234567.0, 4.7e-7, -3.3e12, 1e-30] {
+            for format in formats {
+                for locale in locales {
+                    let format = format.locale(locale);
+                    let text = show(format, value);
+                    let parsed = format.parse(&text).unwrap();
+                    if format.notation == Notation::Fixed {
+                        assert!((parsed - value).abs() <= 1e-4, "{}", text);
+                    } else {
+                        assert_eq!(parsed, value, "{}", text);
+                    }
+                }
+            }
+        }
+
+        // Values longer than any fixed buffer.
+        for (format, value) in [
+            (Format::fixed(2), 1e100),
+            (Format::fixed(2), -f64::MAX),
+            (Format::fixed(70), 0.1),
+            (Format::fixed(340), 5e-324),
+        ] {
+            for locale in locales {
+                let format = format.locale(locale);
+                let text = show(format, value);
+                assert_eq!(format.parse(&text), Ok(value), "{}", text);
+            }
+        }
+
+        let plain = Format::default();
+        // Digits past the ones kept still round: 2^53 + 1 is a tie.
+        let tie = format!("9007199254740993.{}1", "0".repeat(800));
+        assert_eq!(plain.parse(&tie), Ok(9007199254740994.0));
+        let huge = format!("9007199254740993{}", "0".repeat(800));
+        assert_eq!(plain.parse(&huge), Ok(f64::INFINITY));
+        let tiny = format!("0.{}1", "0".repeat(400));
+        assert_eq!(plain.parse(&tiny), Ok(0.0));
+        assert_eq!(plain.parse("-0.000"), Ok(-0.0));
+        assert_eq!(plain.parse("1e99999999999999999999"), Ok(f64::INFINITY));
+        assert_eq!(plain.parse(".5"), Ok(0.5));
+        assert_eq!(plain.parse(" 4.7k "), Ok(4700.0));
+        assert_eq!(plain.parse("220u"), Ok(220e-6));
+        assert_eq!(plain.parse("2E"), Ok(2e18));
+        assert_eq!(plain.parse("1e3"), Ok(1000.0));
+        assert_eq!(plain.parse("-inf"), Ok(f64::NEG_INFINITY));
+        assert!(plain.parse("NaN").unwrap().is_nan());
+        for bad in [
+            "", "k", "1e3 k", "1,5", "4.7 kk", "abc", "1 2", "-", ".", "1e", "1e+", "1.2.3", "--1",
+            "1-2", "1e3.5", "1e3e4",
+        ] {
+            assert_eq!(plain.parse(bad), Err(INVALID), "{:?}", bad);
+        }
+        assert_eq!(
+            Format::default().locale(Locale::EN).parse("1,234.5"),
+            Ok(1234.5)
+        );
+        assert_eq!(
+            Format::default().locale(Locale::EN).parse("1.234,5"),
+            Err(INVALID)
+        );
+    }
+}
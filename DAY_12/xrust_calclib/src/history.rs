@@ -0,0 +1,397 @@
+//! A log of evaluated lines that front ends can search, replay and export.
+//!
+//! [`History::eval`] runs a line in a [`Session`] and records the line, the
+//! variables it read, its value or error and when it ran. With the `serde`
+//! feature a history serializes to JSON (or any serde format) one flat object
+//! per entry, so the CLI, TUI and WASM front ends share one data model.
+//! Infinite and NaN values are written as `"inf"`, `"-inf"` and `"NaN"`.
+//!
+//! ```
+//! use xrust_calclib::history::History;
+//! use xrust_calclib::session::Session;
+//!
+//! let mut session = Session::new();
+//! let mut history = History::new();
+//! history.eval_at(&mut session, "x = 4", 1_700_000_000_000).unwrap();
+//! history.eval_at(&mut session, "sqrt(x) * 10", 1_700_000_001_000).unwrap();
+//! assert!(history.eval_at(&mut session, "1 / 0", 1_700_000_002_000).is_err());
+//!
+//! let entry = &history.entries()[1];
+//! assert_eq!(entry.operands["x"], 4.0);
+//! assert_eq!(entry.value, Some(20.0));
+//! assert_eq!(history.search("sqrt").count(), 1);
+//! assert!(history.last().unwrap().is_error());
+//!
+//! // Rebuild the variables in a fresh session.
+//! let mut restored = Session::new();
+//! history.replay(&mut restored);
+//! assert_eq!(restored.variable("x"), Some(4.0));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::expr::{self, EvalError, Expr};
+use crate::session::{Outcome, Session};
+
+/// One evaluated line.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    /// The line as typed, such as `"area(r) / pi"`.
+    pub expression: String,
+    /// The session variables (and `ans`) the line read directly, with the
+    /// values they had before it ran.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "BTreeMap::is_empty",
+            with = "crate::serde_float::map"
+        )
+    )]
+    pub operands: BTreeMap<String, f64>,
+    /// The value computed or assigned; `None` for function definitions and
+    /// errors.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "crate::serde_float::option"
+        )
+    )]
+    pub value: Option<f64>,
+    /// The error message, such as `"Division by zero is not allowed (column 3)"`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// When the line ran, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl Entry {
+    /// Whether the line failed.
+    pub fn is_error(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// Evaluated lines, oldest first; see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct History {
+    entries: Vec<Entry>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    limit: Option<usize>,
+}
+
+impl History {
+    /// An empty, unbounded history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty history that keeps only the newest `limit` entries.
+    pub fn with_limit(limit: usize) -> Self {
+        History {
+            entries: Vec::new(),
+            limit: Some(limit),
+        }
+    }
+
+    /// The maximum number of entries kept, if any.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// The entries, oldest first.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The newest entry.
+    pub fn last(&self) -> Option<&Entry> {
+        self.entries.last()
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets every entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Appends an entry, dropping the oldest ones past the limit.
+    pub fn push(&mut self, entry: Entry) {
+        self.entries.push(entry);
+        if let Some(limit) = self.limit {
+            let excess = self.entries.len().saturating_sub(limit);
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Runs `line` in `session`, recording it with the current time.
+    ///
+    /// Not available on `wasm32-unknown-unknown`, where the system clock
+    /// panics; use [`eval_at`](History::eval_at) with `Date.now()` instead.
+    #[cfg(all(
+        feature = "std",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    pub fn eval(&mut self, session: &mut Session, line: &str) -> Result<Outcome, EvalError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        self.eval_at(session, line, now)
+    }
+
+    /// Runs `line` in `session`, recording it with `timestamp` (milliseconds
+    /// since the Unix epoch).
+    pub fn eval_at(
+        &mut self,
+        session: &mut Session,
+        line: &str,
+        timestamp: u64,
+    ) -> Result<Outcome, EvalError> {
+        let operands = operands(session, line);
+        let result = session.eval(line);
+        let (value, error) = match &result {
+            Ok(outcome) => (outcome.value(), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        self.push(Entry {
+            expression: line.to_string(),
+            // Definitions only name their parameters.
+            operands: match result {
+                Ok(Outcome::Defined { .. }) => BTreeMap::new(),
+                _ => operands,
+            },
+            value,
+            error,
+            timestamp,
+        });
+        result
+    }
+
+    /// The entries whose expression contains `text`, ignoring ASCII case,
+    /// oldest first.
+    pub fn search<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.entries
+            .iter()
+            .filter(move |entry| contains_ignore_case(&entry.expression, text))
+    }
+
+    /// Runs every recorded line again, in order, in `session`, e.g. to
+    /// restore variables and functions after a restart or to check old
+    /// results against a newer version of the library.
+    ///
+    /// Returns the new entries, which keep the original timestamps.
+    pub fn replay(&self, session: &mut Session) -> History {
+        let mut replayed = History {
+            entries: Vec::with_capacity(self.entries.len()),
+            limit: self.limit,
+        };
+        for entry in &self.entries {
+            // Failures are recorded in `replayed`.
+            let _ = replayed.eval_at(session, &entry.expression, entry.timestamp);
+        }
+        replayed
+    }
+}
+
+/// The values of the session variables `line` reads, before it runs.
+fn operands(session: &Session, line: &str) -> BTreeMap<String, f64> {
+    let body = line.split_once('=').map_or(line, |(_, rhs)| rhs);
+    let Ok(expr) = expr::parse(body) else {
+        return BTreeMap::new();
+    };
+    let mut names = Vec::new();
+    variables(&expr, &mut names);
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let value = match name {
+                "ans" => Some(session.ans()),
+                _ => session.variable(name),
+            };
+            value.map(|value| (name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Collects the identifiers in `expr`.
+fn variables<'a>(expr: &'a Expr, names: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Num(_) | Expr::Decimal { .. } => {}
+        Expr::Var { name, .. } => names.push(name),
+        Expr::Neg(inner) => variables(inner, names),
+        Expr::Binary { lhs, rhs, .. } => {
+            variables(lhs, names);
+            variables(rhs, names);
+        }
+        Expr::Call { args, .. } => {
+            for arg in args {
+                variables(arg, names);
+            }
+        }
+        Expr::WithUnit { value, .. } | Expr::Convert { value, .. } => variables(value, names),
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    needle.is_empty()
+        || haystack
+            .as_bytes()
+            .windows(needle.len())
+            .any(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(history: &mut History, session: &mut Session, lines: &[&str]) {
+        for (i, line) in lines.iter().enumerate() {
+            let _ = history.eval_at(session, line, 1000 * i as u64);
+        }
+    }
+
+    #[test]
+    fn test_records_entries() {
+        let mut session = Session::new();
+        let mut history = History::new();
+        run(
+            &mut history,
+            &mut session,
+            &[
+                "x = 3",
+                "f(t) = t^2 + x",
+                "f(x) + ans",
+                "x = x + 1",
+                "nope(1)",
+            ],
+        );
+        let entries = history.entries();
+        assert_eq!(entries.len(), 5);
+
+        assert_eq!(entries[0].value, Some(3.0));
+        assert!(entries[0].operands.is_empty());
+        assert_eq!(entries[1].value, None);
+        assert!(!entries[1].is_error());
+        assert!(entries[1].operands.is_empty());
+        assert_eq!(entries[2].value, Some(15.0));
+        assert_eq!(
+            entries[2].operands.iter().collect::<Vec<_>>(),
+            [(&"ans".to_string(), &3.0), (&"x".to_string(), &3.0)]
+        );
+        // Operands are read before the line runs.
+        assert_eq!(entries[3].operands["x"], 3.0);
+        assert_eq!(entries[3].value, Some(4.0));
+        assert!(entries[4].is_error());
+        assert_eq!(entries[4].timestamp, 4000);
+
+        assert_eq!(history.search("X =").count(), 2);
+        assert_eq!(history.search("").count(), 5);
+        assert_eq!(history.search("sqrt").count(), 0);
+    }
+
+    #[test]
+    fn test_limit_and_replay() {
+        let mut session = Session::new();
+        let mut history = History::with_limit(3);
+        run(
+            &mut history,
+            &mut session,
+            &["a = 1", "b = 2", "c = a + b", "c * 10"],
+        );
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.entries()[0].expression, "b = 2");
+
+        // `a` was dropped, so replaying `c = a + b` now fails.
+        let mut fresh = Session::new();
+        let replayed = history.replay(&mut fresh);
+        assert_eq!(replayed.len(), 3);
+        assert!(replayed.entries()[1].is_error());
+        assert!(replayed.entries()[2].is_error());
+        assert_eq!(
+            replayed.entries()[2].timestamp,
+            history.entries()[2].timestamp
+        );
+        assert_eq!(fresh.variable("b"), Some(2.0));
+
+        history.clear();
+        assert!(history.is_empty());
+        assert_eq!(History::with_limit(0).limit(), Some(0));
+    }
+
+    #[cfg(all(
+        feature = "std",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    #[test]
+    fn test_eval_uses_clock() {
+        let mut history = History::new();
+        history.eval(&mut Session::new(), "1 + 1").unwrap();
+        // 2020-01-01 in milliseconds.
+        assert!(history.last().unwrap().timestamp > 1_577_836_800_000);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut session = Session::new();
+        let mut history = History::with_limit(10);
+        run(&mut history, &mut session, &["x = 2", "x / 0", "g(y) = y"]);
+
+        let json = serde_json::to_string(&history).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"entries":[{"expression":"x = 2","value":2.0,"timestamp":0},"#,
+                r#"{"expression":"x / 0","operands":{"x":2.0},"#,
+                r#""error":"Division by zero is not allowed (column 3)","timestamp":1000},"#,
+                r#"{"expression":"g(y) = y","timestamp":2000}],"limit":10}"#
+            )
+        );
+        let restored: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, history);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_non_finite() {
+        let mut session = Session::new();
+        let mut history = History::new();
+        run(
+            &mut history,
+            &mut session,
+            &["big = 1e308 * 10", "-big", "big - big"],
+        );
+        assert_eq!(history.entries()[1].operands["big"], f64::INFINITY);
+
+        let json = serde_json::to_string(&history).unwrap();
+        assert!(json.contains(r#""value":"inf""#));
+        assert!(json.contains(r#""operands":{"big":"inf"},"value":"-inf""#));
+        assert!(json.contains(r#""value":"NaN""#));
+        let restored: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.entries()[0].value, Some(f64::INFINITY));
+        assert_eq!(restored.entries()[1].value, Some(f64::NEG_INFINITY));
+        assert!(restored.entries()[2].value.unwrap().is_nan());
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+    }
+}
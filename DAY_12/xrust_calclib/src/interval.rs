@@ -0,0 +1,369 @@
+//! Interval arithmetic for tracking measurement error.
+//!
+//! An [`Interval`] is a closed range `[lo, hi]` that is guaranteed to contain
+//! the true value. Every operation rounds its lower bound down and its upper
+//! bound up by one ulp, standing in for the directed rounding modes Rust does
+//! not expose, so floating-point error can only widen the result, never
+//! exclude the true value.
+//!
+//! ```
+//! use xrust_calclib::Interval;
+//!
+//! // A servo pulse of 0.8 ms to 2.2 ms at 2 MHz, with a 1% clock tolerance.
+//! let clock = Interval::from_percent(2_000_000.0, 1.0).unwrap();
+//! let min_ticks = Interval::point(0.8e-3) * clock;
+//! assert!(min_ticks.contains(1600.0));
+//! assert!(min_ticks.lo() < 1585.0 && min_ticks.hi() > 1615.0);
+//! ```
+
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::{CalcError, math};
+
+/// A closed interval `[lo, hi]` with `lo <= hi`; bounds may be infinite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+/// Rounds the bounds outwards.
+fn outward(lo: f64, hi: f64) -> Interval {
+    Interval {
+        lo: lo.next_down(),
+        hi: hi.next_up(),
+    }
+}
+
+/// `a * b` where `0 * ∞` is `0`, as it is for the limits of bounded values.
+fn mul_bound(a: f64, b: f64) -> f64 {
+    if a == 0.0 || b == 0.0 { 0.0 } else { a * b }
+}
+
+impl Interval {
+    /// Every real number.
+    pub const ENTIRE: Self = Interval {
+        lo: f64::NEG_INFINITY,
+        hi: f64::INFINITY,
+    };
+
+    /// `[lo, hi]`; errors if `lo > hi` or either bound is NaN.
+    pub fn new(lo: f64, hi: f64) -> Result<Self, CalcError> {
+        if lo <= hi {
+            Ok(Interval { lo, hi })
+        } else {
+            Err(CalcError::Domain("Interval bounds must satisfy lo <= hi"))
+        }
+    }
+
+    /// The single exact value `x`.
+    pub const fn point(x: f64) -> Self {
+        Interval { lo: x, hi: x }
+    }
+
+    /// `value ± tolerance`, for a non-negative absolute tolerance.
+    pub fn from_tolerance(value: f64, tolerance: f64) -> Result<Self, CalcError> {
+        if tolerance < 0.0 || tolerance.is_nan() {
+            return Err(CalcError::Domain("Tolerance must not be negative"));
+        }
+        let i = outward(value - tolerance, value + tolerance);
+        Interval::new(i.lo, i.hi)
+    }
+
+    /// `value ± percent%` of its magnitude.
+    pub fn from_percent(value: f64, percent: f64) -> Result<Self, CalcError> {
+        Interval::from_tolerance(value, (value * percent / 100.0).abs())
+    }
+
+    /// The lower bound.
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    /// The upper bound.
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    /// The midpoint.
+    pub fn mid(&self) -> f64 {
+        self.lo + (self.hi - self.lo) / 2.0
+    }
+
+    /// `hi - lo`.
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    /// Half the width: the `±` error bound around [`mid`](Self::mid).
+    pub fn radius(&self) -> f64 {
+        self.width() / 2.0
+    }
+
+    /// Whether `x` lies in the interval.
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    /// Whether both bounds are finite.
+    pub fn is_bounded(&self) -> bool {
+        self.lo.is_finite() && self.hi.is_finite()
+    }
+
+    /// The smallest interval containing both `self` and `other`.
+    pub fn hull(&self, other: &Self) -> Self {
+        Interval {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    /// `1 / self`, or [`CalcError::DivisionByZero`] if the interval contains zero.
+    pub fn recip(self) -> Result<Self, CalcError> {
+        if self.contains(0.0) {
+            return Err(CalcError::DivisionByZero);
+        }
+        Ok(outward(1.0 / self.hi, 1.0 / self.lo))
+    }
+
+    /// `self / rhs`, or [`CalcError::DivisionByZero`] if `rhs` contains zero.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(self * rhs.recip()?)
+    }
+
+    /// `self / rhs`, returning an unbounded interval when `rhs` contains zero
+    /// and only failing when `rhs` is exactly `[0, 0]`.
+    ///
+    /// The result is the hull of the true quotient set, so `[1, 2] / [-1, 1]`
+    /// is the entire real line rather than the two rays it really is.
+    pub fn div_unbounded(self, rhs: Self) -> Result<Self, CalcError> {
+        if rhs.lo == 0.0 && rhs.hi == 0.0 {
+            return Err(CalcError::DivisionByZero);
+        }
+        if !rhs.contains(0.0) {
+            return self.checked_div(rhs);
+        }
+        if self.contains(0.0) || (rhs.lo < 0.0 && rhs.hi > 0.0) {
+            return Ok(Interval::ENTIRE);
+        }
+        // Zero is an endpoint of `rhs`, so the quotient is a half-line.
+        let bounded = if self.hi < 0.0 {
+            if rhs.hi == 0.0 {
+                self.hi / rhs.lo
+            } else {
+                self.hi / rhs.hi
+            }
+        } else if rhs.hi == 0.0 {
+            self.lo / rhs.lo
+        } else {
+            self.lo / rhs.hi
+        };
+        let upward = (self.hi < 0.0) == (rhs.hi == 0.0);
+        Ok(if upward {
+            Interval {
+                lo: bounded.next_down(),
+                hi: f64::INFINITY,
+            }
+        } else {
+            Interval {
+                lo: f64::NEG_INFINITY,
+                hi: bounded.next_up(),
+            }
+        })
+    }
+
+    /// The square root. Negative parts of the interval are outside the domain
+    /// and dropped; an interval that is entirely negative is an error.
+    pub fn sqrt(self) -> Result<Self, CalcError> {
+        if self.hi < 0.0 {
+            return Err(CalcError::NegativeSqrt);
+        }
+        let lo = math::sqrt(self.lo.max(0.0)).next_down().max(0.0);
+        Ok(Interval {
+            lo,
+            hi: math::sqrt(self.hi).next_up(),
+        })
+    }
+
+    /// `self` raised to an integer power. Even powers of an interval that
+    /// contains zero start at zero; negative powers need a non-zero interval.
+    pub fn powi(self, n: i32) -> Result<Self, CalcError> {
+        if n < 0 {
+            return self
+                .recip()?
+                .powi(n.checked_neg().ok_or(CalcError::Overflow)?);
+        }
+        let p = |x: f64| math::powf(x, f64::from(n));
+        if n == 0 {
+            return Ok(Interval::point(1.0));
+        }
+        Ok(if n % 2 == 1 || self.lo >= 0.0 {
+            outward(p(self.lo), p(self.hi))
+        } else if self.hi <= 0.0 {
+            outward(p(self.hi), p(self.lo))
+        } else {
+            Interval {
+                lo: 0.0,
+                hi: p(self.lo).max(p(self.hi)).next_up(),
+            }
+        })
+    }
+
+    /// `self` raised to a real power; the base must be non-negative (and
+    /// positive for negative exponents).
+    pub fn pow(self, exp: f64) -> Result<Self, CalcError> {
+        if math::fract(exp) == 0.0 && exp.abs() <= i32::MAX as f64 {
+            return self.powi(exp as i32);
+        }
+        if self.lo < 0.0 || (exp < 0.0 && self.lo == 0.0) {
+            return Err(CalcError::Domain(
+                "Fractional powers need a positive interval",
+            ));
+        }
+        let (a, b) = (math::powf(self.lo, exp), math::powf(self.hi, exp));
+        let i = if exp >= 0.0 {
+            outward(a, b)
+        } else {
+            outward(b, a)
+        };
+        Ok(Interval {
+            lo: i.lo.max(0.0),
+            hi: i.hi,
+        })
+    }
+}
+
+impl From<f64> for Interval {
+    fn from(x: f64) -> Self {
+        Interval::point(x)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, rhs: Self) -> Interval {
+        outward(self.lo + rhs.lo, self.hi + rhs.hi)
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, rhs: Self) -> Interval {
+        outward(self.lo - rhs.hi, self.hi - rhs.lo)
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, rhs: Self) -> Interval {
+        let products = [
+            mul_bound(self.lo, rhs.lo),
+            mul_bound(self.lo, rhs.hi),
+            mul_bound(self.hi, rhs.lo),
+            mul_bound(self.hi, rhs.hi),
+        ];
+        let lo = products.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = products.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        outward(lo, hi)
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+/// Formats as `[lo, hi]`.
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iv(lo: f64, hi: f64) -> Interval {
+        Interval::new(lo, hi).unwrap()
+    }
+
+    fn encloses(i: Interval, lo: f64, hi: f64) -> bool {
+        i.lo() <= lo && i.hi() >= hi && i.width() < (hi - lo) + 1e-9
+    }
+
+    #[test]
+    fn test_construction() {
+        let t = Interval::from_tolerance(5.0, 0.25).unwrap();
+        assert!(encloses(t, 4.75, 5.25));
+        assert!(encloses(
+            Interval::from_percent(-200.0, 1.0).unwrap(),
+            -202.0,
+            -198.0
+        ));
+        assert!(t.contains(5.0) && !t.contains(5.3));
+        assert!((t.mid() - 5.0).abs() < 1e-12);
+        assert!((t.radius() - 0.25).abs() < 1e-12);
+        assert!(matches!(Interval::new(2.0, 1.0), Err(CalcError::Domain(_))));
+        assert!(Interval::new(f64::NAN, 1.0).is_err());
+        assert!(Interval::from_tolerance(1.0, -0.1).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = iv(1.0, 2.0);
+        let b = iv(-3.0, 4.0);
+        assert!(encloses(a + b, -2.0, 6.0));
+        assert!(encloses(a - b, -3.0, 5.0));
+        assert!(encloses(a * b, -6.0, 8.0));
+        assert!(encloses(-b, -4.0, 3.0));
+        assert!(encloses(a.checked_div(iv(2.0, 4.0)).unwrap(), 0.25, 1.0));
+
+        // Outward rounding keeps the true decimal sum inside.
+        let sum = Interval::point(0.1) + Interval::point(0.2);
+        assert!(sum.contains(0.3));
+        assert!(sum.contains(0.1 + 0.2));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let a = iv(1.0, 2.0);
+        assert_eq!(a.checked_div(iv(-1.0, 1.0)), Err(CalcError::DivisionByZero));
+        assert_eq!(a.div_unbounded(iv(-1.0, 1.0)), Ok(Interval::ENTIRE));
+        let half = a.div_unbounded(iv(0.0, 2.0)).unwrap();
+        assert!(half.lo() <= 0.5 && half.hi() == f64::INFINITY);
+        let neg = a.div_unbounded(iv(-2.0, 0.0)).unwrap();
+        assert!(neg.lo() == f64::NEG_INFINITY && neg.hi() >= -0.5);
+        assert_eq!(
+            a.div_unbounded(Interval::point(0.0)),
+            Err(CalcError::DivisionByZero)
+        );
+        assert!(!half.is_bounded());
+    }
+
+    #[test]
+    fn test_sqrt_and_powers() {
+        assert!(encloses(iv(4.0, 9.0).sqrt().unwrap(), 2.0, 3.0));
+        assert_eq!(iv(-1.0, 4.0).sqrt().unwrap().lo(), 0.0);
+        assert_eq!(iv(-4.0, -1.0).sqrt(), Err(CalcError::NegativeSqrt));
+
+        assert!(encloses(iv(-2.0, 3.0).powi(2).unwrap(), 0.0, 9.0));
+        assert!(encloses(iv(-3.0, -2.0).powi(2).unwrap(), 4.0, 9.0));
+        assert!(encloses(iv(-2.0, 3.0).powi(3).unwrap(), -8.0, 27.0));
+        assert!(encloses(iv(2.0, 4.0).powi(-1).unwrap(), 0.25, 0.5));
+        assert_eq!(iv(-1.0, 1.0).powi(-2), Err(CalcError::DivisionByZero));
+        assert!(encloses(iv(4.0, 9.0).pow(0.5).unwrap(), 2.0, 3.0));
+        assert!(encloses(iv(4.0, 9.0).pow(-0.5).unwrap(), 1.0 / 3.0, 0.5));
+        assert!(matches!(iv(-1.0, 1.0).pow(0.5), Err(CalcError::Domain(_))));
+    }
+}
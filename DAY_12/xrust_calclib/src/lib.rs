@@ -1,4 +1,64 @@
 //! xrust_calclib: A simple floating‐point calculator library.
+//!
+//! The default `std` feature can be turned off to build for targets that only
+//! have `core`; the expression evaluator then needs the `alloc` feature.
+
+// Without `std` we compile as no_std (firmware); host tests keep std.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod angle;
+pub mod batch;
+pub mod calculus;
+pub mod complex;
+pub mod error;
+#[cfg(feature = "alloc")]
+pub mod expr;
+pub mod finance;
+pub mod fixed;
+pub mod format;
+#[cfg(feature = "alloc")]
+pub mod history;
+pub mod interval;
+pub mod linalg;
+mod math;
+pub mod numeric;
+#[cfg(feature = "alloc")]
+pub mod program;
+pub mod programmer;
+pub mod rational;
+#[cfg(feature = "fancy")]
+pub mod scientific;
+#[cfg(feature = "serde")]
+mod serde_float;
+#[cfg(feature = "alloc")]
+pub mod session;
+pub mod stats;
+#[cfg(feature = "alloc")]
+pub mod symbolic;
+pub mod units;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use angle::AngleMode;
+pub use batch::BatchError;
+pub use complex::Complex;
+pub use error::CalcError;
+#[cfg(feature = "alloc")]
+pub use expr::{EvalError, EvalErrorKind, eval, eval_complex, eval_exact, eval_units};
+pub use format::Format;
+#[cfg(feature = "alloc")]
+pub use history::History;
+pub use interval::Interval;
+pub use linalg::{Matrix, Vector};
+pub use numeric::Numeric;
+#[cfg(feature = "alloc")]
+pub use program::{Program, compile};
+pub use rational::Rational;
+#[cfg(feature = "alloc")]
+pub use session::Session;
 
 /// Adds two floating‐point numbers.
 pub fn add(a: f64, b: f64) -> f64 {
@@ -16,18 +76,18 @@ pub fn mul(a: f64, b: f64) -> f64 {
 }
 
 /// Divides `a` by `b`, returning an error if `b` is zero.
-pub fn div(a: f64, b: f64) -> Result<f64, String> {
+pub fn div(a: f64, b: f64) -> Result<f64, CalcError> {
     if b == 0.0 {
-        Err("Division by zero is not allowed".into())
+        Err(CalcError::DivisionByZero)
     } else {
         Ok(a / b)
     }
 }
 
 /// Computes `a % b`, returning an error if `b` is zero.
-pub fn mode(a: f64, b: f64) -> Result<f64, String> {
+pub fn mode(a: f64, b: f64) -> Result<f64, CalcError> {
     if b == 0.0 {
-        Err("Modulo by zero is not allowed".into())
+        Err(CalcError::ModuloByZero)
     } else {
         Ok(a % b)
     }
@@ -35,15 +95,15 @@ pub fn mode(a: f64, b: f64) -> Result<f64, String> {
 
 /// Raises `a` to the power of `b`.
 pub fn pow(a: f64, b: f64) -> f64 {
-    a.powf(b)
+    math::powf(a, b)
 }
 
 /// Computes the square root of `a`, or errors if `a` is negative.
-pub fn sqrt(a: f64) -> Result<f64, String> {
+pub fn sqrt(a: f64) -> Result<f64, CalcError> {
     if a < 0.0 {
-        Err("Cannot calculate square root of a negative number".into())
+        Err(CalcError::NegativeSqrt)
     } else {
-        Ok(a.sqrt())
+        Ok(math::sqrt(a))
     }
 }
 
@@ -52,13 +112,27 @@ pub fn abs(a: f64) -> f64 {
     a.abs()
 }
 
-/// Computes the factorial of `a`. Only defined for non‐negative integers.
-pub fn fact(a: f64) -> Result<f64, String> {
+/// Largest `n` whose factorial is finite as an `f64`.
+const MAX_FACT_F64: f64 = 170.0;
+
+/// Computes the factorial of a non‐negative `a`.
+///
+/// A non-integer `a` is [`gamma`]`(a + 1.0)`, so `fact(3.5)` is about
+/// `11.63`. Results above `170!` do not fit in an `f64` and return
+/// [`CalcError::Overflow`]; use `fact_big` (feature `bigint`) for exact
+/// values. NaN is a [`CalcError::NonIntegerFactorial`].
+pub fn fact(a: f64) -> Result<f64, CalcError> {
+    if a.is_nan() {
+        return Err(CalcError::NonIntegerFactorial);
+    }
     if a < 0.0 {
-        return Err("Cannot calculate factorial of a negative number".into());
+        return Err(CalcError::NegativeFactorial);
     }
-    if a.fract() != 0.0 {
-        return Err("Factorial is not defined for non-integer values".into());
+    if a.is_finite() && math::fract(a) != 0.0 {
+        return gamma(a + 1.0);
+    }
+    if a > MAX_FACT_F64 {
+        return Err(CalcError::Overflow);
     }
 
     let mut result = 1.0;
@@ -70,9 +144,48 @@ pub fn fact(a: f64) -> Result<f64, String> {
     Ok(result)
 }
 
+/// Computes `n!` exactly as an arbitrary-precision integer.
+#[cfg(feature = "bigint")]
+pub fn fact_big(n: u64) -> num_bigint::BigUint {
+    (2..=n).fold(num_bigint::BigUint::from(1u8), |acc, k| acc * k)
+}
+
+/// The gamma function, `Γ(a)`, which extends factorial so that `Γ(n + 1) = n!`.
+///
+/// Errors at the poles (zero and the negative integers) and when the result
+/// overflows an `f64`.
+pub fn gamma(a: f64) -> Result<f64, CalcError> {
+    if a <= 0.0 && math::fract(a) == 0.0 {
+        return Err(CalcError::Domain(
+            "Gamma is undefined at zero and negative integers",
+        ));
+    }
+    let result = math::tgamma(a);
+    if result.is_infinite() {
+        Err(CalcError::Overflow)
+    } else {
+        Ok(result)
+    }
+}
+
+/// The natural log of the absolute value of the gamma function, `ln|Γ(a)|`.
+///
+/// Stays finite far beyond the point where [`gamma`] overflows, which makes it
+/// the right tool for ratios of large factorials.
+pub fn lgamma(a: f64) -> Result<f64, CalcError> {
+    if a <= 0.0 && math::fract(a) == 0.0 {
+        return Err(CalcError::Domain(
+            "Gamma is undefined at zero and negative integers",
+        ));
+    }
+    Ok(math::lgamma(a))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    extern crate std;
+    use std::string::ToString;
 
     #[test]
     fn test_add_sub_mul() {
@@ -84,23 +197,20 @@ mod tests {
     #[test]
     fn test_div() {
         assert_eq!(div(8.0, 2.0), Ok(4.0));
-        assert_eq!(div(1.0, 0.0), Err("Division by zero is not allowed".into()));
+        assert_eq!(div(1.0, 0.0), Err(CalcError::DivisionByZero));
     }
 
     #[test]
     fn test_mode() {
         assert_eq!(mode(10.5, 3.0), Ok(1.5));
-        assert_eq!(mode(5.0, 0.0), Err("Modulo by zero is not allowed".into()));
+        assert_eq!(mode(5.0, 0.0), Err(CalcError::ModuloByZero));
     }
 
     #[test]
     fn test_pow_sqrt_abs() {
         assert_eq!(pow(2.0, 3.0), 8.0);
         assert_eq!(sqrt(9.0), Ok(3.0));
-        assert_eq!(
-            sqrt(-1.0),
-            Err("Cannot calculate square root of a negative number".into())
-        );
+        assert_eq!(sqrt(-1.0), Err(CalcError::NegativeSqrt));
         assert_eq!(abs(-5.5), 5.5);
     }
 
@@ -108,13 +218,52 @@ mod tests {
     fn test_fact() {
         assert_eq!(fact(5.0), Ok(120.0));
         assert_eq!(fact(0.0), Ok(1.0));
+        assert_eq!(fact(-3.0), Err(CalcError::NegativeFactorial));
+        assert!((fact(3.5).unwrap() - 11.631_728_396_567_45).abs() < 1e-9);
+        assert!((fact(0.5).unwrap() - math::sqrt(core::f64::consts::PI) / 2.0).abs() < 1e-12);
+        assert_eq!(fact(170.0).map(f64::is_finite), Ok(true));
+        assert_eq!(fact(170.5).map(f64::is_finite), Ok(true));
+        assert_eq!(fact(171.0), Err(CalcError::Overflow));
+        assert_eq!(fact(171.5), Err(CalcError::Overflow));
+        assert_eq!(fact(f64::INFINITY), Err(CalcError::Overflow));
+        assert_eq!(fact(f64::NAN), Err(CalcError::NonIntegerFactorial));
+    }
+
+    #[test]
+    fn test_gamma() {
+        assert!((gamma(5.0).unwrap() - 24.0).abs() < 1e-9);
+        // Γ(4.5) = 3.5! = 11.631728...
+        assert!((gamma(4.5).unwrap() - 11.631_728_396_567_45).abs() < 1e-9);
+        assert!((gamma(0.5).unwrap() - math::sqrt(core::f64::consts::PI)).abs() < 1e-12);
+        assert!(matches!(gamma(0.0), Err(CalcError::Domain(_))));
+        assert!(matches!(gamma(-2.0), Err(CalcError::Domain(_))));
+        assert_eq!(gamma(200.0), Err(CalcError::Overflow));
+
+        // ln(170!) without overflow, and ln(200!) well past it.
+        assert!((lgamma(171.0).unwrap() - 706.573_062_245_787_4).abs() < 1e-9);
+        assert!(lgamma(201.0).unwrap() > 800.0);
+        assert!(matches!(lgamma(-1.0), Err(CalcError::Domain(_))));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_fact_big() {
+        assert_eq!(fact_big(0).to_string(), "1");
+        assert_eq!(fact_big(20).to_string(), "2432902008176640000");
+        assert_eq!(fact_big(25).to_string(), "15511210043330985984000000");
+        assert_eq!(fact_big(200).to_string().len(), 375);
+    }
+
+    #[test]
+    fn test_error_messages_unchanged() {
         assert_eq!(
-            fact(-3.0),
-            Err("Cannot calculate factorial of a negative number".into())
-        );
-        assert_eq!(
-            fact(3.5),
-            Err("Factorial is not defined for non-integer values".into())
+            div(1.0, 0.0).unwrap_err().to_string(),
+            "Division by zero is not allowed"
         );
+        #[cfg(feature = "alloc")]
+        {
+            let legacy: alloc::string::String = sqrt(-1.0).unwrap_err().into();
+            assert_eq!(legacy, "Cannot calculate square root of a negative number");
+        }
     }
 }
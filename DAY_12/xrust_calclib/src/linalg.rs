@@ -0,0 +1,707 @@
+//! Small dense linear algebra.
+//!
+//! [`Vector<N>`] and [`Matrix<R, C>`] are fixed-size, stack-allocated and
+//! available without an allocator, so 3×3 rotations and calibration
+//! transforms work on `no_std` targets. Dimensions are checked at compile
+//! time: multiplying a `Matrix<2, 3>` by a `Matrix<4, 2>` does not build.
+//!
+//! [`DMatrix`] is the heap-allocated counterpart (`alloc` feature) for sizes
+//! only known at run time; shape mismatches are reported as
+//! [`CalcError::ShapeMismatch`]. Both share one LU decomposition with partial
+//! pivoting, and a singular matrix is reported as [`CalcError::SingularMatrix`].
+//!
+//! ```
+//! use xrust_calclib::{Matrix, Vector};
+//!
+//! let a = Matrix::new([[2.0, 1.0], [1.0, 3.0]]);
+//! let x = a.solve(Vector::new([3.0, 5.0])).unwrap();
+//! assert_eq!(x, Vector::new([0.8, 1.4]));
+//! ```
+
+use core::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+use crate::{CalcError, math};
+
+/// Factors the row-major `n`×`n` matrix `a` in place into `L` (unit lower,
+/// below the diagonal) and `U` (upper, on and above it), choosing the largest
+/// pivot in each column. `perm` receives the row order and `scale` is scratch
+/// space for `n` row sizes; returns the sign of the permutation.
+fn lu_in_place(
+    a: &mut [f64],
+    n: usize,
+    perm: &mut [usize],
+    scale: &mut [f64],
+) -> Result<f64, CalcError> {
+    for (i, (p, s)) in perm.iter_mut().zip(scale.iter_mut()).enumerate() {
+        *p = i;
+        *s = a[i * n..(i + 1) * n]
+            .iter()
+            .fold(0.0f64, |m, x| m.max(x.abs()));
+    }
+    let mut sign = 1.0;
+    for k in 0..n {
+        let p = (k..n)
+            .max_by(|&i, &j| a[i * n + k].abs().total_cmp(&a[j * n + k].abs()))
+            .unwrap_or(k);
+        // A pivot this small next to the largest entry its row started with is
+        // rounding noise. Measuring per row keeps `diag(1e10, 1e-10)` regular.
+        let pivot = a[p * n + k].abs();
+        if pivot <= scale[p] * n as f64 * f64::EPSILON || pivot.is_nan() {
+            return Err(CalcError::SingularMatrix);
+        }
+        if p != k {
+            for j in 0..n {
+                a.swap(k * n + j, p * n + j);
+            }
+            perm.swap(k, p);
+            scale.swap(k, p);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            let f = a[i * n + k] / a[k * n + k];
+            a[i * n + k] = f;
+            for j in k + 1..n {
+                a[i * n + j] -= f * a[k * n + j];
+            }
+        }
+    }
+    Ok(sign)
+}
+
+/// Solves `A x = b` given the factors produced by [`lu_in_place`].
+fn lu_solve(lu: &[f64], n: usize, perm: &[usize], b: &[f64], x: &mut [f64]) {
+    for i in 0..n {
+        let mut s = b[perm[i]];
+        for j in 0..i {
+            s -= lu[i * n + j] * x[j];
+        }
+        x[i] = s;
+    }
+    for i in (0..n).rev() {
+        let mut s = x[i];
+        for j in i + 1..n {
+            s -= lu[i * n + j] * x[j];
+        }
+        x[i] = s / lu[i * n + i];
+    }
+}
+
+fn lu_determinant(lu: &[f64], n: usize, sign: f64) -> f64 {
+    (0..n).fold(sign, |d, i| d * lu[i * n + i])
+}
+
+/// Dot product of two equally long slices.
+pub fn dot(a: &[f64], b: &[f64]) -> Result<f64, CalcError> {
+    if a.len() != b.len() {
+        return Err(CalcError::ShapeMismatch);
+    }
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+/// A fixed-size column vector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vector<const N: usize>(pub [f64; N]);
+
+impl<const N: usize> Vector<N> {
+    /// The zero vector.
+    pub const ZERO: Self = Vector([0.0; N]);
+
+    /// Wraps an array.
+    pub const fn new(v: [f64; N]) -> Self {
+        Vector(v)
+    }
+
+    /// The dot product.
+    pub fn dot(&self, rhs: &Self) -> f64 {
+        self.0.iter().zip(&rhs.0).map(|(x, y)| x * y).sum()
+    }
+
+    /// The Euclidean length.
+    pub fn norm(&self) -> f64 {
+        math::sqrt(self.dot(self))
+    }
+
+    /// Multiplies every component by `k`.
+    pub fn scale(self, k: f64) -> Self {
+        Vector(self.0.map(|x| x * k))
+    }
+}
+
+impl Vector<3> {
+    /// The cross product `self × rhs`.
+    pub fn cross(&self, rhs: &Self) -> Self {
+        let [a1, a2, a3] = self.0;
+        let [b1, b2, b3] = rhs.0;
+        Vector([a2 * b3 - a3 * b2, a3 * b1 - a1 * b3, a1 * b2 - a2 * b1])
+    }
+}
+
+impl<const N: usize> From<[f64; N]> for Vector<N> {
+    fn from(v: [f64; N]) -> Self {
+        Vector(v)
+    }
+}
+
+impl<const N: usize> Index<usize> for Vector<N> {
+    type Output = f64;
+
+    fn index(&self, i: usize) -> &f64 {
+        &self.0[i]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Vector<N> {
+    fn index_mut(&mut self, i: usize) -> &mut f64 {
+        &mut self.0[i]
+    }
+}
+
+impl<const N: usize> Add for Vector<N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Vector(core::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<const N: usize> Sub for Vector<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Vector(core::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<const N: usize> Neg for Vector<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Vector(self.0.map(|x| -x))
+    }
+}
+
+/// A fixed-size row-major matrix with `R` rows and `C` columns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix<const R: usize, const C: usize>(pub [[f64; C]; R]);
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    /// The zero matrix.
+    pub const ZERO: Self = Matrix([[0.0; C]; R]);
+
+    /// Builds a matrix from its rows.
+    pub const fn new(rows: [[f64; C]; R]) -> Self {
+        Matrix(rows)
+    }
+
+    /// The transpose.
+    pub fn transpose(&self) -> Matrix<C, R> {
+        Matrix(core::array::from_fn(|j| {
+            core::array::from_fn(|i| self.0[i][j])
+        }))
+    }
+
+    /// Multiplies every entry by `k`.
+    pub fn scale(self, k: f64) -> Self {
+        Matrix(self.0.map(|row| row.map(|x| x * k)))
+    }
+}
+
+impl<const N: usize> Matrix<N, N> {
+    /// The identity matrix.
+    pub const IDENTITY: Self = {
+        let mut m = [[0.0; N]; N];
+        let mut i = 0;
+        while i < N {
+            m[i][i] = 1.0;
+            i += 1;
+        }
+        Matrix(m)
+    };
+
+    /// The LU decomposition with partial pivoting.
+    pub fn lu(&self) -> Result<Lu<N>, CalcError> {
+        let mut lu = *self;
+        let mut perm = [0; N];
+        let sign = lu_in_place(lu.0.as_flattened_mut(), N, &mut perm, &mut [0.0; N])?;
+        Ok(Lu { lu, perm, sign })
+    }
+
+    /// The determinant; `0.0` for a singular matrix.
+    pub fn determinant(&self) -> f64 {
+        self.lu().map_or(0.0, |lu| lu.determinant())
+    }
+
+    /// The inverse, or [`CalcError::SingularMatrix`].
+    pub fn inverse(&self) -> Result<Self, CalcError> {
+        Ok(self.lu()?.inverse())
+    }
+
+    /// Solves `self · x = b`, or returns [`CalcError::SingularMatrix`].
+    pub fn solve(&self, b: Vector<N>) -> Result<Vector<N>, CalcError> {
+        Ok(self.lu()?.solve(b))
+    }
+}
+
+impl<const R: usize, const C: usize> Index<(usize, usize)> for Matrix<R, C> {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.0[i][j]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<R, C> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.0[i][j]
+    }
+}
+
+impl<const R: usize, const C: usize> Add for Matrix<R, C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Matrix(core::array::from_fn(|i| {
+            core::array::from_fn(|j| self.0[i][j] + rhs.0[i][j])
+        }))
+    }
+}
+
+impl<const R: usize, const C: usize> Sub for Matrix<R, C> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Matrix(core::array::from_fn(|i| {
+            core::array::from_fn(|j| self.0[i][j] - rhs.0[i][j])
+        }))
+    }
+}
+
+impl<const R: usize, const C: usize, const K: usize> Mul<Matrix<C, K>> for Matrix<R, C> {
+    type Output = Matrix<R, K>;
+
+    fn mul(self, rhs: Matrix<C, K>) -> Matrix<R, K> {
+        Matrix(core::array::from_fn(|i| {
+            core::array::from_fn(|j| (0..C).map(|k| self.0[i][k] * rhs.0[k][j]).sum())
+        }))
+    }
+}
+
+impl<const R: usize, const C: usize> Mul<Vector<C>> for Matrix<R, C> {
+    type Output = Vector<R>;
+
+    fn mul(self, v: Vector<C>) -> Vector<R> {
+        Vector(core::array::from_fn(|i| Vector(self.0[i]).dot(&v)))
+    }
+}
+
+/// An LU decomposition `P·A = L·U` of a fixed-size square matrix, reusable
+/// for several right-hand sides.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lu<const N: usize> {
+    lu: Matrix<N, N>,
+    perm: [usize; N],
+    sign: f64,
+}
+
+impl<const N: usize> Lu<N> {
+    /// The unit lower-triangular factor `L`.
+    pub fn l(&self) -> Matrix<N, N> {
+        Matrix(core::array::from_fn(|i| {
+            core::array::from_fn(|j| match i.cmp(&j) {
+                core::cmp::Ordering::Greater => self.lu.0[i][j],
+                core::cmp::Ordering::Equal => 1.0,
+                core::cmp::Ordering::Less => 0.0,
+            })
+        }))
+    }
+
+    /// The upper-triangular factor `U`.
+    pub fn u(&self) -> Matrix<N, N> {
+        Matrix(core::array::from_fn(|i| {
+            core::array::from_fn(|j| if i <= j { self.lu.0[i][j] } else { 0.0 })
+        }))
+    }
+
+    /// Row `i` of `P·A` is row `permutation()[i]` of `A`.
+    pub fn permutation(&self) -> [usize; N] {
+        self.perm
+    }
+
+    /// The determinant of the decomposed matrix.
+    pub fn determinant(&self) -> f64 {
+        lu_determinant(self.lu.0.as_flattened(), N, self.sign)
+    }
+
+    /// Solves `A x = b`.
+    pub fn solve(&self, b: Vector<N>) -> Vector<N> {
+        let mut x = Vector::ZERO;
+        lu_solve(self.lu.0.as_flattened(), N, &self.perm, &b.0, &mut x.0);
+        x
+    }
+
+    /// The inverse of the decomposed matrix.
+    pub fn inverse(&self) -> Matrix<N, N> {
+        let mut inv = Matrix::<N, N>::ZERO;
+        for (j, e) in Matrix::<N, N>::IDENTITY.0.iter().enumerate() {
+            let col = self.solve(Vector(*e));
+            for i in 0..N {
+                inv.0[i][j] = col.0[i];
+            }
+        }
+        inv
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use heap::{DLu, DMatrix};
+
+#[cfg(feature = "alloc")]
+mod heap {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::ops::{Index, IndexMut};
+
+    use super::{Matrix, dot, lu_determinant, lu_in_place, lu_solve};
+    use crate::CalcError;
+
+    /// A heap-allocated row-major matrix whose size is chosen at run time.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DMatrix {
+        rows: usize,
+        cols: usize,
+        data: Vec<f64>,
+    }
+
+    impl DMatrix {
+        /// Builds a `rows`×`cols` matrix from row-major `data`.
+        pub fn new(rows: usize, cols: usize, data: Vec<f64>) -> Result<Self, CalcError> {
+            if rows.checked_mul(cols) != Some(data.len()) {
+                return Err(CalcError::ShapeMismatch);
+            }
+            Ok(DMatrix { rows, cols, data })
+        }
+
+        /// Builds a matrix from equally long rows.
+        pub fn from_rows(rows: &[&[f64]]) -> Result<Self, CalcError> {
+            let cols = rows.first().map_or(0, |r| r.len());
+            if rows.iter().any(|r| r.len() != cols) {
+                return Err(CalcError::ShapeMismatch);
+            }
+            Ok(DMatrix {
+                rows: rows.len(),
+                cols,
+                data: rows.concat(),
+            })
+        }
+
+        /// A `rows`×`cols` matrix of zeros.
+        pub fn zeros(rows: usize, cols: usize) -> Self {
+            DMatrix {
+                rows,
+                cols,
+                data: vec![0.0; rows * cols],
+            }
+        }
+
+        /// The `n`×`n` identity matrix.
+        pub fn identity(n: usize) -> Self {
+            let mut m = DMatrix::zeros(n, n);
+            for i in 0..n {
+                m[(i, i)] = 1.0;
+            }
+            m
+        }
+
+        /// The number of rows.
+        pub fn rows(&self) -> usize {
+            self.rows
+        }
+
+        /// The number of columns.
+        pub fn cols(&self) -> usize {
+            self.cols
+        }
+
+        /// The entries in row-major order.
+        pub fn as_slice(&self) -> &[f64] {
+            &self.data
+        }
+
+        /// The transpose.
+        pub fn transpose(&self) -> DMatrix {
+            let mut t = DMatrix::zeros(self.cols, self.rows);
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    t[(j, i)] = self[(i, j)];
+                }
+            }
+            t
+        }
+
+        /// The matrix product `self · rhs`.
+        pub fn mul(&self, rhs: &DMatrix) -> Result<DMatrix, CalcError> {
+            if self.cols != rhs.rows {
+                return Err(CalcError::ShapeMismatch);
+            }
+            let mut out = DMatrix::zeros(self.rows, rhs.cols);
+            for i in 0..self.rows {
+                for k in 0..self.cols {
+                    let a = self[(i, k)];
+                    for j in 0..rhs.cols {
+                        out[(i, j)] += a * rhs[(k, j)];
+                    }
+                }
+            }
+            Ok(out)
+        }
+
+        /// The matrix-vector product `self · v`.
+        pub fn mul_vec(&self, v: &[f64]) -> Result<Vec<f64>, CalcError> {
+            if self.cols != v.len() {
+                return Err(CalcError::ShapeMismatch);
+            }
+            // Index by row rather than chunking the data, which is empty for
+            // a `rows × 0` matrix whose product is still `rows` zeros.
+            (0..self.rows)
+                .map(|i| dot(&self.data[i * self.cols..(i + 1) * self.cols], v))
+                .collect()
+        }
+
+        /// The LU decomposition with partial pivoting; the matrix must be square.
+        pub fn lu(&self) -> Result<DLu, CalcError> {
+            if self.rows != self.cols {
+                return Err(CalcError::ShapeMismatch);
+            }
+            let mut lu = self.clone();
+            let mut perm = vec![0; self.rows];
+            let mut scale = vec![0.0; self.rows];
+            let sign = lu_in_place(&mut lu.data, self.rows, &mut perm, &mut scale)?;
+            Ok(DLu { lu, perm, sign })
+        }
+
+        /// The determinant of a square matrix; `0.0` if it is singular.
+        pub fn determinant(&self) -> Result<f64, CalcError> {
+            match self.lu() {
+                Ok(lu) => Ok(lu.determinant()),
+                Err(CalcError::SingularMatrix) => Ok(0.0),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// The inverse of a square matrix.
+        pub fn inverse(&self) -> Result<DMatrix, CalcError> {
+            Ok(self.lu()?.inverse())
+        }
+
+        /// Solves `self · x = b`.
+        pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, CalcError> {
+            self.lu()?.solve(b)
+        }
+    }
+
+    impl<const R: usize, const C: usize> From<Matrix<R, C>> for DMatrix {
+        fn from(m: Matrix<R, C>) -> Self {
+            DMatrix {
+                rows: R,
+                cols: C,
+                data: m.0.as_flattened().to_vec(),
+            }
+        }
+    }
+
+    impl Index<(usize, usize)> for DMatrix {
+        type Output = f64;
+
+        fn index(&self, (i, j): (usize, usize)) -> &f64 {
+            assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+            &self.data[i * self.cols + j]
+        }
+    }
+
+    impl IndexMut<(usize, usize)> for DMatrix {
+        fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+            assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+            &mut self.data[i * self.cols + j]
+        }
+    }
+
+    /// An LU decomposition of a [`DMatrix`], reusable for several right-hand sides.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DLu {
+        lu: DMatrix,
+        perm: Vec<usize>,
+        sign: f64,
+    }
+
+    impl DLu {
+        /// Row `i` of `P·A` is row `permutation()[i]` of `A`.
+        pub fn permutation(&self) -> &[usize] {
+            &self.perm
+        }
+
+        /// The determinant of the decomposed matrix.
+        pub fn determinant(&self) -> f64 {
+            lu_determinant(&self.lu.data, self.lu.rows, self.sign)
+        }
+
+        /// Solves `A x = b`.
+        pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, CalcError> {
+            let n = self.lu.rows;
+            if b.len() != n {
+                return Err(CalcError::ShapeMismatch);
+            }
+            let mut x = vec![0.0; n];
+            lu_solve(&self.lu.data, n, &self.perm, b, &mut x);
+            Ok(x)
+        }
+
+        /// The inverse of the decomposed matrix.
+        pub fn inverse(&self) -> DMatrix {
+            let n = self.lu.rows;
+            let mut inv = DMatrix::zeros(n, n);
+            let mut e = vec![0.0; n];
+            let mut col = vec![0.0; n];
+            for j in 0..n {
+                e[j] = 1.0;
+                lu_solve(&self.lu.data, n, &self.perm, &e, &mut col);
+                e[j] = 0.0;
+                for i in 0..n {
+                    inv[(i, j)] = col[i];
+                }
+            }
+            inv
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close<const R: usize, const C: usize>(a: Matrix<R, C>, b: Matrix<R, C>) -> bool {
+        a.0.as_flattened()
+            .iter()
+            .zip(b.0.as_flattened())
+            .all(|(x, y)| (x - y).abs() < 1e-12)
+    }
+
+    #[test]
+    fn test_vectors() {
+        let x = Vector::new([1.0, 0.0, 0.0]);
+        let y = Vector::new([0.0, 1.0, 0.0]);
+        assert_eq!(x.cross(&y), Vector::new([0.0, 0.0, 1.0]));
+        assert_eq!(y.cross(&x), Vector::new([0.0, 0.0, -1.0]));
+        assert_eq!(Vector::new([3.0, 4.0]).norm(), 5.0);
+        assert_eq!(
+            Vector::new([1.0, 2.0, 3.0]).dot(&Vector::new([4.0, 5.0, 6.0])),
+            32.0
+        );
+        assert_eq!(x + y - x.scale(2.0), Vector::new([-1.0, 1.0, 0.0]));
+        assert_eq!(dot(&[1.0, 2.0], &[3.0, 4.0]), Ok(11.0));
+        assert_eq!(dot(&[1.0], &[3.0, 4.0]), Err(CalcError::ShapeMismatch));
+    }
+
+    #[test]
+    fn test_multiply_transpose() {
+        let a = Matrix::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let b = a.transpose();
+        assert_eq!(b, Matrix::new([[1.0, 4.0], [2.0, 5.0], [3.0, 6.0]]));
+        assert_eq!(a * b, Matrix::new([[14.0, 32.0], [32.0, 77.0]]));
+        assert_eq!(a * Vector::new([1.0, 0.0, -1.0]), Vector::new([-2.0, -2.0]));
+        assert_eq!(Matrix::<3, 3>::IDENTITY * b, b);
+        assert_eq!(a[(1, 2)], 6.0);
+    }
+
+    #[test]
+    fn test_rotation() {
+        // 90° about z: x → y, y → -x.
+        let rz = Matrix::new([[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert_eq!(
+            rz * Vector::new([1.0, 0.0, 0.0]),
+            Vector::new([0.0, 1.0, 0.0])
+        );
+        assert_eq!(rz.determinant(), 1.0);
+        assert!(close(rz.inverse().unwrap(), rz.transpose()));
+    }
+
+    #[test]
+    fn test_lu_det_inverse_solve() {
+        let a = Matrix::new([[2.0, 1.0, 1.0], [4.0, -6.0, 0.0], [-2.0, 7.0, 2.0]]);
+        let lu = a.lu().unwrap();
+        let pa = Matrix(lu.permutation().map(|r| a.0[r]));
+        assert!(close(lu.l() * lu.u(), pa));
+        assert!((a.determinant() - -16.0).abs() < 1e-12);
+
+        let inv = a.inverse().unwrap();
+        assert!(close(a * inv, Matrix::IDENTITY));
+        let x = a.solve(Vector::new([5.0, -2.0, 9.0])).unwrap();
+        assert!(close(Matrix([x.0]), Matrix([[1.0, 1.0, 2.0]])));
+    }
+
+    #[test]
+    fn test_singular() {
+        let s = Matrix::new([[1.0, 2.0], [2.0, 4.0]]);
+        assert_eq!(s.determinant(), 0.0);
+        assert_eq!(s.inverse(), Err(CalcError::SingularMatrix));
+        assert_eq!(
+            s.solve(Vector::new([1.0, 2.0])),
+            Err(CalcError::SingularMatrix)
+        );
+        assert_eq!(Matrix::<3, 3>::ZERO.lu(), Err(CalcError::SingularMatrix));
+        // Rank-deficient only after rounding: 0.1 + 0.2 != 0.3 exactly.
+        let near = Matrix::new([[0.1, 0.2, 0.3], [1.0, 1.0, 2.0], [1.0, 2.0, 3.0]]);
+        assert_eq!(near.inverse(), Err(CalcError::SingularMatrix));
+        // Badly scaled is not singular.
+        let wide = Matrix::new([[1e10, 0.0], [0.0, 1e-10]]);
+        assert_eq!(wide.determinant(), 1.0);
+        assert_eq!(wide.inverse(), Ok(Matrix::new([[1e-10, 0.0], [0.0, 1e10]])));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_heap_matrix() {
+        let a =
+            DMatrix::from_rows(&[&[2.0, 1.0, 1.0], &[4.0, -6.0, 0.0], &[-2.0, 7.0, 2.0]]).unwrap();
+        assert_eq!(
+            a,
+            DMatrix::from(Matrix::new([
+                [2.0, 1.0, 1.0],
+                [4.0, -6.0, 0.0],
+                [-2.0, 7.0, 2.0]
+            ]))
+        );
+        assert!((a.determinant().unwrap() - -16.0).abs() < 1e-12);
+        let x = a.solve(&[5.0, -2.0, 9.0]).unwrap();
+        assert!(
+            x.iter()
+                .zip([1.0, 1.0, 2.0])
+                .all(|(a, b)| (a - b).abs() < 1e-12)
+        );
+
+        let prod = a.mul(&a.inverse().unwrap()).unwrap();
+        let id = DMatrix::identity(3);
+        assert!(
+            prod.as_slice()
+                .iter()
+                .zip(id.as_slice())
+                .all(|(a, b)| (a - b).abs() < 1e-12)
+        );
+
+        let r = DMatrix::new(2, 3, alloc::vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(r.transpose().rows(), 3);
+        assert_eq!(r.mul_vec(&[1.0, 0.0, -1.0]), Ok(alloc::vec![-2.0, -2.0]));
+        assert_eq!(DMatrix::zeros(3, 0).mul_vec(&[]), Ok(alloc::vec![0.0; 3]));
+        assert_eq!(DMatrix::zeros(0, 2).mul_vec(&[1.0, 2.0]), Ok(alloc::vec![]));
+        assert_eq!(r.mul(&r), Err(CalcError::ShapeMismatch));
+        assert_eq!(r.lu(), Err(CalcError::ShapeMismatch));
+        assert_eq!(r.determinant(), Err(CalcError::ShapeMismatch));
+        assert_eq!(
+            DMatrix::new(2, 2, alloc::vec![1.0]),
+            Err(CalcError::ShapeMismatch)
+        );
+
+        let s = DMatrix::from_rows(&[&[1.0, 2.0], &[2.0, 4.0]]).unwrap();
+        assert_eq!(s.determinant(), Ok(0.0));
+        assert_eq!(s.inverse(), Err(CalcError::SingularMatrix));
+    }
+}
@@ -0,0 +1,137 @@
+//! Floating-point math backend.
+//!
+//! With the `std` feature the inherent `f64` methods are used; without it the
+//! same operations come from `libm`, so the crate builds for targets that only
+//! have `core` (Cortex-M, AVR).
+
+// Each wrapper picks the std method or the libm routine of the same operation.
+// Some are only used by optional modules, hence the `dead_code` allowances.
+macro_rules! unary {
+    ($($name:ident => $libm:ident),* $(,)?) => {$(
+        #[inline]
+        #[allow(dead_code)]
+        pub(crate) fn $name(x: f64) -> f64 {
+            #[cfg(feature = "std")]
+            {
+                x.$name()
+            }
+            #[cfg(not(feature = "std"))]
+            {
+                libm::$libm(x)
+            }
+        }
+    )*};
+}
+
+unary! {
+    sqrt => sqrt,
+    trunc => trunc,
+    floor => floor,
+    ceil => ceil,
+    round => round,
+    exp => exp,
+    ln => log,
+    log10 => log10,
+    sin => sin,
+    cos => cos,
+    tan => tan,
+    asin => asin,
+    acos => acos,
+    atan => atan,
+    sinh => sinh,
+    cosh => cosh,
+    tanh => tanh,
+    asinh => asinh,
+    acosh => acosh,
+    atanh => atanh,
+}
+
+#[inline]
+pub(crate) fn powf(x: f64, y: f64) -> f64 {
+    #[cfg(feature = "std")]
+    {
+        x.powf(y)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::pow(x, y)
+    }
+}
+
+#[inline]
+#[allow(dead_code)]
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    {
+        y.atan2(x)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::atan2(y, x)
+    }
+}
+
+#[inline]
+#[allow(dead_code)]
+pub(crate) fn hypot(x: f64, y: f64) -> f64 {
+    #[cfg(feature = "std")]
+    {
+        x.hypot(y)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::hypot(x, y)
+    }
+}
+
+// `std` has no stable gamma functions, so both backends use `libm` here.
+#[inline]
+pub(crate) fn tgamma(x: f64) -> f64 {
+    libm::tgamma(x)
+}
+
+#[inline]
+pub(crate) fn lgamma(x: f64) -> f64 {
+    libm::lgamma(x)
+}
+
+/// Fractional part of `x`, with the sign of `x`.
+#[inline]
+pub(crate) fn fract(x: f64) -> f64 {
+    x - trunc(x)
+}
+
+/// Rounds to nearest with ties to the even integer.
+#[inline]
+pub(crate) fn round_half_even(x: f64) -> f64 {
+    if fract(x).abs() == 0.5 {
+        2.0 * round(x / 2.0)
+    } else {
+        round(x)
+    }
+}
+
+/// Rounds `x` to `decimals` places (negative: tens, hundreds, ...) by applying
+/// the integer rounding `round` to `x · 10^decimals`.
+///
+/// Values within a few ulps of a decimal half or whole step are snapped onto
+/// it first, so the decimal the caller wrote decides the result: `1.005` is a
+/// tie at two places although its binary value is slightly below. Values with
+/// no digits past that place, infinities and NaN come back unchanged; values
+/// too small to reach it become zero with the sign of `x`.
+pub(crate) fn round_decimal(x: f64, decimals: i32, round: fn(f64) -> f64) -> f64 {
+    let scale = powf(10.0, decimals as f64);
+    if scale == 0.0 {
+        return 0.0f64.copysign(x);
+    }
+    let mut y = x * scale;
+    // From 2^52 up every f64 is an integer, so there is nothing to round.
+    if !scale.is_finite() || y.is_nan() || y.abs() >= 4_503_599_627_370_496.0 {
+        return x;
+    }
+    let half = self::round(y * 2.0) / 2.0;
+    if (y - half).abs() <= 8.0 * f64::EPSILON * y.abs() {
+        y = half;
+    }
+    round(y) / scale
+}
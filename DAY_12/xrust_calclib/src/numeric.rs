@@ -0,0 +1,279 @@
+//! The calculator API over every primitive numeric type.
+//!
+//! [`Numeric`] is implemented for `i8`–`i128`, `u8`–`u128`, `f32` and `f64`.
+//! The free functions in this module mirror the crate-level `add`, `div`,
+//! `mode`, `pow` and `fact`, but are generic and always fallible: integer
+//! operations are checked, so overflow (including `i32::MIN / -1`) is reported
+//! as [`CalcError::Overflow`] instead of panicking or wrapping.
+//!
+//! ```
+//! use xrust_calclib::{CalcError, numeric};
+//!
+//! assert_eq!(numeric::add(200u8, 55), Ok(255));
+//! assert_eq!(numeric::add(200u8, 56), Err(CalcError::Overflow));
+//! assert_eq!(numeric::div(i32::MIN, -1), Err(CalcError::Overflow));
+//! assert_eq!(numeric::fact(20u64), Ok(2_432_902_008_176_640_000));
+//! ```
+
+use core::fmt::Debug;
+
+use crate::{CalcError, math};
+
+/// A number the calculator can operate on.
+pub trait Numeric: Copy + PartialEq + PartialOrd + Debug {
+    /// The additive identity.
+    const ZERO: Self;
+    /// The multiplicative identity.
+    const ONE: Self;
+
+    /// `self + rhs`.
+    fn try_add(self, rhs: Self) -> Result<Self, CalcError>;
+    /// `self - rhs`.
+    fn try_sub(self, rhs: Self) -> Result<Self, CalcError>;
+    /// `self * rhs`.
+    fn try_mul(self, rhs: Self) -> Result<Self, CalcError>;
+    /// `self / rhs`; integers truncate towards zero.
+    fn try_div(self, rhs: Self) -> Result<Self, CalcError>;
+    /// `self % rhs`, with the sign of `self`.
+    fn try_rem(self, rhs: Self) -> Result<Self, CalcError>;
+    /// `self` raised to `exp`. Integers reject negative exponents.
+    fn try_pow(self, exp: Self) -> Result<Self, CalcError>;
+    /// `self!`, for non-negative integral values.
+    fn try_fact(self) -> Result<Self, CalcError>;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),* $(,)?) => {$(
+        impl Numeric for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn try_add(self, rhs: Self) -> Result<Self, CalcError> {
+                self.checked_add(rhs).ok_or(CalcError::Overflow)
+            }
+
+            fn try_sub(self, rhs: Self) -> Result<Self, CalcError> {
+                self.checked_sub(rhs).ok_or(CalcError::Overflow)
+            }
+
+            fn try_mul(self, rhs: Self) -> Result<Self, CalcError> {
+                self.checked_mul(rhs).ok_or(CalcError::Overflow)
+            }
+
+            fn try_div(self, rhs: Self) -> Result<Self, CalcError> {
+                if rhs == 0 {
+                    return Err(CalcError::DivisionByZero);
+                }
+                // The only other failure is MIN / -1 for signed types.
+                self.checked_div(rhs).ok_or(CalcError::Overflow)
+            }
+
+            fn try_rem(self, rhs: Self) -> Result<Self, CalcError> {
+                if rhs == 0 {
+                    return Err(CalcError::ModuloByZero);
+                }
+                // MIN % -1 is mathematically 0; only the intermediate overflows.
+                Ok(self.wrapping_rem(rhs))
+            }
+
+            #[allow(unused_comparisons)]
+            fn try_pow(self, exp: Self) -> Result<Self, CalcError> {
+                if exp < 0 {
+                    return Err(CalcError::Domain(
+                        "Negative exponents have no integer result",
+                    ));
+                }
+                match u32::try_from(exp) {
+                    Ok(e) => self.checked_pow(e).ok_or(CalcError::Overflow),
+                    // Huge exponents only have a representable result for 0 and ±1.
+                    Err(_) if self.checked_mul(self).is_some_and(|sq| sq <= 1) => {
+                        Ok(self.pow(if exp % 2 == 0 { 2 } else { 1 }))
+                    }
+                    Err(_) => Err(CalcError::Overflow),
+                }
+            }
+
+            #[allow(unused_comparisons)]
+            fn try_fact(self) -> Result<Self, CalcError> {
+                if self < 0 {
+                    return Err(CalcError::NegativeFactorial);
+                }
+                let mut result: Self = 1;
+                let mut n: Self = 2;
+                while n <= self {
+                    result = result.checked_mul(n).ok_or(CalcError::Overflow)?;
+                    n += 1;
+                }
+                Ok(result)
+            }
+        }
+    )*};
+}
+
+impl_int!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl Numeric for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn try_add(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(crate::add(self, rhs))
+    }
+
+    fn try_sub(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(crate::sub(self, rhs))
+    }
+
+    fn try_mul(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(crate::mul(self, rhs))
+    }
+
+    fn try_div(self, rhs: Self) -> Result<Self, CalcError> {
+        crate::div(self, rhs)
+    }
+
+    fn try_rem(self, rhs: Self) -> Result<Self, CalcError> {
+        crate::mode(self, rhs)
+    }
+
+    fn try_pow(self, exp: Self) -> Result<Self, CalcError> {
+        Ok(crate::pow(self, exp))
+    }
+
+    fn try_fact(self) -> Result<Self, CalcError> {
+        crate::fact(self)
+    }
+}
+
+impl Numeric for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn try_add(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(self + rhs)
+    }
+
+    fn try_sub(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(self - rhs)
+    }
+
+    fn try_mul(self, rhs: Self) -> Result<Self, CalcError> {
+        Ok(self * rhs)
+    }
+
+    fn try_div(self, rhs: Self) -> Result<Self, CalcError> {
+        if rhs == 0.0 {
+            Err(CalcError::DivisionByZero)
+        } else {
+            Ok(self / rhs)
+        }
+    }
+
+    fn try_rem(self, rhs: Self) -> Result<Self, CalcError> {
+        if rhs == 0.0 {
+            Err(CalcError::ModuloByZero)
+        } else {
+            Ok(self % rhs)
+        }
+    }
+
+    fn try_pow(self, exp: Self) -> Result<Self, CalcError> {
+        Ok(math::powf(self as f64, exp as f64) as f32)
+    }
+
+    fn try_fact(self) -> Result<Self, CalcError> {
+        let result = crate::fact(self as f64)?;
+        if result > f32::MAX as f64 {
+            Err(CalcError::Overflow)
+        } else {
+            Ok(result as f32)
+        }
+    }
+}
+
+/// Adds two numbers of any [`Numeric`] type.
+pub fn add<T: Numeric>(a: T, b: T) -> Result<T, CalcError> {
+    a.try_add(b)
+}
+
+/// Subtracts `b` from `a`.
+pub fn sub<T: Numeric>(a: T, b: T) -> Result<T, CalcError> {
+    a.try_sub(b)
+}
+
+/// Multiplies two numbers of any [`Numeric`] type.
+pub fn mul<T: Numeric>(a: T, b: T) -> Result<T, CalcError> {
+    a.try_mul(b)
+}
+
+/// Divides `a` by `b`, returning an error if `b` is zero or the quotient overflows.
+pub fn div<T: Numeric>(a: T, b: T) -> Result<T, CalcError> {
+    a.try_div(b)
+}
+
+/// Computes `a % b`, returning an error if `b` is zero.
+pub fn mode<T: Numeric>(a: T, b: T) -> Result<T, CalcError> {
+    a.try_rem(b)
+}
+
+/// Raises `a` to the power of `b`.
+pub fn pow<T: Numeric>(a: T, b: T) -> Result<T, CalcError> {
+    a.try_pow(b)
+}
+
+/// Computes the factorial of `a`.
+pub fn fact<T: Numeric>(a: T) -> Result<T, CalcError> {
+    a.try_fact()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_integer_ops() {
+        assert_eq!(add(i8::MAX, 1), Err(CalcError::Overflow));
+        assert_eq!(sub(0u32, 1), Err(CalcError::Overflow));
+        assert_eq!(mul(1i64 << 62, 2), Err(CalcError::Overflow));
+        assert_eq!(div(7i32, 2), Ok(3));
+        assert_eq!(div(-7i32, 2), Ok(-3));
+        assert_eq!(div(1u8, 0), Err(CalcError::DivisionByZero));
+        assert_eq!(div(i32::MIN, -1), Err(CalcError::Overflow));
+        assert_eq!(mode(-7i16, 3), Ok(-1));
+        assert_eq!(mode(i64::MIN, -1), Ok(0));
+        assert_eq!(mode(5u128, 0), Err(CalcError::ModuloByZero));
+    }
+
+    #[test]
+    fn test_integer_pow_and_fact() {
+        assert_eq!(pow(2u16, 15), Ok(32768));
+        assert_eq!(pow(2i16, 15), Err(CalcError::Overflow));
+        assert!(matches!(pow(2i32, -1), Err(CalcError::Domain(_))));
+        assert_eq!(pow(-1i64, i64::MAX), Ok(-1));
+        assert_eq!(pow(1u64, u64::MAX - 1), Ok(1));
+        assert_eq!(pow(3u64, u64::MAX), Err(CalcError::Overflow));
+
+        assert_eq!(fact(0u8), Ok(1));
+        assert_eq!(fact(5u8), Ok(120));
+        assert_eq!(fact(6u8), Err(CalcError::Overflow));
+        assert_eq!(fact(-1i32), Err(CalcError::NegativeFactorial));
+        assert_eq!(
+            fact(34u128),
+            Ok(295_232_799_039_604_140_847_618_609_643_520_000_000)
+        );
+    }
+
+    #[test]
+    fn test_float_ops_match_crate_api() {
+        assert_eq!(add(2.5f64, 3.0), Ok(crate::add(2.5, 3.0)));
+        assert_eq!(div(1.0f64, 0.0), Err(CalcError::DivisionByZero));
+        assert_eq!(mode(10.5f32, 3.0), Ok(1.5));
+        assert_eq!(pow(2.0f32, 0.5), Ok(core::f32::consts::SQRT_2));
+        assert_eq!(fact(5.0f32), Ok(120.0));
+        assert_eq!(fact(35.0f32), Err(CalcError::Overflow));
+        assert_eq!(fact(2.5f64), crate::fact(2.5));
+        assert!(fact(2.5f64).is_ok());
+    }
+}
@@ -0,0 +1,382 @@
+//! Expressions compiled to a reusable stack bytecode.
+//!
+//! [`compile`] parses an expression once, resolves every function name and
+//! variable to a direct reference, and folds constant subexpressions, so
+//! evaluating the same formula per CSV row or per ADC sample is a tight loop
+//! over a few opcodes. [`Program::eval`] does not allocate: the value stack is
+//! a fixed array of [`STACK_SIZE`] slots, checked when the program is built.
+//!
+//! ```
+//! use xrust_calclib::compile;
+//!
+//! let volts = compile("raw * vref / 4095").unwrap();
+//! assert_eq!(volts.inputs(), ["raw", "vref"]);
+//! assert_eq!(volts.eval(&[4095.0, 3.3]), Ok(3.3));
+//! assert_eq!(volts.eval(&[0.0, 3.3]), Ok(0.0));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::expr::{self, BinOp, EvalError, Expr, Function};
+use crate::{AngleMode, CalcError};
+
+/// The deepest value stack a compiled program may need.
+pub const STACK_SIZE: usize = 32;
+
+/// One bytecode instruction.
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    /// Push a constant.
+    Const(f64),
+    /// Push the input in this slot.
+    Input(usize),
+    /// Negate the top of the stack.
+    Neg,
+    /// Pop two values and push the result.
+    Binary { op: BinOp, column: usize },
+    /// Pop the function's arguments and push the result.
+    Call { function: Function, column: usize },
+}
+
+impl Op {
+    /// How many values the instruction pops.
+    fn arity(self) -> usize {
+        match self {
+            Op::Const(_) | Op::Input(_) => 0,
+            Op::Neg => 1,
+            Op::Binary { .. } => 2,
+            Op::Call { function, .. } => function.arity(),
+        }
+    }
+
+    /// Runs an operator on the values it pops.
+    fn apply(self, args: &[f64], mode: AngleMode) -> Result<f64, EvalError> {
+        match self {
+            Op::Neg => Ok(-args[0]),
+            Op::Binary { op, column } => op
+                .apply(args[0], args[1])
+                .map_err(|err| EvalError::calc(column, err)),
+            Op::Call { function, column } => function
+                .apply(args, mode)
+                .map_err(|err| EvalError::calc(column, err)),
+            Op::Const(_) | Op::Input(_) => unreachable!("operands are pushed, not applied"),
+        }
+    }
+}
+
+/// A compiled expression with named input slots.
+#[derive(Debug, Clone)]
+pub struct Program {
+    ops: Vec<Op>,
+    inputs: Vec<String>,
+    angle_mode: AngleMode,
+}
+
+impl Program {
+    /// The input names, in slot order.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// The slot of the input called `name`.
+    pub fn slot(&self, name: &str) -> Option<usize> {
+        self.inputs.iter().position(|input| input == name)
+    }
+
+    /// The number of bytecode instructions.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Always `false`: every program produces a value.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// The angle unit trig functions use; radians unless changed.
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    /// Evaluates trig functions in `mode` from now on.
+    ///
+    /// ```
+    /// # #[cfg(feature = "fancy")] {
+    /// use xrust_calclib::{AngleMode, compile};
+    ///
+    /// let mut p = compile("sin(a)").unwrap();
+    /// p.set_angle_mode(AngleMode::Degrees);
+    /// assert_eq!(p.eval(&[90.0]), Ok(1.0));
+    /// # }
+    /// ```
+    pub fn set_angle_mode(&mut self, mode: AngleMode) {
+        self.angle_mode = mode;
+    }
+
+    /// The value of a program that folded down to a single constant.
+    pub fn constant(&self) -> Option<f64> {
+        match self.ops.as_slice() {
+            [Op::Const(value)] => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Evaluates the program with `inputs[i]` bound to slot `i`.
+    ///
+    /// Evaluation errors carry the column of the failing operator or call,
+    /// just like [`eval`](crate::eval). Passing the wrong number of inputs is
+    /// a [`CalcError::LengthMismatch`] reported at column 0.
+    pub fn eval(&self, inputs: &[f64]) -> Result<f64, EvalError> {
+        if inputs.len() != self.inputs.len() {
+            return Err(EvalError::calc(0, CalcError::LengthMismatch));
+        }
+        let mut stack = [0.0; STACK_SIZE];
+        let mut top = 0;
+        for &op in &self.ops {
+            match op {
+                Op::Const(value) => {
+                    stack[top] = value;
+                    top += 1;
+                }
+                Op::Input(slot) => {
+                    stack[top] = inputs[slot];
+                    top += 1;
+                }
+                _ => {
+                    let base = top - op.arity();
+                    stack[base] = op.apply(&stack[base..top], self.angle_mode)?;
+                    top = base + 1;
+                }
+            }
+        }
+        Ok(stack[0])
+    }
+}
+
+struct Compiler {
+    ops: Vec<Op>,
+    inputs: Vec<String>,
+    /// Whether identifiers not yet in `inputs` become new slots.
+    open: bool,
+    depth: usize,
+}
+
+impl Compiler {
+    fn push(&mut self, op: Op) -> Result<(), EvalError> {
+        if self.depth == STACK_SIZE {
+            return Err(EvalError::new(
+                1,
+                "Expression is too deeply nested to compile",
+            ));
+        }
+        self.depth += 1;
+        self.ops.push(op);
+        Ok(())
+    }
+
+    /// Emits an operator, folding it into a constant when all of its operands
+    /// are constants.
+    fn apply(&mut self, op: Op, foldable: bool) -> Result<(), EvalError> {
+        let arity = op.arity();
+        let base = self.ops.len() - arity;
+        let mut args = [0.0; 2];
+        let constant = self.ops[base..]
+            .iter()
+            .zip(&mut args)
+            .all(|(operand, arg)| match operand {
+                Op::Const(value) => {
+                    *arg = *value;
+                    true
+                }
+                _ => false,
+            });
+        if foldable && constant {
+            // Only calls that ignore the angle mode are folded.
+            let value = op.apply(&args[..arity], AngleMode::Radians)?;
+            self.ops.truncate(base);
+            self.ops.push(Op::Const(value));
+        } else {
+            self.ops.push(op);
+        }
+        self.depth = self.depth + 1 - arity;
+        Ok(())
+    }
+
+    fn emit(&mut self, expr: &Expr) -> Result<(), EvalError> {
+        match expr {
+            Expr::Num(n) | Expr::Decimal { value: n, .. } => self.push(Op::Const(*n)),
+            Expr::Neg(inner) => {
+                self.emit(inner)?;
+                self.apply(Op::Neg, true)
+            }
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => {
+                self.emit(lhs)?;
+                self.emit(rhs)?;
+                let op = Op::Binary {
+                    op: *op,
+                    column: *column,
+                };
+                self.apply(op, true)
+            }
+            Expr::Var { name, column } => {
+                let slot = match self.inputs.iter().position(|input| input == name) {
+                    Some(slot) => slot,
+                    None if self.open => {
+                        self.inputs.push(name.clone());
+                        self.inputs.len() - 1
+                    }
+                    None => return Err(expr::unknown_identifier(name, *column)),
+                };
+                self.push(Op::Input(slot))
+            }
+            Expr::Call { name, args, column } => {
+                let function = Function::resolve(name, args.len(), *column)?;
+                for arg in args {
+                    self.emit(arg)?;
+                }
+                let op = Op::Call {
+                    function,
+                    column: *column,
+                };
+                // Trig results depend on the angle mode at evaluation time.
+                self.apply(op, !function.uses_angle_mode())
+            }
+            Expr::WithUnit { column, .. } | Expr::Convert { column, .. } => {
+                Err(expr::units_unsupported(*column))
+            }
+        }
+    }
+}
+
+fn compile_expr(expr: &Expr, inputs: Vec<String>, open: bool) -> Result<Program, EvalError> {
+    let mut compiler = Compiler {
+        ops: Vec::new(),
+        inputs,
+        open,
+        depth: 0,
+    };
+    compiler.emit(expr)?;
+    Ok(Program {
+        ops: compiler.ops,
+        inputs: compiler.inputs,
+        angle_mode: AngleMode::Radians,
+    })
+}
+
+/// Compiles an infix expression, giving each identifier an input slot in
+/// order of first appearance.
+///
+/// Accepts the same syntax and functions as [`eval`](crate::eval). Constant
+/// subexpressions are evaluated here, so an error they raise, such as the
+/// `1/0` in `x + 1/0`, is returned by `compile` rather than by every call to
+/// [`Program::eval`].
+///
+/// ```
+/// use xrust_calclib::compile;
+///
+/// let p = compile("x^2 + 2^10 * y").unwrap();
+/// assert_eq!(p.slot("y"), Some(1));
+/// assert_eq!(p.eval(&[3.0, 0.5]), Ok(521.0));
+/// assert_eq!(compile("sqrt(16) * 2").unwrap().constant(), Some(8.0));
+/// ```
+pub fn compile(src: &str) -> Result<Program, EvalError> {
+    compile_expr(&expr::parse(src)?, Vec::new(), true)
+}
+
+/// Compiles an infix expression with a fixed slot order, such as the columns
+/// of a CSV file. Identifiers that are not in `inputs` are an error; inputs
+/// the expression does not use are allowed.
+///
+/// ```
+/// use xrust_calclib::program::compile_with_inputs;
+///
+/// let p = compile_with_inputs("b - a", &["a", "b", "c"]).unwrap();
+/// assert_eq!(p.eval(&[1.0, 5.0, 9.0]), Ok(4.0));
+/// assert!(compile_with_inputs("a + d", &["a", "b"]).is_err());
+/// ```
+pub fn compile_with_inputs(src: &str, inputs: &[&str]) -> Result<Program, EvalError> {
+    expr::parse(src)?.compile_with_inputs(inputs)
+}
+
+impl Expr {
+    /// Compiles an already parsed tree, such as a symbolic
+    /// [`derivative`](Expr::derivative); see [`compile_with_inputs`].
+    pub fn compile_with_inputs(&self, inputs: &[&str]) -> Result<Program, EvalError> {
+        let inputs = inputs.iter().map(|name| name.to_string()).collect();
+        compile_expr(self, inputs, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::EvalErrorKind;
+    use std::format;
+
+    #[test]
+    fn test_matches_tree_evaluation() {
+        let src = "(x + 1) * (x - 1) / 2 + fact(y) % 7 - -x^2";
+        let p = compile(src).unwrap();
+        assert_eq!(p.inputs(), ["x", "y"]);
+        for (x, y) in [(0.0, 3.0), (2.5, 4.0), (-7.0, 0.0)] {
+            let direct = src
+                .replace('x', &format!("({})", x))
+                .replace('y', &format!("({})", y));
+            assert_eq!(p.eval(&[x, y]), crate::eval(&direct));
+        }
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        assert_eq!(
+            compile("2 * (3 + 4) - sqrt(16)").unwrap().constant(),
+            Some(10.0)
+        );
+        // `x * (2 + 3)` folds to three instructions: x, 5, *.
+        let p = compile("x * (2 + 3)").unwrap();
+        assert_eq!(p.len(), 3);
+        assert_eq!(p.constant(), None);
+        assert_eq!(p.eval(&[4.0]), Ok(20.0));
+        // Trig calls follow the angle mode at evaluation time, so stay unfolded.
+        #[cfg(feature = "fancy")]
+        assert_eq!(compile("sin(0)").unwrap().constant(), None);
+
+        let err = compile("x + 1/0").unwrap_err();
+        assert_eq!(err.column, 6);
+        assert_eq!(err.kind, EvalErrorKind::Calc(CalcError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_errors() {
+        let p = compile("10 / (x - 2)").unwrap();
+        let err = p.eval(&[2.0]).unwrap_err();
+        assert_eq!(
+            (err.column, err.kind),
+            (4, EvalErrorKind::Calc(CalcError::DivisionByZero))
+        );
+        assert_eq!(
+            p.eval(&[]).unwrap_err().kind,
+            EvalErrorKind::Calc(CalcError::LengthMismatch)
+        );
+
+        assert_eq!(compile("nope(1)").unwrap_err().column, 1);
+        assert_eq!(compile("3 ms").unwrap_err().column, 3);
+        assert_eq!(compile_with_inputs("a + b", &["a"]).unwrap_err().column, 5);
+
+        let deep = "(1+".repeat(STACK_SIZE) + "x" + &")".repeat(STACK_SIZE);
+        assert!(compile(&deep).is_err());
+        let shallow = "(1+".repeat(STACK_SIZE - 2) + "x" + &")".repeat(STACK_SIZE - 2);
+        assert_eq!(
+            compile(&shallow).unwrap().eval(&[0.0]),
+            Ok((STACK_SIZE - 2) as f64)
+        );
+    }
+}
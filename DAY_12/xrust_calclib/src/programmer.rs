@@ -0,0 +1,457 @@
+//! Programmer mode: fixed-width integers, base prefixes and bitwise operations.
+//!
+//! A [`Word`] is an 8-, 16-, 32- or 64-bit register that is either signed or
+//! unsigned. Its bits are stored as they would be in hardware, so `0xFF` read
+//! into a signed 8-bit word is `-1`, and the `{:b}`/`{:o}`/`{:x}`/`{:X}`
+//! formatters show the two's-complement pattern while `{}` shows the value:
+//!
+//! ```
+//! use xrust_calclib::programmer::{Word, WordSize};
+//!
+//! // TCCR1A = (1 << COM1A1) | (1 << WGM11)
+//! let one = Word::new(1, WordSize::W8, false).unwrap();
+//! let tccr1a = one.shift_left(7).unwrap() | one.shift_left(1).unwrap();
+//! assert_eq!(format!("{:#04x}", tccr1a), "0x82");
+//! assert_eq!(tccr1a.twos_complement().to_string(), "1000 0010");
+//!
+//! let w = Word::parse("0xFF", WordSize::W8, true).unwrap();
+//! assert_eq!(w.value(), -1);
+//! ```
+
+use core::fmt;
+use core::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::CalcError;
+
+/// Parses an integer literal: decimal, or `0x` hex, `0b` binary or `0o`
+/// octal, with an optional sign and `_` separators (`-0b1010_0000`).
+pub fn parse_int(s: &str) -> Result<i128, CalcError> {
+    Ok(parse_literal(s)?.0)
+}
+
+/// Parses an integer literal like [`parse_int`] into an `f64`, for
+/// calculators that compute in floating point.
+///
+/// Values that an `f64` cannot hold exactly, such as `0xFFFF_FFFF_FFFF_FFFF`,
+/// are a [`CalcError::Domain`] error rather than silently rounded.
+///
+/// ```
+/// use xrust_calclib::programmer::parse_int_f64;
+///
+/// assert_eq!(parse_int_f64("-0xFF"), Ok(-255.0));
+/// assert_eq!(parse_int_f64("0x20_0000_0000_0000"), Ok(9007199254740992.0));
+/// assert!(parse_int_f64("0x20_0000_0000_0001").is_err());
+/// assert!(parse_int_f64("0x+FF").is_err());
+/// ```
+pub fn parse_int_f64(s: &str) -> Result<f64, CalcError> {
+    let value = parse_int(s)?;
+    let magnitude = value.unsigned_abs();
+    // Exact when the significant bits fit in the 53-bit mantissa.
+    if magnitude != 0
+        && 128 - magnitude.leading_zeros() - magnitude.trailing_zeros() > f64::MANTISSA_DIGITS
+    {
+        return Err(CalcError::Domain(
+            "Integer literal cannot be represented exactly as a float",
+        ));
+    }
+    Ok(value as f64)
+}
+
+/// Returns the value and whether it was written with a base prefix.
+fn parse_literal(s: &str) -> Result<(i128, bool), CalcError> {
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (radix, digits) = match body.get(..2) {
+        Some("0x" | "0X") => (16, &body[2..]),
+        Some("0b" | "0B") => (2, &body[2..]),
+        Some("0o" | "0O") => (8, &body[2..]),
+        _ => (10, body),
+    };
+    if digits.is_empty() || digits.starts_with('_') {
+        return Err(CalcError::Domain("Integer literal has no digits"));
+    }
+    let mut magnitude: u128 = 0;
+    for c in digits.chars().filter(|&c| c != '_') {
+        let d = c
+            .to_digit(radix)
+            .ok_or(CalcError::Domain("Invalid digit in integer literal"))?;
+        magnitude = magnitude
+            .checked_mul(radix.into())
+            .and_then(|m| m.checked_add(d.into()))
+            .ok_or(CalcError::Overflow)?;
+    }
+    let value = if negative {
+        0i128.checked_sub_unsigned(magnitude)
+    } else {
+        i128::try_from(magnitude).ok()
+    };
+    Ok((value.ok_or(CalcError::Overflow)?, radix != 10))
+}
+
+/// The width of a [`Word`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordSize {
+    W8,
+    W16,
+    W32,
+    W64,
+}
+
+impl WordSize {
+    /// The number of bits.
+    pub const fn bits(self) -> u32 {
+        match self {
+            WordSize::W8 => 8,
+            WordSize::W16 => 16,
+            WordSize::W32 => 32,
+            WordSize::W64 => 64,
+        }
+    }
+
+    const fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.bits())
+    }
+}
+
+/// A fixed-width integer register, signed or unsigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Word {
+    raw: u64,
+    size: WordSize,
+    signed: bool,
+}
+
+impl Word {
+    /// Builds a word holding `value`, or [`CalcError::Overflow`] if it does not
+    /// fit the width and signedness.
+    pub fn new(value: i128, size: WordSize, signed: bool) -> Result<Self, CalcError> {
+        let bits = size.bits();
+        let (min, max) = if signed {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        };
+        if value < min || value > max {
+            return Err(CalcError::Overflow);
+        }
+        Ok(Word::from_bits(value as u64, size, signed))
+    }
+
+    /// Builds a word from a raw bit pattern, keeping only the low `size` bits.
+    pub const fn from_bits(raw: u64, size: WordSize, signed: bool) -> Self {
+        Word {
+            raw: raw & size.mask(),
+            size,
+            signed,
+        }
+    }
+
+    /// Parses a literal (see [`parse_int`]). Prefixed literals are bit
+    /// patterns, so `0xFF` is `-1` in a signed 8-bit word; decimal and negative
+    /// literals must fit the value range.
+    pub fn parse(s: &str, size: WordSize, signed: bool) -> Result<Self, CalcError> {
+        let (value, prefixed) = parse_literal(s)?;
+        if prefixed && value >= 0 {
+            if value > size.mask() as i128 {
+                return Err(CalcError::Overflow);
+            }
+            Ok(Word::from_bits(value as u64, size, signed))
+        } else {
+            Word::new(value, size, signed)
+        }
+    }
+
+    /// The raw bit pattern, zero-extended to 64 bits.
+    pub const fn to_bits(self) -> u64 {
+        self.raw
+    }
+
+    /// The value the bits represent, honouring signedness.
+    pub const fn value(self) -> i128 {
+        let bits = self.size.bits();
+        if self.signed && self.raw >> (bits - 1) & 1 == 1 {
+            self.raw as i128 - (1i128 << bits)
+        } else {
+            self.raw as i128
+        }
+    }
+
+    /// The width.
+    pub const fn size(self) -> WordSize {
+        self.size
+    }
+
+    /// Whether the word is signed.
+    pub const fn is_signed(self) -> bool {
+        self.signed
+    }
+
+    /// Converts to another width and signedness like an `as` cast: widening
+    /// sign-extends signed words, narrowing truncates.
+    pub const fn cast(self, size: WordSize, signed: bool) -> Self {
+        Word::from_bits(self.value() as u64, size, signed)
+    }
+
+    fn check_shift(self, n: u32) -> Result<(), CalcError> {
+        if n >= self.size.bits() {
+            Err(CalcError::Domain(
+                "Shift amount must be less than the word size",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Shifts left by `n` bits, discarding bits shifted out.
+    pub fn shift_left(self, n: u32) -> Result<Self, CalcError> {
+        self.check_shift(n)?;
+        Ok(self.with_bits(self.raw << n))
+    }
+
+    /// Shifts right by `n` bits: arithmetic (sign-filling) for signed words,
+    /// logical for unsigned ones.
+    pub fn shift_right(self, n: u32) -> Result<Self, CalcError> {
+        self.check_shift(n)?;
+        if self.signed {
+            Ok(self.with_bits((self.value() >> n) as u64))
+        } else {
+            Ok(self.with_bits(self.raw >> n))
+        }
+    }
+
+    /// Rotates left by `n` bits within the word width.
+    pub fn rotate_left(self, n: u32) -> Self {
+        let bits = self.size.bits();
+        let n = n % bits;
+        if n == 0 {
+            return self;
+        }
+        self.with_bits(self.raw << n | self.raw >> (bits - n))
+    }
+
+    /// Rotates right by `n` bits within the word width.
+    pub fn rotate_right(self, n: u32) -> Self {
+        let bits = self.size.bits();
+        self.rotate_left(bits - n % bits)
+    }
+
+    /// Formats the full-width bit pattern in groups of four, e.g. `1111 1110`.
+    pub fn twos_complement(self) -> TwosComplement {
+        TwosComplement(self)
+    }
+
+    fn with_bits(self, raw: u64) -> Self {
+        Word::from_bits(raw, self.size, self.signed)
+    }
+}
+
+/// The bitwise operators keep the left operand's width and signedness; the
+/// right operand contributes its raw bits.
+impl BitAnd for Word {
+    type Output = Word;
+
+    fn bitand(self, rhs: Word) -> Word {
+        self.with_bits(self.raw & rhs.raw)
+    }
+}
+
+impl BitOr for Word {
+    type Output = Word;
+
+    fn bitor(self, rhs: Word) -> Word {
+        self.with_bits(self.raw | rhs.raw)
+    }
+}
+
+impl BitXor for Word {
+    type Output = Word;
+
+    fn bitxor(self, rhs: Word) -> Word {
+        self.with_bits(self.raw ^ rhs.raw)
+    }
+}
+
+impl Not for Word {
+    type Output = Word;
+
+    fn not(self) -> Word {
+        self.with_bits(!self.raw)
+    }
+}
+
+/// Formats the value in decimal.
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value(), f)
+    }
+}
+
+impl fmt::Binary for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&self.raw, f)
+    }
+}
+
+impl fmt::Octal for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Octal::fmt(&self.raw, f)
+    }
+}
+
+impl fmt::LowerHex for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.raw, f)
+    }
+}
+
+impl fmt::UpperHex for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.raw, f)
+    }
+}
+
+/// The grouped bit pattern returned by [`Word::twos_complement`].
+#[derive(Debug, Clone, Copy)]
+pub struct TwosComplement(Word);
+
+impl fmt::Display for TwosComplement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits = self.0.size.bits();
+        for i in (0..bits).rev() {
+            let bit = if self.0.raw >> i & 1 == 1 { '1' } else { '0' };
+            fmt::Write::write_char(f, bit)?;
+            if i % 4 == 0 && i != 0 {
+                f.write_str(" ")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::format;
+    use std::string::ToString;
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int("0x1F"), Ok(31));
+        assert_eq!(parse_int("0B1010"), Ok(10));
+        assert_eq!(parse_int("0o17"), Ok(15));
+        assert_eq!(parse_int("-0b1010_0000"), Ok(-160));
+        assert_eq!(parse_int("+255"), Ok(255));
+        assert_eq!(parse_int("0xffff_ffff_ffff_ffff"), Ok(u64::MAX as i128));
+        assert!(matches!(parse_int("0x"), Err(CalcError::Domain(_))));
+        assert!(matches!(parse_int("0b102"), Err(CalcError::Domain(_))));
+        assert!(matches!(parse_int("12a"), Err(CalcError::Domain(_))));
+        assert_eq!(
+            parse_int("0x1_0000_0000_0000_0000_0000_0000_0000_0000"),
+            Err(CalcError::Overflow)
+        );
+        // A sign is only allowed before the prefix.
+        assert!(matches!(parse_int("0x+FF"), Err(CalcError::Domain(_))));
+        assert!(matches!(parse_int("0b-1"), Err(CalcError::Domain(_))));
+    }
+
+    #[test]
+    fn test_parse_int_f64() {
+        assert_eq!(parse_int_f64("0"), Ok(0.0));
+        assert_eq!(parse_int_f64("-0b1010"), Ok(-10.0));
+        assert_eq!(
+            parse_int_f64("0xFFFF_FFFF_FFFF_F800"),
+            Ok(18446744073709549568.0)
+        );
+        assert_eq!(
+            parse_int_f64(&format!("{}", 1u64 << 53)),
+            Ok(9007199254740992.0)
+        );
+        assert_eq!(
+            parse_int_f64(&format!("-{}", (1u64 << 53) - 1)),
+            Ok(-9007199254740991.0)
+        );
+        assert!(matches!(
+            parse_int_f64(&format!("{}", (1u64 << 53) + 1)),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            parse_int_f64("0xFFFF_FFFF_FFFF_FFFF"),
+            Err(CalcError::Domain(_))
+        ));
+        assert_eq!(parse_int_f64("0x+1"), parse_int("0x+1").map(|v| v as f64));
+    }
+
+    #[test]
+    fn test_word_ranges() {
+        let w = Word::parse("0xFF", WordSize::W8, true).unwrap();
+        assert_eq!(w.value(), -1);
+        assert_eq!(
+            Word::parse("255", WordSize::W8, true),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(
+            Word::parse("0x100", WordSize::W8, false),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(
+            Word::new(-129, WordSize::W8, true),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(
+            Word::new(-1, WordSize::W16, false),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(
+            Word::new(i64::MIN.into(), WordSize::W64, true)
+                .unwrap()
+                .value(),
+            i64::MIN.into()
+        );
+
+        let neg = Word::new(-2, WordSize::W8, true).unwrap();
+        assert_eq!(neg.to_bits(), 0xFE);
+        assert_eq!(neg.cast(WordSize::W16, true).value(), -2);
+        assert_eq!(neg.cast(WordSize::W16, false).value(), 0xFFFE);
+        let big = Word::new(0x1234, WordSize::W16, false).unwrap();
+        assert_eq!(big.cast(WordSize::W8, false).value(), 0x34);
+    }
+
+    #[test]
+    fn test_bitwise() {
+        let a = Word::parse("0b1100", WordSize::W8, false).unwrap();
+        let b = Word::parse("0b1010", WordSize::W8, false).unwrap();
+        assert_eq!((a & b).value(), 0b1000);
+        assert_eq!((a | b).value(), 0b1110);
+        assert_eq!((a ^ b).value(), 0b0110);
+        assert_eq!((!a).value(), 0b1111_0011);
+
+        assert_eq!(a.shift_left(4).unwrap().value(), 0b1100_0000);
+        assert_eq!(a.shift_left(5).unwrap().value(), 0b1000_0000);
+        assert!(a.shift_left(8).is_err());
+        assert_eq!(a.rotate_left(6).value(), 0b0000_0011);
+        assert_eq!(a.rotate_right(3).value(), 0b1000_0001);
+        assert_eq!(a.rotate_left(8), a);
+
+        let s = Word::new(-16, WordSize::W8, true).unwrap();
+        assert_eq!(s.shift_right(2).unwrap().value(), -4);
+        let u = s.cast(WordSize::W8, false);
+        assert_eq!(u.shift_right(2).unwrap().value(), 0b0011_1100);
+        assert_eq!((!Word::new(0, WordSize::W32, true).unwrap()).value(), -1);
+    }
+
+    #[test]
+    fn test_formatting() {
+        let w = Word::new(-2, WordSize::W16, true).unwrap();
+        assert_eq!(w.to_string(), "-2");
+        assert_eq!(format!("{:x}", w), "fffe");
+        assert_eq!(format!("{:#X}", w), "0xFFFE");
+        assert_eq!(format!("{:o}", w), "177776");
+        assert_eq!(format!("{:#018b}", w), "0b1111111111111110");
+        assert_eq!(w.twos_complement().to_string(), "1111 1111 1111 1110");
+        let five = Word::new(5, WordSize::W8, false).unwrap();
+        assert_eq!(five.twos_complement().to_string(), "0000 0101");
+    }
+}
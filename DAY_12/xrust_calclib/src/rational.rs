@@ -0,0 +1,657 @@
+//! Exact rational numbers.
+//!
+//! [`Rational`] keeps a numerator and denominator over `i64`, always reduced
+//! by their GCD with a positive denominator, so `sub(7.0, 2.3)` can be
+//! answered as exactly `47/10` instead of whatever `f64` rounding produces.
+//! Operations that would overflow `i64` return [`CalcError::Overflow`]; the
+//! `bigint` feature adds [`BigRational`], which never overflows.
+//!
+//! ```
+//! use xrust_calclib::rational::Rational;
+//!
+//! let a: Rational = "7".parse().unwrap();
+//! let b: Rational = "2.3".parse().unwrap();
+//! assert_eq!(a.checked_sub(b).unwrap().to_string(), "47/10");
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{CalcError, Numeric};
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// A reduced fraction `numer / denom` with `denom > 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i64,
+    den: i64,
+}
+
+impl Rational {
+    /// Zero.
+    pub const ZERO: Self = Rational { num: 0, den: 1 };
+    /// One.
+    pub const ONE: Self = Rational { num: 1, den: 1 };
+
+    /// Builds `num / den` in lowest terms, or errors if `den` is zero.
+    pub fn new(num: i64, den: i64) -> Result<Self, CalcError> {
+        Self::reduce(num as i128, den as i128)
+    }
+
+    /// The integer `n` as a rational.
+    pub const fn from_int(n: i64) -> Self {
+        Rational { num: n, den: 1 }
+    }
+
+    /// Normalizes a wide fraction and narrows it back to `i64`.
+    fn reduce(num: i128, den: i128) -> Result<Self, CalcError> {
+        if den == 0 {
+            return Err(CalcError::DivisionByZero);
+        }
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs()) as i128;
+        let g = if den < 0 { -g } else { g };
+        let (num, den) = (num / g, den / g);
+        match (i64::try_from(num), i64::try_from(den)) {
+            (Ok(num), Ok(den)) => Ok(Rational { num, den }),
+            _ => Err(CalcError::Overflow),
+        }
+    }
+
+    /// The numerator, carrying the sign.
+    pub const fn numer(self) -> i64 {
+        self.num
+    }
+
+    /// The denominator, always positive.
+    pub const fn denom(self) -> i64 {
+        self.den
+    }
+
+    /// Whether the value is a whole number.
+    pub const fn is_integer(self) -> bool {
+        self.den == 1
+    }
+
+    /// The nearest `f64`.
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Converts through the shortest decimal that round-trips `x`, so `2.3`
+    /// becomes `23/10` rather than the binary value `2.29999…`.
+    #[cfg(feature = "alloc")]
+    pub fn from_f64(x: f64) -> Result<Self, CalcError> {
+        if !x.is_finite() {
+            return Err(CalcError::Domain(
+                "Only finite numbers have a rational value",
+            ));
+        }
+        alloc::format!("{}", x).parse()
+    }
+
+    /// `self + rhs`.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, CalcError> {
+        let (a, b, c, d) = self.wide(rhs);
+        // Each product fits in i128, but their sum can exceed it.
+        let num = (a * d).checked_add(c * b).ok_or(CalcError::Overflow)?;
+        Self::reduce(num, b * d)
+    }
+
+    /// `self - rhs`.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, CalcError> {
+        let (a, b, c, d) = self.wide(rhs);
+        let num = (a * d).checked_sub(c * b).ok_or(CalcError::Overflow)?;
+        Self::reduce(num, b * d)
+    }
+
+    /// `self * rhs`.
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, CalcError> {
+        let (a, b, c, d) = self.wide(rhs);
+        Self::reduce(a * c, b * d)
+    }
+
+    /// `self / rhs`, or an error if `rhs` is zero.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, CalcError> {
+        let (a, b, c, d) = self.wide(rhs);
+        Self::reduce(a * d, b * c)
+    }
+
+    /// `self % rhs`, the remainder after truncating division, with the sign of `self`.
+    pub fn checked_rem(self, rhs: Self) -> Result<Self, CalcError> {
+        if rhs.num == 0 {
+            return Err(CalcError::ModuloByZero);
+        }
+        let quotient = self.checked_div(rhs)?.trunc();
+        self.checked_sub(rhs.checked_mul(quotient)?)
+    }
+
+    /// `-self`.
+    pub fn checked_neg(self) -> Result<Self, CalcError> {
+        self.num
+            .checked_neg()
+            .map(|num| Rational { num, den: self.den })
+            .ok_or(CalcError::Overflow)
+    }
+
+    /// `|self|`.
+    pub fn checked_abs(self) -> Result<Self, CalcError> {
+        if self.num < 0 {
+            self.checked_neg()
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// `1 / self`, or an error if `self` is zero.
+    pub fn recip(self) -> Result<Self, CalcError> {
+        Self::reduce(self.den as i128, self.num as i128)
+    }
+
+    /// The integer part, truncated towards zero.
+    pub const fn trunc(self) -> Self {
+        Rational::from_int(self.num / self.den)
+    }
+
+    /// `self` raised to an integer power; negative powers take the reciprocal.
+    pub fn pow(self, exp: i32) -> Result<Self, CalcError> {
+        let base = if exp < 0 { self.recip()? } else { self };
+        let mut e = exp.unsigned_abs();
+        let (mut num, mut den) = (1i64, 1i64);
+        let (mut bn, mut bd) = (base.num, base.den);
+        while e > 0 {
+            if e & 1 == 1 {
+                num = num.checked_mul(bn).ok_or(CalcError::Overflow)?;
+                den = den.checked_mul(bd).ok_or(CalcError::Overflow)?;
+            }
+            e >>= 1;
+            if e > 0 {
+                bn = bn.checked_mul(bn).ok_or(CalcError::Overflow)?;
+                bd = bd.checked_mul(bd).ok_or(CalcError::Overflow)?;
+            }
+        }
+        // Powers of a reduced fraction stay reduced.
+        Ok(Rational { num, den })
+    }
+
+    /// Exact square root, if both numerator and denominator are perfect squares.
+    pub fn sqrt(self) -> Result<Self, CalcError> {
+        if self.num < 0 {
+            return Err(CalcError::NegativeSqrt);
+        }
+        let (n, d) = (self.num.isqrt(), self.den.isqrt());
+        if n * n == self.num && d * d == self.den {
+            Ok(Rational { num: n, den: d })
+        } else {
+            Err(CalcError::Domain("Square root has no exact rational value"))
+        }
+    }
+
+    fn wide(self, rhs: Self) -> (i128, i128, i128, i128) {
+        (
+            self.num as i128,
+            self.den as i128,
+            rhs.num as i128,
+            rhs.den as i128,
+        )
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Rational::ZERO
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Rational::from_int(n)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, c, d) = self.wide(*other);
+        (a * d).cmp(&(c * b))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Splits a decimal literal into sign, integer digits, fraction digits and
+/// exponent: `"-2.5e3"` becomes `(true, "2", "5", 3)`.
+fn parse_decimal(s: &str) -> Option<(bool, &str, &str, i32)> {
+    let (negative, rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (mantissa, exp) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], rest[i + 1..].parse::<i32>().ok()?),
+        None => (rest, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits_ok = |d: &str| d.bytes().all(|b| b.is_ascii_digit());
+    if int.is_empty() && frac.is_empty() || !digits_ok(int) || !digits_ok(frac) {
+        return None;
+    }
+    Some((negative, int, frac, exp))
+}
+
+const INVALID_LITERAL: CalcError = CalcError::Domain("Invalid rational literal");
+
+/// Parses integers (`"42"`), fractions (`"3/4"`) and decimals with an optional
+/// exponent (`"2.3"`, `"-1.5e-3"`) exactly.
+impl FromStr for Rational {
+    type Err = CalcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((n, d)) = s.split_once('/') {
+            let n = n.trim().parse::<i64>().map_err(|_| INVALID_LITERAL)?;
+            let d = d.trim().parse::<i64>().map_err(|_| INVALID_LITERAL)?;
+            return Rational::new(n, d);
+        }
+
+        let (negative, int, frac, exp) = parse_decimal(s).ok_or(INVALID_LITERAL)?;
+        let mut num: i128 = 0;
+        for b in int.bytes().chain(frac.bytes()) {
+            num = num
+                .checked_mul(10)
+                .and_then(|n| n.checked_add((b - b'0') as i128))
+                .ok_or(CalcError::Overflow)?;
+        }
+        // Zero is exact whatever the exponent, e.g. `0e999`.
+        if num == 0 {
+            return Ok(Rational::ZERO);
+        }
+        if negative {
+            num = -num;
+        }
+        let scale = exp as i64 - frac.len() as i64;
+        let ten = u32::try_from(scale.unsigned_abs())
+            .ok()
+            .and_then(|scale| 10i128.checked_pow(scale))
+            .ok_or(CalcError::Overflow)?;
+        if scale >= 0 {
+            Rational::reduce(num.checked_mul(ten).ok_or(CalcError::Overflow)?, 1)
+        } else {
+            Rational::reduce(num, ten)
+        }
+    }
+}
+
+/// Plugs rationals into the generic [`numeric`](crate::numeric) API.
+impl Numeric for Rational {
+    const ZERO: Self = Rational::ZERO;
+    const ONE: Self = Rational::ONE;
+
+    fn try_add(self, rhs: Self) -> Result<Self, CalcError> {
+        self.checked_add(rhs)
+    }
+
+    fn try_sub(self, rhs: Self) -> Result<Self, CalcError> {
+        self.checked_sub(rhs)
+    }
+
+    fn try_mul(self, rhs: Self) -> Result<Self, CalcError> {
+        self.checked_mul(rhs)
+    }
+
+    fn try_div(self, rhs: Self) -> Result<Self, CalcError> {
+        self.checked_div(rhs)
+    }
+
+    fn try_rem(self, rhs: Self) -> Result<Self, CalcError> {
+        self.checked_rem(rhs)
+    }
+
+    fn try_pow(self, exp: Self) -> Result<Self, CalcError> {
+        if !exp.is_integer() {
+            return Err(CalcError::Domain(
+                "Rational powers need an integer exponent",
+            ));
+        }
+        let exp = i32::try_from(exp.num).map_err(|_| CalcError::Overflow)?;
+        self.pow(exp)
+    }
+
+    fn try_fact(self) -> Result<Self, CalcError> {
+        if self.num < 0 {
+            return Err(CalcError::NegativeFactorial);
+        }
+        if !self.is_integer() {
+            return Err(CalcError::NonIntegerFactorial);
+        }
+        self.num.try_fact().map(Rational::from_int)
+    }
+}
+
+#[cfg(feature = "bigint")]
+pub use big::BigRational;
+
+#[cfg(feature = "bigint")]
+mod big {
+    use core::cmp::Ordering;
+    use core::fmt;
+    use core::ops::{Add, Mul, Neg, Sub};
+    use core::str::FromStr;
+
+    use num_bigint::BigInt;
+    use num_integer::Integer;
+    use num_traits::{One, Signed, ToPrimitive, Zero};
+
+    use super::{INVALID_LITERAL, Rational, parse_decimal};
+    use crate::CalcError;
+
+    /// An arbitrary-precision reduced fraction; never overflows.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct BigRational {
+        num: BigInt,
+        den: BigInt,
+    }
+
+    impl BigRational {
+        /// Builds `num / den` in lowest terms, or errors if `den` is zero.
+        pub fn new(num: BigInt, den: BigInt) -> Result<Self, CalcError> {
+            if den.is_zero() {
+                return Err(CalcError::DivisionByZero);
+            }
+            let mut g = num.gcd(&den);
+            if den.is_negative() {
+                g = -g;
+            }
+            Ok(BigRational {
+                num: num / &g,
+                den: den / g,
+            })
+        }
+
+        /// The numerator, carrying the sign.
+        pub fn numer(&self) -> &BigInt {
+            &self.num
+        }
+
+        /// The denominator, always positive.
+        pub fn denom(&self) -> &BigInt {
+            &self.den
+        }
+
+        /// Whether the value is a whole number.
+        pub fn is_integer(&self) -> bool {
+            self.den.is_one()
+        }
+
+        /// The nearest `f64`, or infinity/NaN when a part exceeds the `f64` range.
+        pub fn to_f64(&self) -> f64 {
+            let n = self.num.to_f64().unwrap_or(f64::NAN);
+            let d = self.den.to_f64().unwrap_or(f64::NAN);
+            n / d
+        }
+
+        /// `self / rhs`, or an error if `rhs` is zero.
+        pub fn checked_div(&self, rhs: &Self) -> Result<Self, CalcError> {
+            BigRational::new(&self.num * &rhs.den, &self.den * &rhs.num)
+        }
+
+        /// `self` raised to an integer power; negative powers take the reciprocal.
+        pub fn pow(&self, exp: i32) -> Result<Self, CalcError> {
+            let e = exp.unsigned_abs();
+            let (num, den) = (self.num.pow(e), self.den.pow(e));
+            if exp < 0 {
+                BigRational::new(den, num)
+            } else {
+                Ok(BigRational { num, den })
+            }
+        }
+    }
+
+    impl From<Rational> for BigRational {
+        fn from(r: Rational) -> Self {
+            BigRational {
+                num: r.numer().into(),
+                den: r.denom().into(),
+            }
+        }
+    }
+
+    impl Add for &BigRational {
+        type Output = BigRational;
+
+        fn add(self, rhs: Self) -> BigRational {
+            let num = &self.num * &rhs.den + &rhs.num * &self.den;
+            BigRational::new(num, &self.den * &rhs.den).expect("denominators are non-zero")
+        }
+    }
+
+    impl Sub for &BigRational {
+        type Output = BigRational;
+
+        fn sub(self, rhs: Self) -> BigRational {
+            let num = &self.num * &rhs.den - &rhs.num * &self.den;
+            BigRational::new(num, &self.den * &rhs.den).expect("denominators are non-zero")
+        }
+    }
+
+    impl Mul for &BigRational {
+        type Output = BigRational;
+
+        fn mul(self, rhs: Self) -> BigRational {
+            BigRational::new(&self.num * &rhs.num, &self.den * &rhs.den)
+                .expect("denominators are non-zero")
+        }
+    }
+
+    impl Neg for BigRational {
+        type Output = BigRational;
+
+        fn neg(self) -> BigRational {
+            BigRational {
+                num: -self.num,
+                den: self.den,
+            }
+        }
+    }
+
+    impl Ord for BigRational {
+        fn cmp(&self, other: &Self) -> Ordering {
+            (&self.num * &other.den).cmp(&(&other.num * &self.den))
+        }
+    }
+
+    impl PartialOrd for BigRational {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl fmt::Display for BigRational {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.den.is_one() {
+                write!(f, "{}", self.num)
+            } else {
+                write!(f, "{}/{}", self.num, self.den)
+            }
+        }
+    }
+
+    /// The largest exponent magnitude [`BigRational`] parses, so a short
+    /// literal such as `"1e999999999"` cannot demand gigabytes of digits.
+    const MAX_EXPONENT: u32 = 100_000;
+
+    /// Accepts the same literals as [`Rational`]. Only an exponent beyond
+    /// ±100 000 is an overflow.
+    impl FromStr for BigRational {
+        type Err = CalcError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let s = s.trim();
+            if let Some((n, d)) = s.split_once('/') {
+                let n = n.trim().parse::<BigInt>().map_err(|_| INVALID_LITERAL)?;
+                let d = d.trim().parse::<BigInt>().map_err(|_| INVALID_LITERAL)?;
+                return BigRational::new(n, d);
+            }
+
+            let (negative, int, frac, exp) = parse_decimal(s).ok_or(INVALID_LITERAL)?;
+            let digits = alloc::format!("{}{}", int, frac);
+            let mut num: BigInt = digits.parse().map_err(|_| INVALID_LITERAL)?;
+            if num.is_zero() {
+                return BigRational::new(num, BigInt::one());
+            }
+            if exp.unsigned_abs() > MAX_EXPONENT {
+                return Err(CalcError::Overflow);
+            }
+            if negative {
+                num = -num;
+            }
+            let scale = exp as i64 - frac.len() as i64;
+            // At most `MAX_EXPONENT` more digits than the literal itself.
+            let ten = BigInt::from(10u8).pow(scale.unsigned_abs() as u32);
+            if scale >= 0 {
+                BigRational::new(num * ten, BigInt::one())
+            } else {
+                BigRational::new(num, ten)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::string::ToString;
+
+    fn q(s: &str) -> Rational {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_normalization_and_parsing() {
+        assert_eq!(Rational::new(6, -8).unwrap(), q("-3/4"));
+        assert_eq!(Rational::new(0, -5).unwrap(), Rational::ZERO);
+        assert_eq!(Rational::new(1, 0), Err(CalcError::DivisionByZero));
+        assert_eq!(q("2.3"), Rational::new(23, 10).unwrap());
+        assert_eq!(q("-0.125"), Rational::new(-1, 8).unwrap());
+        assert_eq!(q("1.5e3"), Rational::from_int(1500));
+        assert_eq!(q("25e-2"), Rational::new(1, 4).unwrap());
+        assert_eq!(q(".5"), Rational::new(1, 2).unwrap());
+        assert!("1.2.3".parse::<Rational>().is_err());
+        assert!("abc".parse::<Rational>().is_err());
+        assert_eq!(q("0e999"), Rational::ZERO);
+        assert_eq!(q("-0.00e-99999999"), Rational::ZERO);
+        assert_eq!("1e999".parse::<Rational>(), Err(CalcError::Overflow));
+        assert_eq!(
+            "1e-2147483648".parse::<Rational>(),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(
+            "123456789012345678901234".parse::<Rational>(),
+            Err(CalcError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(q("7").checked_sub(q("2.3")).unwrap(), q("4.7"));
+        assert_eq!(q("1/3").checked_add(q("1/6")).unwrap(), q("1/2"));
+        assert_eq!(q("2/3").checked_mul(q("9/4")).unwrap(), q("3/2"));
+        assert_eq!(q("1/2").checked_div(q("1/4")).unwrap(), q("2"));
+        assert_eq!(q("1").checked_div(q("0")), Err(CalcError::DivisionByZero));
+        assert_eq!(q("7/2").checked_rem(q("1")).unwrap(), q("1/2"));
+        assert_eq!(q("-7/2").checked_rem(q("1")).unwrap(), q("-1/2"));
+        assert_eq!(
+            Rational::from_int(i64::MAX).checked_add(Rational::ONE),
+            Err(CalcError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_pow_sqrt_and_order() {
+        assert_eq!(q("2/3").pow(3).unwrap(), q("8/27"));
+        assert_eq!(q("-2/3").pow(-2).unwrap(), q("9/4"));
+        assert_eq!(q("0").pow(-1), Err(CalcError::DivisionByZero));
+        assert_eq!(q("10").pow(19), Err(CalcError::Overflow));
+        assert_eq!(q("9/16").sqrt().unwrap(), q("3/4"));
+        assert!(matches!(q("2").sqrt(), Err(CalcError::Domain(_))));
+        assert!(q("1/3") < q("0.34"));
+        assert!(q("-1/2") < q("-1/3"));
+        assert_eq!(q("4/2").to_string(), "2");
+        assert_eq!(q("-0.75").to_string(), "-3/4");
+        assert_eq!(q("1/8").to_f64(), 0.125);
+    }
+
+    #[test]
+    fn test_numeric_api() {
+        use crate::numeric;
+        assert_eq!(numeric::add(q("1/3"), q("2/3")), Ok(Rational::ONE));
+        assert_eq!(numeric::pow(q("1/2"), q("3")), Ok(q("1/8")));
+        assert!(matches!(
+            numeric::pow(q("2"), q("1/2")),
+            Err(CalcError::Domain(_))
+        ));
+        assert_eq!(numeric::fact(q("5")), Ok(q("120")));
+        assert_eq!(numeric::fact(q("5/2")), Err(CalcError::NonIntegerFactorial));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(Rational::from_f64(2.3), Ok(q("23/10")));
+        assert_eq!(Rational::from_f64(-0.1), Ok(q("-1/10")));
+        assert!(Rational::from_f64(f64::NAN).is_err());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_big_rational() {
+        let a: BigRational = "123456789012345678901234.5".parse().unwrap();
+        let b: BigRational = "1/2".parse().unwrap();
+        assert_eq!((&a + &b).to_string(), "123456789012345678901235");
+        assert_eq!((&a * &b).to_string(), "246913578024691357802469/4");
+        assert_eq!(
+            BigRational::from(q("10")).pow(30).unwrap().to_string(),
+            "1000000000000000000000000000000"
+        );
+        assert_eq!(
+            b.checked_div(&"0".parse().unwrap()),
+            Err(CalcError::DivisionByZero)
+        );
+        assert!(-b.clone() < b);
+        let seven: BigRational = "7".parse().unwrap();
+        let b2: BigRational = "2.3".parse().unwrap();
+        assert_eq!((&seven - &b2).to_string(), "47/10");
+
+        let zero: BigRational = "0e999999999".parse().unwrap();
+        assert_eq!(zero.to_string(), "0");
+        let tiny: BigRational = "1e-1000".parse().unwrap();
+        assert_eq!(tiny.denom().to_string().len(), 1001);
+        assert_eq!(
+            "0.5e-100001".parse::<BigRational>(),
+            Err(CalcError::Overflow)
+        );
+        assert_eq!(
+            "1e999999999".parse::<BigRational>(),
+            Err(CalcError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Scientific functions (feature `fancy`): trigonometry, hyperbolics,
+//! logarithms, exponentials, rounding and common constants.
+//!
+//! The trigonometric functions here work in radians; [`AngleMode`] has the
+//! same functions for degrees and gradians. Functions that can leave their
+//! domain return `Result` in the same style as [`sqrt`](crate::sqrt).
+
+pub use crate::angle::AngleMode;
+use crate::{CalcError, math};
+
+/// Mathematical constants.
+pub mod consts {
+    pub use core::f64::consts::{E, LN_2, LN_10, PI, SQRT_2, TAU};
+
+    /// The golden ratio, `(1 + √5) / 2`.
+    pub const PHI: f64 = 1.618_033_988_749_895;
+}
+
+/// Sine of `angle` in radians.
+pub fn sin(angle: f64) -> f64 {
+    AngleMode::Radians.sin(angle)
+}
+
+/// Cosine of `angle` in radians.
+pub fn cos(angle: f64) -> f64 {
+    AngleMode::Radians.cos(angle)
+}
+
+/// Tangent of `angle` in radians, or an error at its poles.
+pub fn tan(angle: f64) -> Result<f64, CalcError> {
+    AngleMode::Radians.tan(angle)
+}
+
+/// Arcsine in radians.
+pub fn asin(x: f64) -> Result<f64, CalcError> {
+    AngleMode::Radians.asin(x)
+}
+
+/// Arccosine in radians.
+pub fn acos(x: f64) -> Result<f64, CalcError> {
+    AngleMode::Radians.acos(x)
+}
+
+/// Arctangent in radians.
+pub fn atan(x: f64) -> f64 {
+    AngleMode::Radians.atan(x)
+}
+
+/// Four-quadrant arctangent of `y / x` in radians.
+pub fn atan2(y: f64, x: f64) -> f64 {
+    AngleMode::Radians.atan2(y, x)
+}
+
+/// Turns an infinite result from finite input into [`CalcError::Overflow`].
+fn finite(input: f64, result: f64) -> Result<f64, CalcError> {
+    if result.is_infinite() && input.is_finite() {
+        Err(CalcError::Overflow)
+    } else {
+        Ok(result)
+    }
+}
+
+/// Hyperbolic sine.
+pub fn sinh(x: f64) -> Result<f64, CalcError> {
+    finite(x, math::sinh(x))
+}
+
+/// Hyperbolic cosine.
+pub fn cosh(x: f64) -> Result<f64, CalcError> {
+    finite(x, math::cosh(x))
+}
+
+/// Hyperbolic tangent.
+pub fn tanh(x: f64) -> f64 {
+    math::tanh(x)
+}
+
+/// Inverse hyperbolic sine.
+pub fn asinh(x: f64) -> f64 {
+    math::asinh(x)
+}
+
+/// Inverse hyperbolic cosine; errors below 1.
+pub fn acosh(x: f64) -> Result<f64, CalcError> {
+    if x < 1.0 {
+        return Err(CalcError::Domain(
+            "Inverse hyperbolic cosine is only defined for values >= 1",
+        ));
+    }
+    Ok(math::acosh(x))
+}
+
+/// Inverse hyperbolic tangent; errors outside `(-1, 1)`.
+pub fn atanh(x: f64) -> Result<f64, CalcError> {
+    if x <= -1.0 || x >= 1.0 {
+        return Err(CalcError::Domain(
+            "Inverse hyperbolic tangent is only defined on (-1, 1)",
+        ));
+    }
+    Ok(math::atanh(x))
+}
+
+/// Natural logarithm; errors for non-positive input.
+pub fn ln(x: f64) -> Result<f64, CalcError> {
+    if x <= 0.0 {
+        return Err(CalcError::Domain(
+            "Logarithm is only defined for positive numbers",
+        ));
+    }
+    Ok(math::ln(x))
+}
+
+/// Base-10 logarithm; errors for non-positive input.
+pub fn log10(x: f64) -> Result<f64, CalcError> {
+    if x <= 0.0 {
+        return Err(CalcError::Domain(
+            "Logarithm is only defined for positive numbers",
+        ));
+    }
+    Ok(math::log10(x))
+}
+
+/// Logarithm of `x` in base `base`; errors for non-positive `x` or a base
+/// that is non-positive or one.
+pub fn log_b(x: f64, base: f64) -> Result<f64, CalcError> {
+    if base <= 0.0 || base == 1.0 {
+        return Err(CalcError::Domain(
+            "Logarithm base must be positive and not equal to 1",
+        ));
+    }
+    Ok(ln(x)? / math::ln(base))
+}
+
+/// `e` raised to `x`; errors if the result overflows.
+pub fn exp(x: f64) -> Result<f64, CalcError> {
+    finite(x, math::exp(x))
+}
+
+/// How [`round`] resolves values between two representable results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Round to nearest; ties go away from zero (`2.5 -> 3`, `-2.5 -> -3`).
+    #[default]
+    HalfAwayFromZero,
+    /// Round to nearest; ties go to the even neighbour (`2.5 -> 2`, `3.5 -> 4`).
+    HalfEven,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceil,
+    /// Drop the fractional part.
+    TowardZero,
+}
+
+/// Rounds `x` to `decimals` places after the point (negative values round to
+/// tens, hundreds, ...) using the given [`Rounding`] mode.
+///
+/// Values that are a tie in decimal, such as `1.005` to two places, are
+/// treated as ties even though their binary value is slightly off, so this
+/// agrees with [`finance::round_money`](crate::finance::round_money). Values
+/// that already have no digits past that place, including infinities and NaN,
+/// come back unchanged; values too small to reach it round to zero (keeping
+/// the sign of `x`) whatever the mode.
+pub fn round(x: f64, decimals: i32, mode: Rounding) -> f64 {
+    let round = match mode {
+        Rounding::HalfAwayFromZero => math::round,
+        Rounding::HalfEven => math::round_half_even,
+        Rounding::Floor => math::floor,
+        Rounding::Ceil => math::ceil,
+        Rounding::TowardZero => math::trunc,
+    };
+    math::round_decimal(x, decimals, round)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_trig_in_radians() {
+        assert!(close(sin(consts::PI / 6.0), 0.5));
+        assert!(close(cos(consts::PI), -1.0));
+        assert!(close(tan(consts::PI / 4.0).unwrap(), 1.0));
+        assert!(close(acos(0.5).unwrap(), consts::PI / 3.0));
+        assert!(close(atan2(1.0, -1.0), 0.75 * consts::PI));
+        assert!(matches!(asin(1.5), Err(CalcError::Domain(_))));
+    }
+
+    #[test]
+    fn test_hyperbolic() {
+        assert_eq!(sinh(0.0), Ok(0.0));
+        assert_eq!(cosh(0.0), Ok(1.0));
+        assert_eq!(sinh(1000.0), Err(CalcError::Overflow));
+        assert!(close(asinh(sinh(0.3).unwrap()), 0.3));
+        assert!(close(tanh(0.3), sinh(0.3).unwrap() / cosh(0.3).unwrap()));
+        assert!(close(acosh(1.0).unwrap(), 0.0));
+        assert!(matches!(acosh(0.5), Err(CalcError::Domain(_))));
+        assert!(close(atanh(0.5).unwrap(), 0.549_306_144_334_054_8));
+        assert!(matches!(atanh(1.0), Err(CalcError::Domain(_))));
+    }
+
+    #[test]
+    fn test_logs_and_exp() {
+        assert!(close(ln(consts::E).unwrap(), 1.0));
+        assert_eq!(log10(1000.0), Ok(3.0));
+        assert!(close(log_b(8.0, 2.0).unwrap(), 3.0));
+        assert!(matches!(ln(0.0), Err(CalcError::Domain(_))));
+        assert!(matches!(log_b(8.0, 1.0), Err(CalcError::Domain(_))));
+        assert_eq!(exp(0.0), Ok(1.0));
+        assert_eq!(exp(1000.0), Err(CalcError::Overflow));
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        assert_eq!(round(2.5, 0, Rounding::HalfAwayFromZero), 3.0);
+        assert_eq!(round(-2.5, 0, Rounding::HalfAwayFromZero), -3.0);
+        assert_eq!(round(2.5, 0, Rounding::HalfEven), 2.0);
+        assert_eq!(round(3.5, 0, Rounding::HalfEven), 4.0);
+        assert_eq!(round(1.234, 2, Rounding::Ceil), 1.24);
+        assert_eq!(round(-1.234, 1, Rounding::Floor), -1.3);
+        assert_eq!(round(-1.29, 1, Rounding::TowardZero), -1.2);
+        assert_eq!(round(1234.0, -2, Rounding::HalfAwayFromZero), 1200.0);
+    }
+
+    #[test]
+    fn test_rounding_extremes() {
+        assert_eq!(round(1e300, 10, Rounding::HalfAwayFromZero), 1e300);
+        assert_eq!(round(1.5, 400, Rounding::HalfEven), 1.5);
+        assert_eq!(round(1234.0, -400, Rounding::Ceil), 0.0);
+        assert!(round(-1234.0, -400, Rounding::Floor).is_sign_negative());
+        assert_eq!(round(f64::INFINITY, 2, Rounding::Floor), f64::INFINITY);
+        assert!(round(f64::NAN, 2, Rounding::Floor).is_nan());
+    }
+
+    #[test]
+    fn test_rounding_decimal_ties() {
+        assert_eq!(round(1.005, 2, Rounding::HalfAwayFromZero), 1.01);
+        assert_eq!(
+            round(1.005, 2, Rounding::HalfAwayFromZero),
+            crate::finance::round_money(1.005, 2)
+        );
+        assert_eq!(round(0.125, 2, Rounding::HalfEven), 0.12);
+        assert_eq!(round(1.13, 2, Rounding::Floor), 1.13);
+    }
+}
@@ -0,0 +1,111 @@
+//! Serde helpers for `f64` fields that may hold infinities or NaN.
+//!
+//! Formats such as JSON have no number for them (`serde_json` writes `null`
+//! and cannot read it back), so non-finite values are written as the strings
+//! `"inf"`, `"-inf"` and `"NaN"`. Finite values stay plain numbers. Use with
+//! `#[serde(with = "crate::serde_float")]` or one of the submodules.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// An `f64` in its serialized form.
+#[derive(Clone, Copy)]
+struct Float(f64);
+
+impl Serialize for Float {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.is_finite() {
+            serializer.serialize_f64(self.0)
+        } else {
+            // `inf`, `-inf` or `NaN`, which `str::parse` reads back.
+            serializer.collect_str(&self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Float {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(FloatVisitor)
+    }
+}
+
+struct FloatVisitor;
+
+impl Visitor<'_> for FloatVisitor {
+    type Value = Float;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a number, \"inf\", \"-inf\" or \"NaN\"")
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Float, E> {
+        Ok(Float(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Float, E> {
+        Ok(Float(value as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Float, E> {
+        Ok(Float(value as f64))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Float, E> {
+        value
+            .parse()
+            .map(Float)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+pub(crate) fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    Float(*value).serialize(serializer)
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Float::deserialize(deserializer).map(|float| float.0)
+}
+
+/// For `BTreeMap<String, f64>` fields.
+pub(crate) mod map {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        map: &BTreeMap<String, f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(map.iter().map(|(name, value)| (name, Float(*value))))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, f64>, D::Error> {
+        let map = BTreeMap::<String, Float>::deserialize(deserializer)?;
+        Ok(map
+            .into_iter()
+            .map(|(name, value)| (name, value.0))
+            .collect())
+    }
+}
+
+/// For `Option<f64>` fields.
+pub(crate) mod option {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(Float).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<f64>, D::Error> {
+        Ok(Option::<Float>::deserialize(deserializer)?.map(|value| value.0))
+    }
+}
@@ -0,0 +1,591 @@
+//! Interactive calculator sessions with variables, constants, `ans` and
+//! user-defined functions.
+//!
+//! Each line given to [`Session::eval`] is an expression, a variable
+//! assignment such as `x = 3`, or a function definition such as
+//! `f(x) = x^2 + 1`. The built-in constants `pi`, `e` and `tau` are always
+//! available, and `ans` is the last value computed. Trig functions use the
+//! session's own [`AngleMode`]. With the `serde` feature a session can be
+//! saved and restored, e.g. as JSON.
+//!
+//! ```
+//! use xrust_calclib::session::{Outcome, Session};
+//!
+//! let mut s = Session::new();
+//! s.eval("r = 2").unwrap();
+//! s.eval("area(r) = pi * r^2").unwrap();
+//! assert_eq!(s.eval("area(r) / pi"), Ok(Outcome::Value(4.0)));
+//! assert_eq!(s.eval("ans + 1").unwrap().value(), Some(5.0));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::f64::consts;
+use core::fmt;
+
+use crate::AngleMode;
+use crate::expr::{self, EvalError, Expr, Function};
+
+/// How deeply user-defined functions may call each other by default.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// The highest depth limit [`Session::set_max_depth`] accepts.
+pub const MAX_DEPTH: usize = 256;
+
+/// How many subexpressions deep evaluation may go through calls to
+/// user-defined functions, whatever the depth limit, so that deeply nested
+/// bodies cannot overflow the stack either.
+const MAX_NESTING: usize = 512;
+
+/// The built-in constants, which cannot be reassigned.
+pub const CONSTANTS: [(&str, f64); 3] =
+    [("pi", consts::PI), ("e", consts::E), ("tau", consts::TAU)];
+
+/// A function defined in a session, such as `f(x) = x^2 + 1`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "FunctionDef", try_from = "FunctionDef")
+)]
+pub struct UserFunction {
+    params: Vec<String>,
+    body: Expr,
+}
+
+impl UserFunction {
+    /// The parameter names, in call order.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// The parsed body.
+    pub fn body(&self) -> &Expr {
+        &self.body
+    }
+}
+
+/// The serialized form of a [`UserFunction`]: its body is kept as source,
+/// which `Display` writes so that it parses back to the same values.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FunctionDef {
+    params: Vec<String>,
+    body: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<UserFunction> for FunctionDef {
+    fn from(function: UserFunction) -> Self {
+        FunctionDef {
+            params: function.params,
+            body: function.body.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FunctionDef> for UserFunction {
+    type Error = EvalError;
+
+    fn try_from(def: FunctionDef) -> Result<Self, EvalError> {
+        Ok(UserFunction {
+            params: def.params,
+            body: expr::parse(&def.body)?,
+        })
+    }
+}
+
+/// Reads a depth limit, refusing one above [`MAX_DEPTH`].
+#[cfg(feature = "serde")]
+fn max_depth<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let max_depth = <usize as serde::Deserialize>::deserialize(deserializer)?;
+    if max_depth > MAX_DEPTH {
+        return Err(serde::de::Error::custom(format!(
+            "max_depth {} is above the limit of {}",
+            max_depth, MAX_DEPTH
+        )));
+    }
+    Ok(max_depth)
+}
+
+/// What a line given to [`Session::eval`] did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// An expression was evaluated; the value is now `ans`.
+    Value(f64),
+    /// A variable was assigned; the value is now `ans`.
+    Assigned { name: String, value: f64 },
+    /// A function was defined or replaced.
+    Defined { name: String },
+}
+
+impl Outcome {
+    /// The computed value, if the line produced one.
+    pub fn value(&self) -> Option<f64> {
+        match self {
+            Outcome::Value(value) | Outcome::Assigned { value, .. } => Some(*value),
+            Outcome::Defined { .. } => None,
+        }
+    }
+}
+
+/// Formats as `3`, `x = 3` or `f defined`.
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Value(value) => write!(f, "{}", value),
+            Outcome::Assigned { name, value } => write!(f, "{} = {}", name, value),
+            Outcome::Defined { name } => write!(f, "{} defined", name),
+        }
+    }
+}
+
+/// Calculator state that persists between lines.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_float::map"))]
+    variables: BTreeMap<String, f64>,
+    functions: BTreeMap<String, UserFunction>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_float"))]
+    ans: f64,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "max_depth"))]
+    max_depth: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    angle_mode: AngleMode,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn constant(name: &str) -> Option<f64> {
+    CONSTANTS
+        .iter()
+        .find(|(constant, _)| *constant == name)
+        .map(|(_, value)| *value)
+}
+
+/// Moves an error from a right-hand side back to its column in the line.
+fn shifted(err: EvalError, by: usize) -> EvalError {
+    EvalError {
+        column: err.column + by,
+        ..err
+    }
+}
+
+impl Session {
+    /// An empty session with `ans = 0`.
+    pub fn new() -> Self {
+        Session {
+            variables: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            ans: 0.0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            angle_mode: AngleMode::Radians,
+        }
+    }
+
+    /// The last value computed.
+    pub fn ans(&self) -> f64 {
+        self.ans
+    }
+
+    /// The value of the variable `name`.
+    pub fn variable(&self, name: &str) -> Option<f64> {
+        self.variables.get(name).copied()
+    }
+
+    /// All variables, sorted by name.
+    pub fn variables(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.variables
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Sets a variable, as `name = value` would.
+    pub fn set_variable(&mut self, name: &str, value: f64) -> Result<(), EvalError> {
+        self.check_name(name, 1)?;
+        self.variables.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Removes a variable, returning its value.
+    pub fn remove_variable(&mut self, name: &str) -> Option<f64> {
+        self.variables.remove(name)
+    }
+
+    /// The user-defined function `name`.
+    pub fn function(&self, name: &str) -> Option<&UserFunction> {
+        self.functions.get(name)
+    }
+
+    /// All user-defined functions, sorted by name.
+    pub fn functions(&self) -> impl Iterator<Item = (&str, &UserFunction)> {
+        self.functions.iter().map(|(name, f)| (name.as_str(), f))
+    }
+
+    /// Removes a user-defined function.
+    pub fn remove_function(&mut self, name: &str) -> Option<UserFunction> {
+        self.functions.remove(name)
+    }
+
+    /// How deeply user-defined functions may nest calls.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Limits how deeply user-defined functions may nest calls, which also
+    /// stops runaway recursion such as `f(x) = f(x - 1)`. Limits above
+    /// [`MAX_DEPTH`] are lowered to it.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth.min(MAX_DEPTH);
+    }
+
+    /// The angle unit trig functions use; radians unless changed.
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    /// Evaluates trig functions in `mode` from now on.
+    pub fn set_angle_mode(&mut self, mode: AngleMode) {
+        self.angle_mode = mode;
+    }
+
+    /// Forgets all variables and functions and resets `ans` to 0, keeping
+    /// the depth limit and angle mode.
+    pub fn clear(&mut self) {
+        *self = Session {
+            max_depth: self.max_depth,
+            angle_mode: self.angle_mode,
+            ..Session::new()
+        };
+    }
+
+    /// Runs one line: an expression, `name = expr` or `name(a, b) = expr`.
+    ///
+    /// Function bodies are checked for syntax when defined and may refer to
+    /// variables and functions that are only defined later.
+    pub fn eval(&mut self, line: &str) -> Result<Outcome, EvalError> {
+        let Some((lhs, rhs)) = line.split_once('=') else {
+            let value = self.eval_expr(&expr::parse(line)?, &[], 0, 0)?;
+            self.ans = value;
+            return Ok(Outcome::Value(value));
+        };
+        let offset = lhs.chars().count() + 1;
+        let target = expr::parse(lhs)?;
+        let body = expr::parse(rhs).map_err(|err| shifted(err, offset))?;
+        match target {
+            Expr::Var { name, column } => {
+                self.check_name(&name, column)?;
+                let value = self
+                    .eval_expr(&body, &[], 0, 0)
+                    .map_err(|err| shifted(err, offset))?;
+                self.variables.insert(name.clone(), value);
+                self.ans = value;
+                Ok(Outcome::Assigned { name, value })
+            }
+            Expr::Call { name, args, column } => {
+                if Function::lookup(&name).is_some() {
+                    return Err(EvalError::new(
+                        column,
+                        format!("Cannot redefine built-in function '{}'", name),
+                    ));
+                }
+                let mut params: Vec<String> = Vec::with_capacity(args.len());
+                for arg in args {
+                    match arg {
+                        Expr::Var { name, .. } if !params.contains(&name) => params.push(name),
+                        _ => {
+                            return Err(EvalError::new(
+                                column,
+                                "Function parameters must be distinct names",
+                            ));
+                        }
+                    }
+                }
+                self.functions
+                    .insert(name.clone(), UserFunction { params, body });
+                Ok(Outcome::Defined { name })
+            }
+            _ => Err(EvalError::new(
+                1,
+                "Only a name or a function signature can be assigned to",
+            )),
+        }
+    }
+
+    fn check_name(&self, name: &str, column: usize) -> Result<(), EvalError> {
+        if !is_identifier(name) {
+            Err(EvalError::new(column, format!("Invalid name '{}'", name)))
+        } else if name == "ans" || constant(name).is_some() {
+            Err(EvalError::new(
+                column,
+                format!("Cannot assign to constant '{}'", name),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Evaluates `e` with function parameters bound by `locals`, `depth`
+    /// user-defined calls and `nesting` subexpressions deep.
+    fn eval_expr(
+        &self,
+        e: &Expr,
+        locals: &[(&str, f64)],
+        depth: usize,
+        nesting: usize,
+    ) -> Result<f64, EvalError> {
+        let nesting = nesting + 1;
+        match e {
+            Expr::Num(n) | Expr::Decimal { value: n, .. } => Ok(*n),
+            Expr::Neg(inner) => Ok(-self.eval_expr(inner, locals, depth, nesting)?),
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => {
+                let a = self.eval_expr(lhs, locals, depth, nesting)?;
+                let b = self.eval_expr(rhs, locals, depth, nesting)?;
+                op.apply(a, b).map_err(|err| EvalError::calc(*column, err))
+            }
+            Expr::Var { name, column } => locals
+                .iter()
+                .find(|(local, _)| local == name)
+                .map(|(_, value)| *value)
+                .or_else(|| self.variable(name))
+                .or_else(|| (name == "ans").then_some(self.ans))
+                .or_else(|| constant(name))
+                .ok_or_else(|| expr::unknown_identifier(name, *column)),
+            Expr::Call { name, args, column } => {
+                let values = args
+                    .iter()
+                    .map(|arg| self.eval_expr(arg, locals, depth, nesting))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let Some(function) = self.functions.get(name) else {
+                    return Function::resolve(name, values.len(), *column)?
+                        .apply(&values, self.angle_mode)
+                        .map_err(|err| EvalError::calc(*column, err));
+                };
+                if values.len() != function.params.len() {
+                    return Err(EvalError::new(
+                        *column,
+                        format!(
+                            "Function '{}' expects {} argument(s), got {}",
+                            name,
+                            function.params.len(),
+                            values.len()
+                        ),
+                    ));
+                }
+                if depth >= self.max_depth {
+                    return Err(EvalError::new(
+                        *column,
+                        format!("Recursion depth limit of {} exceeded", self.max_depth),
+                    ));
+                }
+                if nesting >= MAX_NESTING {
+                    return Err(EvalError::new(*column, "Expression is nested too deeply"));
+                }
+                let locals: Vec<(&str, f64)> = function
+                    .params
+                    .iter()
+                    .map(String::as_str)
+                    .zip(values)
+                    .collect();
+                // The body's own columns are meaningless at the call site.
+                self.eval_expr(&function.body, &locals, depth + 1, nesting)
+                    .map_err(|err| EvalError {
+                        column: *column,
+                        ..err
+                    })
+            }
+            Expr::WithUnit { column, .. } | Expr::Convert { column, .. } => {
+                Err(expr::units_unsupported(*column))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CalcError, EvalErrorKind};
+
+    fn value(s: &mut Session, line: &str) -> f64 {
+        s.eval(line).unwrap().value().unwrap()
+    }
+
+    #[test]
+    fn test_variables_and_ans() {
+        let mut s = Session::new();
+        assert_eq!(value(&mut s, "ans"), 0.0);
+        assert_eq!(
+            s.eval("x = 3"),
+            Ok(Outcome::Assigned {
+                name: "x".into(),
+                value: 3.0
+            })
+        );
+        assert_eq!(value(&mut s, "x * 2"), 6.0);
+        assert_eq!(value(&mut s, "ans + x"), 9.0);
+        assert_eq!(value(&mut s, "x = x + 1"), 4.0);
+        assert_eq!(s.variable("x"), Some(4.0));
+        assert_eq!(value(&mut s, "2 * pi / tau + e - e"), 1.0);
+        s.set_variable("y", 0.5).unwrap();
+        assert_eq!(s.variables().collect::<Vec<_>>(), [("x", 4.0), ("y", 0.5)]);
+    }
+
+    #[test]
+    fn test_user_functions() {
+        let mut s = Session::new();
+        assert_eq!(
+            s.eval("f(x) = x^2 + 1"),
+            Ok(Outcome::Defined { name: "f".into() })
+        );
+        assert_eq!(value(&mut s, "f(3)"), 10.0);
+        s.eval("k = 10").unwrap();
+        s.eval("g(a, b) = f(a) * b + k").unwrap();
+        assert_eq!(value(&mut s, "g(2, 3)"), 25.0);
+        // Parameters shadow variables, and late binding sees new values.
+        s.eval("h(k) = k + later").unwrap();
+        s.eval("later = 0.5").unwrap();
+        assert_eq!(value(&mut s, "h(1)"), 1.5);
+        assert_eq!(s.function("g").unwrap().params(), ["a", "b"]);
+        assert_eq!(s.function("f").unwrap().body().to_string(), "x^2 + 1");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut s = Session::new();
+        assert_eq!(s.eval("pi = 3").unwrap_err().column, 1);
+        assert!(s.eval("ans = 3").is_err());
+        assert!(s.eval("sqrt(x) = 3").is_err());
+        assert!(s.eval("f(x, x) = x").is_err());
+        assert!(s.eval("2 + x = 3").is_err());
+
+        let err = s.eval("y = 1 / 0").unwrap_err();
+        assert_eq!(
+            (err.column, err.kind),
+            (7, EvalErrorKind::Calc(CalcError::DivisionByZero))
+        );
+        assert_eq!(s.variable("y"), None);
+        assert_eq!(s.eval("y = (1").unwrap_err().column, 7);
+
+        s.eval("f(x) = x + 1").unwrap();
+        assert_eq!(s.eval("2 * f(1, 2)").unwrap_err().column, 5);
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let mut s = Session::new();
+        s.eval("loop(n) = loop(n + 1)").unwrap();
+        let err = s.eval("1 + loop(0)").unwrap_err();
+        assert_eq!(err.column, 5);
+        assert_eq!(
+            err.kind,
+            EvalErrorKind::Syntax("Recursion depth limit of 64 exceeded".into())
+        );
+
+        s.eval("a(x) = b(x) + 1").unwrap();
+        s.eval("b(x) = c(x) + 1").unwrap();
+        s.eval("c(x) = x").unwrap();
+        s.set_max_depth(2);
+        assert!(s.eval("a(0)").is_err());
+        s.set_max_depth(3);
+        assert_eq!(value(&mut s, "a(0)"), 2.0);
+        s.clear();
+        assert_eq!((s.max_depth(), s.function("a")), (3, None));
+
+        // The limit is capped, and deep bodies count too, so neither can
+        // overflow the stack.
+        s.set_max_depth(usize::MAX);
+        assert_eq!(s.max_depth(), MAX_DEPTH);
+        s.eval("g(n) = g(n + 1)").unwrap();
+        let err = s.eval("g(0)").unwrap_err();
+        assert_eq!(
+            err.kind,
+            EvalErrorKind::Syntax("Recursion depth limit of 256 exceeded".into())
+        );
+        let body = format!("{}h(n + 1){}", "0 + (".repeat(100), ")".repeat(100));
+        s.eval(&format!("h(n) = {}", body)).unwrap();
+        let err = s.eval("h(0)").unwrap_err();
+        assert_eq!(
+            err.kind,
+            EvalErrorKind::Syntax("Expression is nested too deeply".into())
+        );
+    }
+
+    #[cfg(feature = "fancy")]
+    #[test]
+    fn test_angle_mode_per_session() {
+        let mut deg = Session::new();
+        deg.set_angle_mode(AngleMode::Degrees);
+        let mut rad = Session::new();
+        assert_eq!(value(&mut deg, "sin(90)"), 1.0);
+        deg.eval("f(x) = atan2(x, x)").unwrap();
+        assert_eq!(value(&mut deg, "f(1)"), 45.0);
+        assert!((value(&mut rad, "sin(pi / 2)") - 1.0).abs() < 1e-15);
+        deg.clear();
+        assert_eq!(deg.angle_mode(), AngleMode::Degrees);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut s = Session::new();
+        s.eval("x = 2").unwrap();
+        s.eval("f(a, b) = -a^2 + b * x").unwrap();
+        s.eval("f(1, 3)").unwrap();
+        s.set_angle_mode(AngleMode::Degrees);
+
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(json.contains(r#""f":{"params":["a","b"],"body":"-a^2 + b * x"}"#));
+        assert!(json.contains(r#""angle_mode":"degrees""#));
+        let mut restored: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.angle_mode(), AngleMode::Degrees);
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        assert_eq!(value(&mut restored, "ans + f(2, 1)"), 5.0 - 2.0);
+
+        let bad = json.replace("-a^2 + b * x", "a +");
+        assert!(serde_json::from_str::<Session>(&bad).is_err());
+        let deep = json.replace(r#""max_depth":64"#, r#""max_depth":100000"#);
+        assert!(serde_json::from_str::<Session>(&deep).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_non_finite() {
+        let mut s = Session::new();
+        s.eval("big = 1e308 * 10").unwrap();
+        s.eval("small = -big").unwrap();
+        s.eval("f(x) = x * 1e400").unwrap();
+        s.eval("big * 0").unwrap();
+        assert!(s.ans().is_nan());
+
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(json.contains(r#""variables":{"big":"inf","small":"-inf"}"#));
+        assert!(json.contains(r#""ans":"NaN""#));
+        let restored: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.variable("big"), Some(f64::INFINITY));
+        assert_eq!(restored.variable("small"), Some(f64::NEG_INFINITY));
+        assert!(restored.ans().is_nan());
+        assert!(json.contains(r#""body":"x * 1e400""#));
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        let mut restored = restored;
+        assert_eq!(restored.eval("f(2)"), Ok(Outcome::Value(f64::INFINITY)));
+    }
+}
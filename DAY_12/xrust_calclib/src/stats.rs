@@ -0,0 +1,307 @@
+//! Summary statistics over slices of `f64`.
+//!
+//! Means, variances and co-moments are computed in a single pass with
+//! Welford's algorithm, which stays accurate for long sensor logs where the
+//! naive sum-of-squares formula cancels catastrophically. Every function
+//! returns [`CalcError::EmptyInput`] for an empty slice.
+//!
+//! `median`, `percentile` and `mode` sort a copy of the data and need the
+//! `alloc` feature.
+
+use crate::{CalcError, math};
+
+/// Single-pass running moments of one variable.
+struct Moments {
+    n: usize,
+    mean: f64,
+    m2: f64,
+}
+
+fn moments(data: &[f64]) -> Result<Moments, CalcError> {
+    if data.is_empty() {
+        return Err(CalcError::EmptyInput);
+    }
+    let mut m = Moments {
+        n: 0,
+        mean: 0.0,
+        m2: 0.0,
+    };
+    for &x in data {
+        m.n += 1;
+        let delta = x - m.mean;
+        m.mean += delta / m.n as f64;
+        m.m2 += delta * (x - m.mean);
+    }
+    Ok(m)
+}
+
+/// Single-pass running moments and co-moment of paired variables.
+struct CoMoments {
+    n: usize,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64,
+    m2_y: f64,
+    c: f64,
+}
+
+fn co_moments(xs: &[f64], ys: &[f64]) -> Result<CoMoments, CalcError> {
+    if xs.len() != ys.len() {
+        return Err(CalcError::LengthMismatch);
+    }
+    if xs.is_empty() {
+        return Err(CalcError::EmptyInput);
+    }
+    let mut m = CoMoments {
+        n: 0,
+        mean_x: 0.0,
+        mean_y: 0.0,
+        m2_x: 0.0,
+        m2_y: 0.0,
+        c: 0.0,
+    };
+    for (&x, &y) in xs.iter().zip(ys) {
+        m.n += 1;
+        let n = m.n as f64;
+        let dx = x - m.mean_x;
+        let dy = y - m.mean_y;
+        m.mean_x += dx / n;
+        m.mean_y += dy / n;
+        m.m2_x += dx * (x - m.mean_x);
+        m.m2_y += dy * (y - m.mean_y);
+        m.c += dx * (y - m.mean_y);
+    }
+    Ok(m)
+}
+
+fn sample_divisor(n: usize) -> Result<f64, CalcError> {
+    if n < 2 {
+        Err(CalcError::Domain(
+            "Sample statistics need at least two values",
+        ))
+    } else {
+        Ok((n - 1) as f64)
+    }
+}
+
+/// The arithmetic mean.
+pub fn mean(data: &[f64]) -> Result<f64, CalcError> {
+    Ok(moments(data)?.mean)
+}
+
+/// Population variance (divides by `n`).
+pub fn variance_pop(data: &[f64]) -> Result<f64, CalcError> {
+    let m = moments(data)?;
+    Ok(m.m2 / m.n as f64)
+}
+
+/// Sample variance (divides by `n - 1`); needs at least two values.
+pub fn variance_sample(data: &[f64]) -> Result<f64, CalcError> {
+    let m = moments(data)?;
+    Ok(m.m2 / sample_divisor(m.n)?)
+}
+
+/// Population standard deviation.
+pub fn stddev_pop(data: &[f64]) -> Result<f64, CalcError> {
+    variance_pop(data).map(math::sqrt)
+}
+
+/// Sample standard deviation; needs at least two values.
+pub fn stddev_sample(data: &[f64]) -> Result<f64, CalcError> {
+    variance_sample(data).map(math::sqrt)
+}
+
+/// The smallest value. NaNs are ignored unless every value is NaN.
+pub fn min(data: &[f64]) -> Result<f64, CalcError> {
+    data.iter()
+        .copied()
+        .reduce(f64::min)
+        .ok_or(CalcError::EmptyInput)
+}
+
+/// The largest value. NaNs are ignored unless every value is NaN.
+pub fn max(data: &[f64]) -> Result<f64, CalcError> {
+    data.iter()
+        .copied()
+        .reduce(f64::max)
+        .ok_or(CalcError::EmptyInput)
+}
+
+/// Population covariance of paired samples.
+pub fn covariance_pop(xs: &[f64], ys: &[f64]) -> Result<f64, CalcError> {
+    let m = co_moments(xs, ys)?;
+    Ok(m.c / m.n as f64)
+}
+
+/// Sample covariance of paired samples; needs at least two pairs.
+pub fn covariance_sample(xs: &[f64], ys: &[f64]) -> Result<f64, CalcError> {
+    let m = co_moments(xs, ys)?;
+    Ok(m.c / sample_divisor(m.n)?)
+}
+
+/// Pearson correlation coefficient; errors if either input is constant.
+pub fn correlation(xs: &[f64], ys: &[f64]) -> Result<f64, CalcError> {
+    let m = co_moments(xs, ys)?;
+    if m.m2_x == 0.0 || m.m2_y == 0.0 {
+        return Err(CalcError::Domain(
+            "Correlation is undefined for constant data",
+        ));
+    }
+    Ok(m.c / math::sqrt(m.m2_x * m.m2_y))
+}
+
+/// A least-squares line `y = slope * x + intercept`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Regression {
+    pub slope: f64,
+    pub intercept: f64,
+    /// Coefficient of determination, `1.0` for a perfect fit.
+    pub r_squared: f64,
+}
+
+impl Regression {
+    /// Evaluates the fitted line at `x`.
+    pub fn predict(&self, x: f64) -> f64 {
+        self.slope * x + self.intercept
+    }
+}
+
+/// Ordinary least-squares fit of `ys` against `xs`; errors if `xs` is constant.
+pub fn linear_regression(xs: &[f64], ys: &[f64]) -> Result<Regression, CalcError> {
+    let m = co_moments(xs, ys)?;
+    if m.m2_x == 0.0 {
+        return Err(CalcError::Domain(
+            "Regression needs at least two distinct x values",
+        ));
+    }
+    let slope = m.c / m.m2_x;
+    let r_squared = if m.m2_y == 0.0 {
+        1.0
+    } else {
+        (m.c * m.c) / (m.m2_x * m.m2_y)
+    };
+    Ok(Regression {
+        slope,
+        intercept: m.mean_y - slope * m.mean_x,
+        r_squared,
+    })
+}
+
+#[cfg(feature = "alloc")]
+fn sorted(data: &[f64]) -> Result<alloc::vec::Vec<f64>, CalcError> {
+    if data.is_empty() {
+        return Err(CalcError::EmptyInput);
+    }
+    let mut v = data.to_vec();
+    v.sort_by(f64::total_cmp);
+    Ok(v)
+}
+
+/// The middle value, or the mean of the two middle values for even lengths.
+#[cfg(feature = "alloc")]
+pub fn median(data: &[f64]) -> Result<f64, CalcError> {
+    percentile(data, 50.0)
+}
+
+/// The `p`-th percentile (`0..=100`), linearly interpolating between ranks.
+#[cfg(feature = "alloc")]
+pub fn percentile(data: &[f64], p: f64) -> Result<f64, CalcError> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(CalcError::Domain("Percentile must be between 0 and 100"));
+    }
+    let v = sorted(data)?;
+    let rank = p / 100.0 * (v.len() - 1) as f64;
+    let lo = math::floor(rank) as usize;
+    let hi = math::ceil(rank) as usize;
+    Ok(v[lo] + (v[hi] - v[lo]) * (rank - lo as f64))
+}
+
+/// The most frequent value; ties go to the smallest value.
+#[cfg(feature = "alloc")]
+pub fn mode(data: &[f64]) -> Result<f64, CalcError> {
+    let v = sorted(data)?;
+    let (mut best, mut best_count) = (v[0], 0);
+    let mut i = 0;
+    while i < v.len() {
+        let run = v[i..].iter().take_while(|&&x| x == v[i]).count().max(1);
+        if run > best_count {
+            best = v[i];
+            best_count = run;
+        }
+        i += run;
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_mean_and_variance() {
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(mean(&data), Ok(5.0));
+        assert_eq!(variance_pop(&data), Ok(4.0));
+        assert_eq!(stddev_pop(&data), Ok(2.0));
+        assert!(close(variance_sample(&data).unwrap(), 32.0 / 7.0));
+        assert_eq!(min(&data), Ok(2.0));
+        assert_eq!(max(&data), Ok(9.0));
+
+        assert_eq!(mean(&[]), Err(CalcError::EmptyInput));
+        assert_eq!(max(&[]), Err(CalcError::EmptyInput));
+        assert!(matches!(variance_sample(&[1.0]), Err(CalcError::Domain(_))));
+    }
+
+    #[test]
+    fn test_welford_is_stable() {
+        // A large offset destroys the naive E[x²] - E[x]² formula.
+        let data = [1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0];
+        assert_eq!(variance_sample(&data), Ok(30.0));
+    }
+
+    #[test]
+    fn test_paired_statistics() {
+        let xs = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys = [2.0, 4.1, 5.9, 8.0, 10.0];
+        assert!(close(correlation(&xs, &xs).unwrap(), 1.0));
+        assert!(close(covariance_sample(&xs, &xs).unwrap(), 2.5));
+        assert!(close(covariance_pop(&xs, &xs).unwrap(), 2.0));
+
+        let fit = linear_regression(&xs, &ys).unwrap();
+        assert!(close(fit.slope, 1.99));
+        assert!(close(fit.intercept, 0.03));
+        assert!(fit.r_squared > 0.999);
+        assert!(close(fit.predict(6.0), 11.97));
+
+        assert_eq!(correlation(&xs, &ys[..4]), Err(CalcError::LengthMismatch));
+        assert_eq!(covariance_pop(&[], &[]), Err(CalcError::EmptyInput));
+        assert!(matches!(
+            linear_regression(&[1.0, 1.0], &[2.0, 3.0]),
+            Err(CalcError::Domain(_))
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_order_statistics() {
+        // First five play counts for user 1 in data/user_plays.csv.
+        let plays = [102.0, 435.0, 108.0, 146.0, 172.0];
+        assert_eq!(median(&plays), Ok(146.0));
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Ok(2.5));
+        assert_eq!(percentile(&plays, 0.0), Ok(102.0));
+        assert_eq!(percentile(&plays, 100.0), Ok(435.0));
+        assert_eq!(percentile(&plays, 75.0), Ok(172.0));
+        assert_eq!(percentile(&plays, 10.0), Ok(104.4));
+        assert_eq!(mode(&[5.0, 2.0, 5.0, 1.0]), Ok(5.0));
+        assert_eq!(mode(&[3.0, 1.0, 3.0, 1.0]), Ok(1.0));
+        assert_eq!(median(&[]), Err(CalcError::EmptyInput));
+        assert!(matches!(
+            percentile(&plays, 101.0),
+            Err(CalcError::Domain(_))
+        ));
+    }
+}
@@ -0,0 +1,780 @@
+//! Symbolic manipulation of parsed expressions: differentiation,
+//! simplification and printing back to infix or LaTeX.
+//!
+//! Everything here works on the [`Expr`] tree from [`parse`](crate::expr::parse),
+//! where any identifier that is not a function call is a variable. Nodes
+//! created by these methods carry column 0.
+//!
+//! ```
+//! use xrust_calclib::calculus::{self, Options};
+//! use xrust_calclib::expr::parse;
+//!
+//! let f = parse("x^3 - 2*x - 5").unwrap();
+//! let df = f.derivative("x").unwrap();
+//! assert_eq!(df.to_string(), "3 * x^2 - 2");
+//! assert_eq!(df.to_latex(), "3 \\cdot x^{2} - 2");
+//!
+//! // The derivative feeds straight into Newton's method.
+//! let (f, df) = (f.compile_with_inputs(&["x"]).unwrap(), df.compile_with_inputs(&["x"]).unwrap());
+//! let root = calculus::newton(
+//!     |x| f.eval(&[x]).unwrap(),
+//!     |x| df.eval(&[x]).unwrap(),
+//!     2.0,
+//!     Options::default(),
+//! )
+//! .unwrap();
+//! assert!((root.value - 2.094_551_481_5).abs() < 1e-9);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::expr::{self, BinOp, EvalError, EvalErrorKind, Expr, Function, binary};
+use crate::{AngleMode, CalcError};
+
+fn num(n: f64) -> Expr {
+    Expr::Num(n)
+}
+
+fn neg(e: Expr) -> Expr {
+    Expr::Neg(Box::new(e))
+}
+
+fn op(op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
+    binary(op, lhs, rhs, 0)
+}
+
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::Call {
+        name: name.to_string(),
+        args: vec![arg],
+        column: 0,
+    }
+}
+
+fn no_derivative(column: usize) -> EvalError {
+    EvalError::calc(
+        column,
+        CalcError::Domain("Expression has no symbolic derivative"),
+    )
+}
+
+impl Expr {
+    /// Whether the expression mentions the variable `var`.
+    pub fn depends_on(&self, var: &str) -> bool {
+        match self {
+            Expr::Num(_) | Expr::Decimal { .. } => false,
+            Expr::Var { name, .. } => name == var,
+            Expr::Neg(inner) => inner.depends_on(var),
+            Expr::Binary { lhs, rhs, .. } => lhs.depends_on(var) || rhs.depends_on(var),
+            Expr::Call { args, .. } => args.iter().any(|arg| arg.depends_on(var)),
+            Expr::WithUnit { value, .. } | Expr::Convert { value, .. } => value.depends_on(var),
+        }
+    }
+
+    /// The derivative with respect to `var`, simplified.
+    ///
+    /// Every operator except `%` is supported, as are all functions except
+    /// `mode`, `fact`, `gamma` and `lgamma`. Trigonometric derivatives are
+    /// in radians; see [`derivative_in`](Expr::derivative_in) for other angle
+    /// modes. Powers with a variable exponent need `ln` from the `fancy`
+    /// feature.
+    ///
+    /// ```
+    /// use xrust_calclib::expr::parse;
+    ///
+    /// let d = |src: &str| parse(src).unwrap().derivative("x").unwrap().to_string();
+    /// assert_eq!(d("x^2 + 3*x*y"), "2 * x + 3 * y");
+    /// assert_eq!(d("1 / x"), "-1 / x^2");
+    /// assert_eq!(d("sqrt(x)"), "1 / (2 * sqrt(x))");
+    /// ```
+    pub fn derivative(&self, var: &str) -> Result<Expr, EvalError> {
+        self.derivative_in(var, AngleMode::Radians)
+    }
+
+    /// The derivative with respect to `var` when trig functions take and
+    /// return angles in `mode`, simplified. In degrees, `sin(x)'` is
+    /// `cos(x) * pi/180`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "fancy")] {
+    /// use xrust_calclib::{AngleMode, expr::parse};
+    ///
+    /// let d = parse("sin(x)").unwrap().derivative_in("x", AngleMode::Degrees).unwrap();
+    /// assert_eq!(d.to_string(), "0.017453292519943295 * cos(x)");
+    /// # }
+    /// ```
+    pub fn derivative_in(&self, var: &str, mode: AngleMode) -> Result<Expr, EvalError> {
+        Ok(self.diff(var, mode)?.simplify())
+    }
+
+    fn diff(&self, x: &str, mode: AngleMode) -> Result<Expr, EvalError> {
+        Ok(match self {
+            Expr::Num(_) | Expr::Decimal { .. } => num(0.0),
+            Expr::Var { name, .. } => num(if name == x { 1.0 } else { 0.0 }),
+            Expr::Neg(u) => neg(u.diff(x, mode)?),
+            Expr::Binary {
+                op: bin,
+                lhs: u,
+                rhs: v,
+                column,
+            } => {
+                let (u, v) = (u.as_ref().clone(), v.as_ref().clone());
+                let (du, dv) = (u.diff(x, mode)?, v.diff(x, mode)?);
+                match bin {
+                    BinOp::Add => op(BinOp::Add, du, dv),
+                    BinOp::Sub => op(BinOp::Sub, du, dv),
+                    BinOp::Mul => op(BinOp::Add, op(BinOp::Mul, du, v), op(BinOp::Mul, u, dv)),
+                    // The quotient rule would leave `0 / v^2`, which only
+                    // simplifies for a constant `v`.
+                    BinOp::Div if !u.depends_on(x) && !v.depends_on(x) => num(0.0),
+                    BinOp::Div => op(
+                        BinOp::Div,
+                        op(
+                            BinOp::Sub,
+                            op(BinOp::Mul, du, v.clone()),
+                            op(BinOp::Mul, u, dv),
+                        ),
+                        op(BinOp::Pow, v, num(2.0)),
+                    ),
+                    BinOp::Pow if !v.depends_on(x) => {
+                        // d(u^n) = n * u^(n - 1) * u'
+                        let lowered = op(BinOp::Pow, u, op(BinOp::Sub, v.clone(), num(1.0)));
+                        op(BinOp::Mul, op(BinOp::Mul, v, lowered), du)
+                    }
+                    BinOp::Pow => {
+                        // d(u^v) = u^v * (v' * ln(u) + v * u' / u)
+                        if !cfg!(feature = "fancy") {
+                            return Err(EvalError::calc(
+                                *column,
+                                CalcError::Domain("Variable exponents need the `fancy` feature"),
+                            ));
+                        }
+                        let growth = op(
+                            BinOp::Add,
+                            op(BinOp::Mul, dv, call("ln", u.clone())),
+                            op(BinOp::Div, op(BinOp::Mul, v, du), u),
+                        );
+                        op(BinOp::Mul, self.clone(), growth)
+                    }
+                    BinOp::Rem => return Err(no_derivative(*column)),
+                }
+            }
+            Expr::Call { name, args, column } => {
+                Function::resolve(name, args.len(), *column)?;
+                return call_diff(name, args, *column, x, mode);
+            }
+            Expr::WithUnit { column, .. } | Expr::Convert { column, .. } => {
+                return Err(expr::units_unsupported(*column));
+            }
+        })
+    }
+
+    /// Rewrites the tree into a simpler equivalent: constant subexpressions
+    /// are folded and identities such as `x*1`, `x+0`, `x^1` and `--x` are
+    /// removed.
+    ///
+    /// The identities are algebraic, so `0*x` becomes `0` even though the
+    /// floating-point result would be NaN for an infinite `x`; `0/x` is only
+    /// `0` for a non-zero constant `x`. Constants whose evaluation fails, such
+    /// as `1/0`, are left in place and no identity removes them, and neither
+    /// are folded constants that would not be finite, such as `1e300 * 1e300`.
+    ///
+    /// ```
+    /// use xrust_calclib::expr::parse;
+    ///
+    /// let s = parse("(x*1 + 0) * 2^3 - -y^1").unwrap().simplify();
+    /// assert_eq!(s.to_string(), "8 * x + y");
+    /// ```
+    pub fn simplify(&self) -> Expr {
+        match self {
+            Expr::Num(_) | Expr::Decimal { .. } | Expr::Var { .. } => self.clone(),
+            Expr::Neg(inner) => simplify_neg(inner.simplify()),
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                column,
+            } => simplify_binary(*op, lhs.simplify(), rhs.simplify(), *column),
+            Expr::Call { name, args, column } => {
+                let args: Vec<Expr> = args.iter().map(Expr::simplify).collect();
+                let mut values = [0.0; 2];
+                let constant = args.len() <= values.len()
+                    && args.iter().zip(&mut values).all(|(arg, value)| match arg {
+                        Expr::Num(n) => {
+                            *value = *n;
+                            true
+                        }
+                        _ => false,
+                    });
+                // Trig calls depend on the angle mode when evaluated, so stay symbolic.
+                let folded = Function::resolve(name, args.len(), *column)
+                    .ok()
+                    .filter(|function| constant && !function.uses_angle_mode())
+                    .and_then(|function| {
+                        function
+                            .apply(&values[..args.len()], AngleMode::Radians)
+                            .ok()
+                    });
+                if let Some(value) = folded.filter(|value| value.is_finite()) {
+                    return num(value);
+                }
+                Expr::Call {
+                    name: name.clone(),
+                    args,
+                    column: *column,
+                }
+            }
+            Expr::WithUnit {
+                value,
+                unit,
+                column,
+            } => Expr::WithUnit {
+                value: Box::new(value.simplify()),
+                unit: unit.clone(),
+                column: *column,
+            },
+            Expr::Convert {
+                value,
+                unit,
+                column,
+            } => Expr::Convert {
+                value: Box::new(value.simplify()),
+                unit: unit.clone(),
+                column: *column,
+            },
+        }
+    }
+
+    /// The expression as LaTeX math, e.g. `\frac{1}{2} \cdot x^{2}`.
+    pub fn to_latex(&self) -> String {
+        Latex(self).to_string()
+    }
+}
+
+/// Differentiates a call by the chain rule: `f(u)' = f'(u) * u'`.
+fn call_diff(
+    name: &str,
+    args: &[Expr],
+    column: usize,
+    x: &str,
+    mode: AngleMode,
+) -> Result<Expr, EvalError> {
+    let bin = |bin_op| binary(bin_op, args[0].clone(), args[1].clone(), column);
+    match name {
+        "add" => return bin(BinOp::Add).diff(x, mode),
+        "sub" => return bin(BinOp::Sub).diff(x, mode),
+        "mul" => return bin(BinOp::Mul).diff(x, mode),
+        "div" => return bin(BinOp::Div).diff(x, mode),
+        "pow" => return bin(BinOp::Pow).diff(x, mode),
+        "log_b" => {
+            let ratio = op(
+                BinOp::Div,
+                call("ln", args[0].clone()),
+                call("ln", args[1].clone()),
+            );
+            return ratio.diff(x, mode);
+        }
+        "atan2" => {
+            // d atan2(y, z) = (z * y' - y * z') / (y^2 + z^2)
+            let (y, z) = (args[0].clone(), args[1].clone());
+            let (dy, dz) = (y.diff(x, mode)?, z.diff(x, mode)?);
+            let per_radian = num(mode.from_radians(1.0));
+            return Ok(op(
+                BinOp::Mul,
+                per_radian,
+                op(
+                    BinOp::Div,
+                    op(
+                        BinOp::Sub,
+                        op(BinOp::Mul, z.clone(), dy),
+                        op(BinOp::Mul, y.clone(), dz),
+                    ),
+                    op(
+                        BinOp::Add,
+                        op(BinOp::Pow, y, num(2.0)),
+                        op(BinOp::Pow, z, num(2.0)),
+                    ),
+                ),
+            ));
+        }
+        _ => {}
+    }
+
+    let u = args[0].clone();
+    let square = || op(BinOp::Pow, u.clone(), num(2.0));
+    let recip = |e| op(BinOp::Div, num(1.0), e);
+    // Trig functions read their argument, and inverse trig functions return
+    // their result, in the angle mode's unit.
+    let radians = num(mode.to_radians(1.0));
+    let per_radian = num(mode.from_radians(1.0));
+    let outer = match name {
+        "sqrt" => recip(op(BinOp::Mul, num(2.0), call("sqrt", u.clone()))),
+        "abs" => op(BinOp::Div, u.clone(), call("abs", u.clone())),
+        "sin" => op(BinOp::Mul, radians, call("cos", u.clone())),
+        "cos" => neg(op(BinOp::Mul, radians, call("sin", u.clone()))),
+        "tan" => op(
+            BinOp::Div,
+            radians,
+            op(BinOp::Pow, call("cos", u.clone()), num(2.0)),
+        ),
+        "asin" => op(
+            BinOp::Div,
+            per_radian,
+            call("sqrt", op(BinOp::Sub, num(1.0), square())),
+        ),
+        "acos" => neg(op(
+            BinOp::Div,
+            per_radian,
+            call("sqrt", op(BinOp::Sub, num(1.0), square())),
+        )),
+        "atan" => op(BinOp::Div, per_radian, op(BinOp::Add, num(1.0), square())),
+        "sinh" => call("cosh", u.clone()),
+        "cosh" => call("sinh", u.clone()),
+        "tanh" => recip(op(BinOp::Pow, call("cosh", u.clone()), num(2.0))),
+        "asinh" => recip(call("sqrt", op(BinOp::Add, square(), num(1.0)))),
+        "acosh" => recip(call("sqrt", op(BinOp::Sub, square(), num(1.0)))),
+        "atanh" => recip(op(BinOp::Sub, num(1.0), square())),
+        "ln" => recip(u.clone()),
+        "log10" => recip(op(BinOp::Mul, u.clone(), num(core::f64::consts::LN_10))),
+        "exp" => call("exp", u.clone()),
+        _ => return Err(no_derivative(column)),
+    };
+    Ok(op(BinOp::Mul, outer, u.diff(x, mode)?))
+}
+
+fn simplify_neg(e: Expr) -> Expr {
+    match e {
+        Expr::Num(n) => num(-n),
+        Expr::Neg(inner) => *inner,
+        Expr::Binary {
+            op: BinOp::Mul,
+            lhs,
+            rhs,
+            column,
+        } if matches!(*lhs, Expr::Num(_)) => {
+            let Expr::Num(n) = *lhs else { unreachable!() };
+            simplify_binary(BinOp::Mul, num(-n), *rhs, column)
+        }
+        e => neg(e),
+    }
+}
+
+fn simplify_binary(bin: BinOp, lhs: Expr, rhs: Expr, column: usize) -> Expr {
+    use BinOp::{Add, Div, Mul, Pow, Sub};
+    use Expr::{Neg, Num};
+
+    if let (Num(a), Num(b)) = (&lhs, &rhs)
+        && let Ok(value) = bin.apply(*a, *b)
+        && value.is_finite()
+    {
+        return num(value);
+    }
+    match (bin, lhs, rhs) {
+        (Add, Num(0.0), e) | (Add, e, Num(0.0)) => e,
+        (Add, e, Neg(u)) => simplify_binary(Sub, e, *u, column),
+        (Sub, e, Num(0.0)) => e,
+        (Sub, Num(0.0), e) => simplify_neg(e),
+        (Sub, e, Neg(u)) => simplify_binary(Add, e, *u, column),
+        (Sub, e, Num(n)) if n < 0.0 => binary(Add, e, num(-n), column),
+        (Mul, Num(0.0), e) | (Mul, e, Num(0.0)) if !fails(&e) => num(0.0),
+        (Mul, Num(1.0), e) | (Mul, e, Num(1.0)) => e,
+        (Mul, Num(-1.0), e) | (Mul, e, Num(-1.0)) => simplify_neg(e),
+        // Constant factors go first and merge: `x * 2 * 3` is `6 * x`.
+        (Mul, e, Num(n)) => simplify_binary(Mul, num(n), e, column),
+        (Mul, Num(a), Neg(u)) => simplify_binary(Mul, num(-a), *u, column),
+        (
+            Mul,
+            Num(a),
+            Expr::Binary {
+                op: Mul,
+                lhs: inner,
+                rhs,
+                ..
+            },
+        ) if matches!(*inner, Num(_)) => {
+            let Num(b) = *inner else { unreachable!() };
+            simplify_binary(Mul, num(a * b), *rhs, column)
+        }
+        (Div, e, Num(1.0)) => e,
+        (Div, Num(0.0), e) if matches!(e.eval(), Ok(d) if d != 0.0 && !d.is_nan()) => num(0.0),
+        (Pow, e, Num(0.0)) | (Pow, Num(1.0), e) if !fails(&e) => num(1.0),
+        (Pow, e, Num(1.0)) => e,
+        (bin, lhs, rhs) => binary(bin, lhs, rhs, column),
+    }
+}
+
+/// Whether `e` is a constant whose evaluation fails, such as `1/0`, which an
+/// identity like `0*e = 0` must not make disappear.
+fn fails(e: &Expr) -> bool {
+    matches!(
+        e.eval(),
+        Err(EvalError {
+            kind: EvalErrorKind::Calc(_),
+            ..
+        })
+    )
+}
+
+/// Binding strength, used to decide where parentheses are needed.
+fn precedence(e: &Expr) -> u8 {
+    match e {
+        Expr::Convert { .. } => 0,
+        Expr::Binary { op, .. } => precedence_of(*op),
+        Expr::Neg(_) => 3,
+        Expr::Num(n) if n.is_sign_negative() => 3,
+        Expr::WithUnit { .. } => 4,
+        Expr::Num(_) | Expr::Decimal { .. } | Expr::Var { .. } | Expr::Call { .. } => 5,
+    }
+}
+
+/// Where an operand needs parentheses inside a binary operation.
+fn needs_parens(bin: BinOp, lhs: &Expr, rhs: &Expr, prec: fn(&Expr) -> u8) -> (bool, bool) {
+    match bin {
+        // Right associative and tighter than unary minus: `(-x)^2`, `x^(-1)`.
+        BinOp::Pow => (prec(lhs) <= 4, prec(rhs) < 4),
+        _ => {
+            let p = precedence_of(bin);
+            (prec(lhs) < p, prec(rhs) <= p)
+        }
+    }
+}
+
+fn precedence_of(bin: BinOp) -> u8 {
+    match bin {
+        BinOp::Add | BinOp::Sub => 1,
+        BinOp::Mul | BinOp::Div | BinOp::Rem => 2,
+        BinOp::Pow => 4,
+    }
+}
+
+/// Writes `e`, wrapped in `open`/`close` when `parens` is set.
+fn group(
+    f: &mut fmt::Formatter<'_>,
+    parens: bool,
+    (open, close): (&str, &str),
+    e: impl fmt::Display,
+) -> fmt::Result {
+    if parens {
+        write!(f, "{}{}{}", open, e, close)
+    } else {
+        write!(f, "{}", e)
+    }
+}
+
+/// Formats as infix source that [`parse`](crate::expr::parse) reads back into
+/// the same tree, with only the parentheses precedence requires.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const PARENS: (&str, &str) = ("(", ")");
+        match self {
+            // `inf` would read back as a variable; these literals overflow to
+            // the same values instead.
+            Expr::Num(n) if n.is_nan() => f.write_str("(1e999 - 1e999)"),
+            Expr::Num(n) if n.is_infinite() => {
+                f.write_str(if *n > 0.0 { "1e999" } else { "-1e999" })
+            }
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Decimal { text, .. } => f.write_str(text),
+            Expr::Var { name, .. } => f.write_str(name),
+            Expr::Neg(inner) => {
+                f.write_str("-")?;
+                group(f, precedence(inner) <= 3, PARENS, inner)
+            }
+            Expr::Binary { op, lhs, rhs, .. } => {
+                let (left, right) = needs_parens(*op, lhs, rhs, precedence);
+                group(f, left, PARENS, lhs)?;
+                match op {
+                    BinOp::Pow => f.write_str("^")?,
+                    _ => write!(f, " {} ", op.symbol())?,
+                }
+                group(f, right, PARENS, rhs)
+            }
+            Expr::Call { name, args, .. } => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                f.write_str(")")
+            }
+            Expr::WithUnit { value, unit, .. } => {
+                group(f, precedence(value) < 5, PARENS, value)?;
+                write!(f, " {}", unit)
+            }
+            Expr::Convert { value, unit, .. } => write!(f, "{} -> {}", value, unit),
+        }
+    }
+}
+
+/// Formats an [`Expr`] as LaTeX.
+struct Latex<'a>(&'a Expr);
+
+/// Fractions are set as blocks, so they only need parentheses as a base.
+fn latex_precedence(e: &Expr) -> u8 {
+    match e {
+        Expr::Binary { op: BinOp::Div, .. } => 5,
+        _ => precedence(e),
+    }
+}
+
+impl fmt::Display for Latex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const PARENS: (&str, &str) = ("\\left(", "\\right)");
+        match self.0 {
+            Expr::Num(n) if n.is_nan() => f.write_str("\\mathrm{NaN}"),
+            Expr::Num(n) if n.is_infinite() => {
+                f.write_str(if *n > 0.0 { "\\infty" } else { "-\\infty" })
+            }
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Decimal { text, .. } => f.write_str(text),
+            Expr::Var { name, .. } if name.chars().count() == 1 => f.write_str(name),
+            Expr::Var { name, .. } => write!(f, "\\mathrm{{{}}}", name.replace('_', "\\_")),
+            Expr::Neg(inner) => {
+                f.write_str("-")?;
+                group(f, latex_precedence(inner) <= 3, PARENS, Latex(inner))
+            }
+            Expr::Binary {
+                op: BinOp::Div,
+                lhs,
+                rhs,
+                ..
+            } => write!(f, "\\frac{{{}}}{{{}}}", Latex(lhs), Latex(rhs)),
+            Expr::Binary {
+                op: BinOp::Pow,
+                lhs,
+                rhs,
+                ..
+            } => {
+                group(f, precedence(lhs) <= 4, PARENS, Latex(lhs))?;
+                write!(f, "^{{{}}}", Latex(rhs))
+            }
+            Expr::Binary { op, lhs, rhs, .. } => {
+                let (left, right) = needs_parens(*op, lhs, rhs, latex_precedence);
+                group(f, left, PARENS, Latex(lhs))?;
+                f.write_str(match op {
+                    BinOp::Add => " + ",
+                    BinOp::Sub => " - ",
+                    BinOp::Mul => " \\cdot ",
+                    _ => " \\bmod ",
+                })?;
+                group(f, right, PARENS, Latex(rhs))
+            }
+            Expr::Call { name, args, .. } => latex_call(f, name, args),
+            Expr::WithUnit { value, unit, .. } => {
+                group(f, precedence(value) < 5, PARENS, Latex(value))?;
+                write!(f, "\\,\\mathrm{{{}}}", unit)
+            }
+            Expr::Convert { value, unit, .. } => {
+                write!(f, "{} \\to \\mathrm{{{}}}", Latex(value), unit)
+            }
+        }
+    }
+}
+
+fn latex_call(f: &mut fmt::Formatter<'_>, name: &str, args: &[Expr]) -> fmt::Result {
+    match (name, args) {
+        ("sqrt", [a]) => write!(f, "\\sqrt{{{}}}", Latex(a)),
+        ("abs", [a]) => write!(f, "\\left|{}\\right|", Latex(a)),
+        ("exp", [a]) => write!(f, "e^{{{}}}", Latex(a)),
+        ("fact", [a]) => {
+            group(f, precedence(a) < 5, ("\\left(", "\\right)"), Latex(a))?;
+            f.write_str("!")
+        }
+        ("log10", [a]) => write!(f, "\\log_{{10}}\\left({}\\right)", Latex(a)),
+        ("log_b", [a, b]) => write!(f, "\\log_{{{}}}\\left({}\\right)", Latex(b), Latex(a)),
+        _ => {
+            match name {
+                "sin" | "cos" | "tan" | "sinh" | "cosh" | "tanh" | "ln" => write!(f, "\\{}", name)?,
+                "asin" | "acos" | "atan" => write!(f, "\\arc{}", &name[1..])?,
+                _ => write!(f, "\\operatorname{{{}}}", name.replace('_', "\\_"))?,
+            }
+            f.write_str("\\left(")?;
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", Latex(arg))?;
+            }
+            f.write_str("\\right)")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculus::{self, Options};
+    use crate::expr::parse;
+
+    fn d(src: &str) -> String {
+        parse(src).unwrap().derivative("x").unwrap().to_string()
+    }
+
+    #[test]
+    fn test_infix_round_trip() {
+        for src in [
+            "1 + 2 * 3",
+            "(1 + 2) * 3",
+            "a - (b - c)",
+            "a / (b * c)",
+            "-x^2",
+            "(-x)^2",
+            "x^y^z",
+            "(x^y)^z",
+            "x^(-1)",
+            "2 * -x",
+            "-(-x)",
+            "sqrt(x + 1) % 3",
+            "pow(x, 2) + 3 ms",
+        ] {
+            let e = parse(src).unwrap();
+            assert_eq!(e.to_string(), src);
+            assert_eq!(parse(&e.to_string()).unwrap().to_string(), src);
+        }
+        assert_eq!(parse("((x))+(2*y)").unwrap().to_string(), "x + 2 * y");
+    }
+
+    #[test]
+    fn test_simplify() {
+        let s = |src: &str| parse(src).unwrap().simplify().to_string();
+        assert_eq!(s("x * 1 + 0"), "x");
+        assert_eq!(s("0 * y + x^1"), "x");
+        assert_eq!(s("2 * 3 + x"), "6 + x");
+        assert_eq!(s("x * 2 * 3"), "6 * x");
+        assert_eq!(s("0 - x"), "-x");
+        assert_eq!(s("x - -y"), "x + y");
+        assert_eq!(s("x^0 + sqrt(16)"), "5");
+        assert_eq!(s("1 / 0 + x"), "1 / 0 + x");
+        // Identities keep constants that fail, and overflow is not folded.
+        assert_eq!(s("1 - 0/0"), "1 - 0 / 0");
+        assert_eq!(s("0 * (1/0)"), "0 * (1 / 0)");
+        assert_eq!(s("(1/0)^0"), "(1 / 0)^0");
+        assert_eq!(s("0 / x"), "0 / x");
+        assert_eq!(s("0 / 4 + x"), "x");
+        assert_eq!(s("1e300*1e300*x"), "1e300 * 1e300 * x");
+    }
+
+    #[test]
+    fn test_non_finite_display() {
+        let inf = Expr::Num(f64::INFINITY);
+        assert_eq!(inf.to_string(), "1e999");
+        assert_eq!(parse(&inf.to_string()).unwrap().eval(), Ok(f64::INFINITY));
+        let x = parse("x").unwrap();
+        let product = binary(BinOp::Mul, Expr::Num(f64::NEG_INFINITY), x, 0);
+        assert_eq!(product.to_string(), "-1e999 * x");
+        let nan = Expr::Num(f64::NAN);
+        assert_eq!(nan.to_string(), "(1e999 - 1e999)");
+        assert!(parse(&nan.to_string()).unwrap().eval().unwrap().is_nan());
+        assert_eq!(inf.to_latex(), "\\infty");
+    }
+
+    #[test]
+    fn test_derivatives() {
+        assert_eq!(d("7"), "0");
+        assert_eq!(d("x"), "1");
+        assert_eq!(d("x^2 - 2"), "2 * x");
+        assert_eq!(d("3 * x^2"), "6 * x");
+        assert_eq!(d("x^3 + 2*x"), "3 * x^2 + 2");
+        assert_eq!(d("y^2"), "0");
+        assert_eq!(d("y / z"), "0");
+        assert_eq!(d("y / x"), "-y / x^2");
+        assert_eq!(d("mul(x, x)"), "x + x");
+        if cfg!(feature = "fancy") {
+            assert_eq!(d("sin(x)"), "cos(x)");
+            assert_eq!(d("cos(2*x)"), "-2 * sin(2 * x)");
+            assert_eq!(d("atan(x)"), "1 / (1 + x^2)");
+            assert_eq!(d("atan2(x, 2)"), "2 / (x^2 + 4)");
+            let deg = |src: &str| {
+                let e = parse(src).unwrap();
+                e.derivative_in("x", AngleMode::Degrees)
+                    .unwrap()
+                    .to_string()
+            };
+            assert_eq!(deg("sin(x)"), "0.017453292519943295 * cos(x)");
+            assert_eq!(deg("atan(x)"), "57.29577951308232 / (1 + x^2)");
+        }
+    }
+
+    #[test]
+    fn test_derivatives_match_numeric() {
+        let mut cases = vec![
+            "x^3 / (1 + x^2)",
+            "sqrt(x^2 + 1) * abs(x)",
+            "pow(2*x, 3) - div(1, x)",
+        ];
+        if cfg!(feature = "fancy") {
+            cases.extend([
+                "exp(x^2) / ln(x + 2)",
+                "x^x + 2^x + log_b(x, 3) + log10(x)",
+                "asinh(x) + atanh(x / 2) + acosh(x + 1)",
+                "tanh(x) + sinh(x) * cosh(x)",
+                "sin(x) * cos(2*x) + tan(x / 3)",
+                "asin(x / 2) + acos(x / 3) + atan(x) + atan2(x, 2)",
+            ]);
+        }
+        let modes = [AngleMode::Radians, AngleMode::Degrees, AngleMode::Gradians];
+        for (src, mode) in cases.iter().flat_map(|src| modes.map(|mode| (src, mode))) {
+            let e = parse(src).unwrap();
+            let mut f = e.compile_with_inputs(&["x"]).unwrap();
+            let mut df = e
+                .derivative_in("x", mode)
+                .unwrap()
+                .compile_with_inputs(&["x"])
+                .unwrap();
+            f.set_angle_mode(mode);
+            df.set_angle_mode(mode);
+            for x in [0.3, 0.9, 1.7] {
+                let numeric =
+                    calculus::derivative(|x| f.eval(&[x]).unwrap(), x, Options::default()).unwrap();
+                let symbolic = df.eval(&[x]).unwrap();
+                assert!(
+                    (symbolic - numeric.value).abs() < 1e-6 * symbolic.abs().max(1.0),
+                    "{} in {}: {} vs {} at {}",
+                    src,
+                    mode,
+                    symbolic,
+                    numeric.value,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_derivative_errors() {
+        let err = |src: &str| parse(src).unwrap().derivative("x").unwrap_err();
+        assert_eq!(err("x % 2").column, 3);
+        assert_eq!(
+            err("1 + fact(x)").kind,
+            crate::EvalErrorKind::Calc(CalcError::Domain("Expression has no symbolic derivative"))
+        );
+        assert_eq!(err("nope(x)").column, 1);
+        assert_eq!(err("2 m").column, 3);
+    }
+
+    #[test]
+    fn test_latex() {
+        let tex = |src: &str| parse(src).unwrap().to_latex();
+        assert_eq!(tex("x^2/2 + sqrt(y)"), "\\frac{x^{2}}{2} + \\sqrt{y}");
+        assert_eq!(tex("(a + b)^-1"), "\\left(a + b\\right)^{-1}");
+        assert_eq!(tex("2 * (x / 3)"), "2 \\cdot \\frac{x}{3}");
+        assert_eq!(
+            tex("fact(n) * abs(x_1)"),
+            "n! \\cdot \\left|\\mathrm{x\\_1}\\right|"
+        );
+        assert_eq!(
+            tex("sin(theta) - atan(x)"),
+            "\\sin\\left(\\mathrm{theta}\\right) - \\arctan\\left(x\\right)"
+        );
+        assert_eq!(
+            tex("gamma(x, y)"),
+            "\\operatorname{gamma}\\left(x, y\\right)"
+        );
+    }
+}
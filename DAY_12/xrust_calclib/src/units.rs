@@ -0,0 +1,646 @@
+//! Physical units and dimension-checked quantities.
+//!
+//! A [`Quantity`] stores its magnitude in SI base units together with its
+//! [`Dimension`], so `3 ms + 200 us` adds up correctly while `3 ms + 2 m` is a
+//! [`CalcError::DimensionMismatch`]. Multiplication and division combine
+//! dimensions. The conversion table covers length, time, frequency,
+//! temperature, angle and voltage; see [`unit()`] for the accepted symbols.
+//!
+//! ```
+//! use xrust_calclib::units::Quantity;
+//!
+//! let pulse = Quantity::new(1.5, "ms").unwrap();
+//! let period = Quantity::new(50.0, "Hz").unwrap().to("ms").unwrap();
+//! assert_eq!(period.to_string(), "20 ms");
+//! assert_eq!(pulse.checked_div(period).unwrap().si_value(), 0.075);
+//! ```
+//!
+//! Temperatures are stored as absolute kelvin, so converting `20 degC` to
+//! `degF` is exact. A `degC` or `degF` temperature is a point on its scale:
+//! subtracting two points gives a difference (`30 degC - 20 degC` is `10 K`
+//! in kelvin), and adding anything to a point reads it as a difference, so
+//! `20 degC + 5 K` and `5 K + 20 degC` are both `25 degC`. Adding points on
+//! different scales, or multiplying one, has no meaning and is an error.
+
+use core::fmt;
+use core::ops::Neg;
+
+use crate::{CalcError, math};
+
+/// Exponents of the base dimensions of a quantity; `m/s` is length 1, time -1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Dimension {
+    pub length: i8,
+    pub time: i8,
+    pub temperature: i8,
+    pub angle: i8,
+    pub voltage: i8,
+}
+
+impl Dimension {
+    /// A plain number.
+    pub const NONE: Self = Dimension::new([0, 0, 0, 0, 0]);
+    pub const LENGTH: Self = Dimension::new([1, 0, 0, 0, 0]);
+    pub const TIME: Self = Dimension::new([0, 1, 0, 0, 0]);
+    pub const FREQUENCY: Self = Dimension::new([0, -1, 0, 0, 0]);
+    pub const TEMPERATURE: Self = Dimension::new([0, 0, 1, 0, 0]);
+    pub const ANGLE: Self = Dimension::new([0, 0, 0, 1, 0]);
+    pub const VOLTAGE: Self = Dimension::new([0, 0, 0, 0, 1]);
+
+    const SYMBOLS: [&'static str; 5] = ["m", "s", "K", "rad", "V"];
+
+    const fn new([length, time, temperature, angle, voltage]: [i8; 5]) -> Self {
+        Dimension {
+            length,
+            time,
+            temperature,
+            angle,
+            voltage,
+        }
+    }
+
+    fn exponents(self) -> [i8; 5] {
+        [
+            self.length,
+            self.time,
+            self.temperature,
+            self.angle,
+            self.voltage,
+        ]
+    }
+
+    fn zip(self, rhs: Self, op: impl Fn(i8, i8) -> Option<i8>) -> Result<Self, CalcError> {
+        let mut out = [0; 5];
+        for ((o, a), b) in out.iter_mut().zip(self.exponents()).zip(rhs.exponents()) {
+            *o = op(a, b).ok_or(CalcError::Overflow)?;
+        }
+        Ok(Dimension::new(out))
+    }
+
+    /// Whether this is a plain number.
+    pub fn is_none(self) -> bool {
+        self == Dimension::NONE
+    }
+
+    /// The dimension of a product.
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, CalcError> {
+        self.zip(rhs, i8::checked_add)
+    }
+
+    /// The dimension of a quotient.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, CalcError> {
+        self.zip(rhs, i8::checked_sub)
+    }
+
+    /// The dimension raised to an integer power.
+    pub fn checked_pow(self, n: i32) -> Result<Self, CalcError> {
+        let n = i8::try_from(n).map_err(|_| CalcError::Overflow)?;
+        self.zip(Dimension::NONE, |a, _| a.checked_mul(n))
+    }
+}
+
+/// Formats in SI base units, e.g. `m/s^2`; empty for [`Dimension::NONE`].
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exps = self.exponents();
+        let mut first = true;
+        for (sym, &e) in Dimension::SYMBOLS
+            .iter()
+            .zip(&exps)
+            .filter(|(_, e)| **e > 0)
+        {
+            if !first {
+                f.write_str("*")?;
+            }
+            first = false;
+            f.write_str(sym)?;
+            if e != 1 {
+                write!(f, "^{}", e)?;
+            }
+        }
+        for (sym, &e) in Dimension::SYMBOLS
+            .iter()
+            .zip(&exps)
+            .filter(|(_, e)| **e < 0)
+        {
+            if first {
+                f.write_str("1")?;
+                first = false;
+            }
+            write!(f, "/{}", sym)?;
+            if e != -1 {
+                write!(f, "^{}", -e)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A named unit: `si = value * scale + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub dimension: Dimension,
+    pub scale: f64,
+    /// Non-zero only for temperature scales with a shifted zero point.
+    pub offset: f64,
+}
+
+impl Unit {
+    const fn linear(symbol: &'static str, dimension: Dimension, scale: f64) -> Self {
+        Unit {
+            symbol,
+            dimension,
+            scale,
+            offset: 0.0,
+        }
+    }
+
+    fn si_of(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+
+    fn value_of(&self, si: f64) -> f64 {
+        (si - self.offset) / self.scale
+    }
+}
+
+const PI: f64 = core::f64::consts::PI;
+
+/// Every unit [`unit()`] recognises.
+pub const UNITS: &[Unit] = &[
+    Unit::linear("m", Dimension::LENGTH, 1.0),
+    Unit::linear("km", Dimension::LENGTH, 1e3),
+    Unit::linear("cm", Dimension::LENGTH, 1e-2),
+    Unit::linear("mm", Dimension::LENGTH, 1e-3),
+    Unit::linear("um", Dimension::LENGTH, 1e-6),
+    Unit::linear("µm", Dimension::LENGTH, 1e-6),
+    Unit::linear("nm", Dimension::LENGTH, 1e-9),
+    Unit::linear("in", Dimension::LENGTH, 0.0254),
+    Unit::linear("ft", Dimension::LENGTH, 0.3048),
+    Unit::linear("yd", Dimension::LENGTH, 0.9144),
+    Unit::linear("mi", Dimension::LENGTH, 1609.344),
+    Unit::linear("s", Dimension::TIME, 1.0),
+    Unit::linear("ms", Dimension::TIME, 1e-3),
+    Unit::linear("us", Dimension::TIME, 1e-6),
+    Unit::linear("µs", Dimension::TIME, 1e-6),
+    Unit::linear("ns", Dimension::TIME, 1e-9),
+    Unit::linear("min", Dimension::TIME, 60.0),
+    Unit::linear("h", Dimension::TIME, 3600.0),
+    Unit::linear("day", Dimension::TIME, 86400.0),
+    Unit::linear("Hz", Dimension::FREQUENCY, 1.0),
+    Unit::linear("kHz", Dimension::FREQUENCY, 1e3),
+    Unit::linear("MHz", Dimension::FREQUENCY, 1e6),
+    Unit::linear("GHz", Dimension::FREQUENCY, 1e9),
+    Unit::linear("rpm", Dimension::FREQUENCY, 1.0 / 60.0),
+    Unit::linear("K", Dimension::TEMPERATURE, 1.0),
+    Unit {
+        symbol: "degC",
+        dimension: Dimension::TEMPERATURE,
+        scale: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        symbol: "°C",
+        dimension: Dimension::TEMPERATURE,
+        scale: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        symbol: "degF",
+        dimension: Dimension::TEMPERATURE,
+        scale: 5.0 / 9.0,
+        offset: 459.67 * 5.0 / 9.0,
+    },
+    Unit {
+        symbol: "°F",
+        dimension: Dimension::TEMPERATURE,
+        scale: 5.0 / 9.0,
+        offset: 459.67 * 5.0 / 9.0,
+    },
+    Unit::linear("rad", Dimension::ANGLE, 1.0),
+    Unit::linear("deg", Dimension::ANGLE, PI / 180.0),
+    Unit::linear("°", Dimension::ANGLE, PI / 180.0),
+    Unit::linear("grad", Dimension::ANGLE, PI / 200.0),
+    Unit::linear("rev", Dimension::ANGLE, 2.0 * PI),
+    Unit::linear("V", Dimension::VOLTAGE, 1.0),
+    Unit::linear("kV", Dimension::VOLTAGE, 1e3),
+    Unit::linear("mV", Dimension::VOLTAGE, 1e-3),
+    Unit::linear("uV", Dimension::VOLTAGE, 1e-6),
+    Unit::linear("µV", Dimension::VOLTAGE, 1e-6),
+];
+
+/// Looks up a unit by its symbol (case-sensitive: `ms` is not `Ms`).
+pub fn unit(symbol: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|u| u.symbol == symbol)
+}
+
+fn lookup(symbol: &str) -> Result<&'static Unit, CalcError> {
+    unit(symbol).ok_or(CalcError::Domain("Unknown unit"))
+}
+
+/// A magnitude with a dimension, stored in SI base units.
+///
+/// A quantity remembers the unit it was created in or converted to and is
+/// displayed in it; results of multiplication and division are displayed in
+/// SI base units.
+#[derive(Debug, Clone, Copy)]
+pub struct Quantity {
+    si: f64,
+    dimension: Dimension,
+    unit: Option<&'static Unit>,
+    /// Whether `si` is a difference rather than a point on the unit's scale.
+    difference: bool,
+}
+
+impl Quantity {
+    /// `value` in the unit named `symbol`.
+    pub fn new(value: f64, symbol: &str) -> Result<Self, CalcError> {
+        Ok(Quantity::of(value, lookup(symbol)?))
+    }
+
+    /// `value` in `unit`.
+    pub fn of(value: f64, unit: &'static Unit) -> Self {
+        Quantity {
+            si: unit.si_of(value),
+            dimension: unit.dimension,
+            unit: Some(unit),
+            difference: false,
+        }
+    }
+
+    /// A plain number.
+    pub const fn number(value: f64) -> Self {
+        Quantity::from_si(value, Dimension::NONE)
+    }
+
+    /// A quantity from its SI magnitude.
+    pub const fn from_si(si: f64, dimension: Dimension) -> Self {
+        Quantity {
+            si,
+            dimension,
+            unit: None,
+            difference: false,
+        }
+    }
+
+    /// The magnitude in SI base units (kelvin for temperatures).
+    pub fn si_value(&self) -> f64 {
+        self.si
+    }
+
+    /// The dimension.
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    /// The unit the quantity is displayed in, if any.
+    pub fn unit(&self) -> Option<&'static Unit> {
+        self.unit
+    }
+
+    /// The magnitude in the display unit, or in SI base units if there is none.
+    pub fn value(&self) -> f64 {
+        match self.unit {
+            Some(unit) if self.difference => self.si / unit.scale,
+            Some(unit) => unit.value_of(self.si),
+            None => self.si,
+        }
+    }
+
+    /// The magnitude in the unit named `symbol`. See [`to`](Self::to).
+    pub fn value_in(&self, symbol: &str) -> Result<f64, CalcError> {
+        Ok(self.to(symbol)?.value())
+    }
+
+    /// Converts to the unit named `symbol`. A quantity can also be converted
+    /// to a unit of the inverse dimension, which takes the reciprocal:
+    /// `50 Hz` converts to `20 ms`.
+    pub fn to(&self, symbol: &str) -> Result<Self, CalcError> {
+        let unit = lookup(symbol)?;
+        let si = if unit.dimension == self.dimension {
+            self.si
+        } else if unit.dimension == Dimension::NONE.checked_div(self.dimension)? {
+            crate::div(1.0, self.si)?
+        } else {
+            return Err(CalcError::DimensionMismatch);
+        };
+        Ok(Quantity {
+            si,
+            dimension: unit.dimension,
+            unit: Some(unit),
+            ..*self
+        })
+    }
+
+    fn same_dimension(&self, rhs: &Self) -> Result<(), CalcError> {
+        if self.dimension == rhs.dimension {
+            Ok(())
+        } else {
+            Err(CalcError::DimensionMismatch)
+        }
+    }
+
+    /// The zero point of the scale `self` is a point on, in kelvin: 273.15
+    /// for a `degC` temperature and 0 for kelvin, differences and anything
+    /// that is not a temperature.
+    fn offset(&self) -> f64 {
+        match self.unit {
+            Some(unit) if !self.difference => unit.offset,
+            _ => 0.0,
+        }
+    }
+
+    /// Errors if `self` is a point on an offset scale, which cannot be scaled.
+    fn linear(&self) -> Result<(), CalcError> {
+        if self.offset() == 0.0 {
+            Ok(())
+        } else {
+            Err(CalcError::Domain(
+                "Only temperature differences can be multiplied or divided",
+            ))
+        }
+    }
+
+    /// `self + rhs`; the dimensions must match. A `degC` or `degF` point plus
+    /// a difference (or kelvin) stays a point on its scale, in either order;
+    /// two points must be on the same scale, and the second one then counts
+    /// from its zero, so `20 degC + 5 degC` is `25 degC`. Otherwise the sum is
+    /// shown in `self`'s unit.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, CalcError> {
+        self.same_dimension(&rhs)?;
+        match (self.offset(), rhs.offset()) {
+            (0.0, 0.0) => Ok(Quantity {
+                si: self.si + rhs.si,
+                difference: self.difference && rhs.difference,
+                ..self
+            }),
+            (0.0, _) => Ok(Quantity {
+                si: rhs.si + self.si,
+                ..rhs
+            }),
+            (_, 0.0) => Ok(Quantity {
+                si: self.si + rhs.si,
+                ..self
+            }),
+            (a, b) if a == b => Ok(Quantity {
+                si: self.si + (rhs.si - b),
+                ..self
+            }),
+            _ => Err(CalcError::Domain(
+                "Temperatures on different scales cannot be added",
+            )),
+        }
+    }
+
+    /// `self - rhs`, shown in `self`'s unit; the dimensions must match. Two
+    /// temperature points give their difference, so `(30 degC - 20 degC) -> K`
+    /// is `10 K`, and a point minus a difference is a point again.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, CalcError> {
+        self.same_dimension(&rhs)?;
+        let difference = if rhs.offset() != 0.0 {
+            if self.difference {
+                return Err(CalcError::Domain(
+                    "A temperature point cannot be subtracted from a difference",
+                ));
+            }
+            true
+        } else {
+            self.offset() == 0.0 && (self.difference || !rhs.difference)
+        };
+        Ok(Quantity {
+            si: self.si - rhs.si,
+            difference,
+            ..self
+        })
+    }
+
+    /// `self % rhs`, shown in `self`'s unit; the dimensions must match.
+    pub fn checked_rem(self, rhs: Self) -> Result<Self, CalcError> {
+        self.same_dimension(&rhs)?;
+        Ok(Quantity {
+            si: crate::mode(self.si, rhs.si)?,
+            ..self
+        })
+    }
+
+    /// `self * rhs`, combining dimensions.
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, CalcError> {
+        self.linear()?;
+        rhs.linear()?;
+        let dimension = self.dimension.checked_mul(rhs.dimension)?;
+        Ok(combine(self.si * rhs.si, dimension, [self, rhs]))
+    }
+
+    /// `self / rhs`, combining dimensions.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, CalcError> {
+        self.linear()?;
+        rhs.linear()?;
+        let dimension = self.dimension.checked_div(rhs.dimension)?;
+        Ok(combine(
+            crate::div(self.si, rhs.si)?,
+            dimension,
+            [self, rhs],
+        ))
+    }
+
+    /// `self` raised to an integer power.
+    pub fn checked_powi(self, n: i32) -> Result<Self, CalcError> {
+        let dimension = self.dimension.checked_pow(n)?;
+        self.linear()?;
+        Ok(combine(
+            math::powf(self.si, f64::from(n)),
+            dimension,
+            [self, Quantity::number(1.0)],
+        ))
+    }
+
+    /// The square root; every exponent of the dimension must be even.
+    pub fn sqrt(self) -> Result<Self, CalcError> {
+        let exps = self.dimension.exponents();
+        if exps.iter().any(|e| e % 2 != 0) {
+            return Err(CalcError::DimensionMismatch);
+        }
+        let dimension = Dimension::new(exps.map(|e| e / 2));
+        self.linear()?;
+        Ok(combine(
+            crate::sqrt(self.si)?,
+            dimension,
+            [self, Quantity::number(1.0)],
+        ))
+    }
+}
+
+/// Keeps the first operand's display unit that still fits the result, so
+/// `2 * 3 ms` stays in milliseconds while `m / s` falls back to SI units. A
+/// scaled temperature difference stays a difference, which may be in `degC`.
+fn combine(si: f64, dimension: Dimension, operands: [Quantity; 2]) -> Quantity {
+    let difference = operands.iter().any(|q| q.difference);
+    let unit = operands
+        .iter()
+        .filter_map(|q| q.unit)
+        .find(|u| u.dimension == dimension && (difference || u.offset == 0.0));
+    Quantity {
+        si,
+        dimension,
+        unit,
+        difference,
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity::number(value)
+    }
+}
+
+/// Negates in the display unit, so `-(20 degC)` is `-20 degC`.
+impl Neg for Quantity {
+    type Output = Quantity;
+
+    fn neg(self) -> Quantity {
+        match self.unit {
+            Some(unit) if !self.difference => Quantity::of(-self.value(), unit),
+            _ => Quantity {
+                si: -self.si,
+                ..self
+            },
+        }
+    }
+}
+
+/// Quantities are equal when they have the same dimension and SI magnitude,
+/// whatever unit they are displayed in.
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.dimension == other.dimension && self.si == other.si
+    }
+}
+
+/// Formats as `20 ms`, or in SI base units such as `9.81 m/s^2`.
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            Some(unit) => write!(f, "{} {}", self.value(), unit.symbol),
+            None if self.dimension.is_none() => write!(f, "{}", self.si),
+            None => write!(f, "{} {}", self.si, self.dimension),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::string::ToString;
+
+    fn q(value: f64, symbol: &str) -> Quantity {
+        Quantity::new(value, symbol).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(q(1.0, "km").value_in("m"), Ok(1000.0));
+        assert!(close(q(1.0, "mi").value_in("ft").unwrap(), 5280.0));
+        assert!(close(q(180.0, "deg").value_in("rad").unwrap(), PI));
+        assert!(close(q(100.0, "degC").value_in("degF").unwrap(), 212.0));
+        assert!(close(q(-40.0, "°F").value_in("°C").unwrap(), -40.0));
+        assert!(close(q(0.0, "degC").value_in("K").unwrap(), 273.15));
+        assert!(close(q(3.3, "V").value_in("mV").unwrap(), 3300.0));
+        assert_eq!(q(50.0, "Hz").value_in("ms"), Ok(20.0));
+        assert_eq!(q(2.0, "ms").value_in("kHz"), Ok(0.5));
+        assert_eq!(q(1.0, "m").to("s"), Err(CalcError::DimensionMismatch));
+        assert_eq!(q(0.0, "Hz").to("s"), Err(CalcError::DivisionByZero));
+        assert!(matches!(
+            Quantity::new(1.0, "parsec"),
+            Err(CalcError::Domain(_))
+        ));
+    }
+
+    #[test]
+    fn test_dimension_tracking() {
+        let sum = q(3.0, "ms").checked_add(q(200.0, "us")).unwrap();
+        assert_eq!(sum.to_string(), "3.2 ms");
+        assert_eq!(
+            q(3.0, "ms").checked_add(q(2.0, "m")),
+            Err(CalcError::DimensionMismatch)
+        );
+
+        let speed = q(100.0, "m").checked_div(q(9.58, "s")).unwrap();
+        assert_eq!(speed.dimension(), Dimension::new([1, -1, 0, 0, 0]));
+        let accel = speed.checked_div(q(1.0, "s")).unwrap();
+        assert_eq!(accel.dimension().to_string(), "m/s^2");
+        assert_eq!(Dimension::FREQUENCY.to_string(), "1/s");
+
+        // Frequency times time is a plain number.
+        let cycles = q(50.0, "Hz").checked_mul(q(2.0, "s")).unwrap();
+        assert_eq!(cycles, Quantity::number(100.0));
+        assert_eq!(cycles.to_string(), "100");
+
+        let area = q(3.0, "m").checked_powi(2).unwrap();
+        assert_eq!(area.to_string(), "9 m^2");
+        assert_eq!(area.sqrt(), Ok(q(3.0, "m")));
+        assert_eq!(q(3.0, "m").sqrt(), Err(CalcError::DimensionMismatch));
+
+        let doubled = Quantity::number(2.0).checked_mul(q(1.5, "ms")).unwrap();
+        assert_eq!(doubled.to_string(), "3 ms");
+        assert_eq!(
+            q(1.5, "ms").checked_mul(2.0.into()).unwrap().to_string(),
+            "3 ms"
+        );
+        assert_eq!((-q(20.0, "degC")).to_string(), "-20 degC");
+    }
+
+    #[test]
+    fn test_temperature_differences() {
+        let warmer = q(20.0, "degC").checked_add(q(5.0, "degC")).unwrap();
+        assert!(close(warmer.value(), 25.0));
+        assert_eq!(warmer.unit().unwrap().symbol, "degC");
+        let cooler = q(30.0, "degC").checked_sub(q(20.0, "degC")).unwrap();
+        assert!(close(cooler.value(), 10.0));
+        assert!(close(cooler.value_in("K").unwrap(), 10.0));
+        assert!(close(cooler.value_in("degF").unwrap(), 18.0));
+        assert!(close(
+            q(30.0, "degC")
+                .checked_sub(q(50.0, "degF"))
+                .unwrap()
+                .value(),
+            20.0
+        ));
+        // The degF point is the point here, so 300 K is 540 degF on top.
+        assert!(close(
+            q(300.0, "K").checked_add(q(9.0, "degF")).unwrap().value(),
+            549.0
+        ));
+        assert!(close(
+            q(50.0, "degF").checked_sub(q(10.0, "K")).unwrap().value(),
+            32.0
+        ));
+        // Kelvin is added as a difference whichever side it is on.
+        let sum = q(20.0, "degC").checked_add(q(5.0, "K")).unwrap();
+        assert!(close(sum.value(), 25.0));
+        assert_eq!(sum, q(5.0, "K").checked_add(q(20.0, "degC")).unwrap());
+        // A point plus a difference is a point again.
+        let back = q(20.0, "degC").checked_add(cooler).unwrap();
+        assert!(close(back.value_in("K").unwrap(), 303.15));
+        let scaled = Quantity::number(2.0).checked_mul(cooler).unwrap();
+        assert!(close(scaled.value_in("K").unwrap(), 20.0));
+
+        assert!(matches!(
+            q(20.0, "degC").checked_add(q(50.0, "degF")),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            Quantity::number(2.0).checked_mul(q(5.0, "degC")),
+            Err(CalcError::Domain(_))
+        ));
+        assert!(matches!(
+            cooler.checked_sub(q(20.0, "degC")),
+            Err(CalcError::Domain(_))
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! `wasm_bindgen` exports for browsers (feature `wasm`).
+//!
+//! The calculator functions keep their Rust names, the evaluators are
+//! `evalExpr`, `evalExact` and `evalComplex`, and [`Calculator`] wraps a
+//! [`Session`] and records its lines in a [`History`]. A failure is thrown
+//! as a JavaScript `Error` named `CalcError` with these extra fields:
+//!
+//! - `kind`: the error variant, such as `"DivisionByZero"`, or `"Syntax"` for
+//!   parse errors and unknown names
+//! - `column`: the 1-based column in the expression, for evaluator errors
+//!
+//! A library crate cannot also be a `cdylib` without breaking `no_std`
+//! builds, so the browser module is built from a one-line wrapper crate such
+//! as `DAY_18/calc_wasm`.
+
+use alloc::string::{String, ToString};
+
+use js_sys::{Array, Date, Error, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::CalcError;
+use crate::expr::{EvalError, EvalErrorKind};
+use crate::history::{Entry, History};
+use crate::session::Session;
+
+/// The `kind` field for a [`CalcError`].
+fn calc_kind(err: CalcError) -> &'static str {
+    match err {
+        CalcError::DivisionByZero => "DivisionByZero",
+        CalcError::ModuloByZero => "ModuloByZero",
+        CalcError::NegativeSqrt => "NegativeSqrt",
+        CalcError::NegativeFactorial => "NegativeFactorial",
+        CalcError::NonIntegerFactorial => "NonIntegerFactorial",
+        CalcError::Overflow => "Overflow",
+        CalcError::Domain(_) => "Domain",
+        CalcError::EmptyInput => "EmptyInput",
+        CalcError::LengthMismatch => "LengthMismatch",
+        CalcError::SingularMatrix => "SingularMatrix",
+        CalcError::ShapeMismatch => "ShapeMismatch",
+        CalcError::DimensionMismatch => "DimensionMismatch",
+    }
+}
+
+/// The `kind` field for an [`EvalError`].
+fn eval_kind(err: &EvalError) -> &'static str {
+    match err.kind {
+        EvalErrorKind::Syntax(_) => "Syntax",
+        EvalErrorKind::Calc(err) => calc_kind(err),
+    }
+}
+
+/// A JS `Error` named `CalcError` carrying `kind` and, if given, `column`.
+fn js_error(message: &str, kind: &str, column: Option<usize>) -> JsValue {
+    let err = Error::new(message);
+    err.set_name("CalcError");
+    // Setting a property on a fresh `Error` object cannot fail.
+    let _ = Reflect::set(&err, &"kind".into(), &kind.into());
+    if let Some(column) = column {
+        let _ = Reflect::set(&err, &"column".into(), &(column as f64).into());
+    }
+    err.into()
+}
+
+fn calc_error(err: CalcError) -> JsValue {
+    js_error(&err.to_string(), calc_kind(err), None)
+}
+
+fn eval_error(err: EvalError) -> JsValue {
+    js_error(&err.to_string(), eval_kind(&err), Some(err.column))
+}
+
+/// A history entry as a plain object; `value` and `error` are left out when
+/// absent, like in the serde form.
+fn entry_object(entry: &Entry) -> JsValue {
+    let object = Object::new();
+    let operands = Object::new();
+    // Setting properties on fresh plain objects cannot fail.
+    for (name, value) in &entry.operands {
+        let _ = Reflect::set(&operands, &name.into(), &(*value).into());
+    }
+    let _ = Reflect::set(
+        &object,
+        &"expression".into(),
+        &entry.expression.as_str().into(),
+    );
+    let _ = Reflect::set(&object, &"operands".into(), &operands);
+    if let Some(value) = entry.value {
+        let _ = Reflect::set(&object, &"value".into(), &value.into());
+    }
+    if let Some(error) = &entry.error {
+        let _ = Reflect::set(&object, &"error".into(), &error.as_str().into());
+    }
+    let _ = Reflect::set(
+        &object,
+        &"timestamp".into(),
+        &(entry.timestamp as f64).into(),
+    );
+    object.into()
+}
+
+/// `a + b`.
+#[wasm_bindgen]
+pub fn add(a: f64, b: f64) -> f64 {
+    crate::add(a, b)
+}
+
+/// `a - b`.
+#[wasm_bindgen]
+pub fn sub(a: f64, b: f64) -> f64 {
+    crate::sub(a, b)
+}
+
+/// `a * b`.
+#[wasm_bindgen]
+pub fn mul(a: f64, b: f64) -> f64 {
+    crate::mul(a, b)
+}
+
+/// `a / b`; throws on division by zero.
+#[wasm_bindgen]
+pub fn div(a: f64, b: f64) -> Result<f64, JsValue> {
+    crate::div(a, b).map_err(calc_error)
+}
+
+/// `a % b`; throws on modulo by zero.
+#[wasm_bindgen]
+pub fn mode(a: f64, b: f64) -> Result<f64, JsValue> {
+    crate::mode(a, b).map_err(calc_error)
+}
+
+/// `a` raised to `b`.
+#[wasm_bindgen]
+pub fn pow(a: f64, b: f64) -> f64 {
+    crate::pow(a, b)
+}
+
+/// Square root; throws for negative input.
+#[wasm_bindgen]
+pub fn sqrt(a: f64) -> Result<f64, JsValue> {
+    crate::sqrt(a).map_err(calc_error)
+}
+
+/// Absolute value.
+#[wasm_bindgen]
+pub fn abs(a: f64) -> f64 {
+    crate::abs(a)
+}
+
+/// Factorial of a non-negative integer.
+#[wasm_bindgen]
+pub fn fact(a: f64) -> Result<f64, JsValue> {
+    crate::fact(a).map_err(calc_error)
+}
+
+/// The gamma function.
+#[wasm_bindgen]
+pub fn gamma(a: f64) -> Result<f64, JsValue> {
+    crate::gamma(a).map_err(calc_error)
+}
+
+/// The natural logarithm of `|gamma(a)|`.
+#[wasm_bindgen]
+pub fn lgamma(a: f64) -> Result<f64, JsValue> {
+    crate::lgamma(a).map_err(calc_error)
+}
+
+/// Evaluates an infix expression; see [`eval`](crate::eval).
+#[wasm_bindgen(js_name = evalExpr)]
+pub fn eval_expr(src: &str) -> Result<f64, JsValue> {
+    crate::eval(src).map_err(eval_error)
+}
+
+/// Evaluates an expression exactly and returns the fraction as text, e.g. `"1/2"`.
+#[wasm_bindgen(js_name = evalExact)]
+pub fn eval_exact(src: &str) -> Result<String, JsValue> {
+    crate::eval_exact(src)
+        .map(|r| r.to_string())
+        .map_err(eval_error)
+}
+
+/// Evaluates an expression over complex numbers and returns it as text, e.g. `"1+2i"`.
+#[wasm_bindgen(js_name = evalComplex)]
+pub fn eval_complex(src: &str) -> Result<String, JsValue> {
+    crate::eval_complex(src)
+        .map(|z| z.to_string())
+        .map_err(eval_error)
+}
+
+/// A calculator with variables, `ans`, user functions and a history of the
+/// lines it ran.
+#[wasm_bindgen]
+pub struct Calculator {
+    session: Session,
+    history: History,
+}
+
+impl Default for Calculator {
+    fn default() -> Self {
+        Calculator::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Calculator {
+    /// An empty calculator.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Calculator {
+        Calculator {
+            session: Session::new(),
+            history: History::new(),
+        }
+    }
+
+    /// Runs one line, recording it with `Date.now()`, and returns what it did
+    /// as text: `"3"`, `"x = 3"` or `"f defined"`.
+    pub fn eval(&mut self, line: &str) -> Result<String, JsValue> {
+        // The system clock is unavailable on wasm32-unknown-unknown.
+        let now = Date::now() as u64;
+        self.history
+            .eval_at(&mut self.session, line, now)
+            .map(|outcome| outcome.to_string())
+            .map_err(eval_error)
+    }
+
+    /// The lines run so far, oldest first, as objects with `expression`,
+    /// `operands`, `timestamp` and `value` or `error`.
+    pub fn history(&self) -> Array {
+        self.history.entries().iter().map(entry_object).collect()
+    }
+
+    /// Forgets the recorded lines, keeping variables and functions.
+    #[wasm_bindgen(js_name = clearHistory)]
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// The last value computed.
+    #[wasm_bindgen(getter)]
+    pub fn ans(&self) -> f64 {
+        self.session.ans()
+    }
+
+    /// The angle unit for trig functions: `"radians"`, `"degrees"` or
+    /// `"gradians"`.
+    #[wasm_bindgen(getter = angleMode)]
+    pub fn angle_mode(&self) -> String {
+        self.session.angle_mode().to_string()
+    }
+
+    /// Sets the angle unit from `"rad"`, `"deg"`, `"grad"` or a full name;
+    /// throws for anything else.
+    #[wasm_bindgen(setter = angleMode)]
+    pub fn set_angle_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        let mode = mode.parse().map_err(calc_error)?;
+        self.session.set_angle_mode(mode);
+        Ok(())
+    }
+
+    /// Forgets all variables and functions; the history is kept.
+    pub fn clear(&mut self) {
+        self.session.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Building JS values needs a wasm host, so only the mapping is tested here.
+    #[test]
+    fn test_error_kinds() {
+        assert_eq!(calc_kind(CalcError::DivisionByZero), "DivisionByZero");
+        assert_eq!(calc_kind(CalcError::Domain("x")), "Domain");
+        assert_eq!(
+            eval_kind(&crate::eval("1 / 0").unwrap_err()),
+            "DivisionByZero"
+        );
+        assert_eq!(eval_kind(&crate::eval("1 +").unwrap_err()), "Syntax");
+        assert_eq!(eval_kind(&crate::eval("nope(1)").unwrap_err()), "Syntax");
+    }
+}
@@ -0,0 +1,18 @@
+//! Generates `xrust_calclib.h` from `src/lib.rs` with cbindgen into `OUT_DIR`.
+//! The copy in `include/` is checked against it by `tests/c_api.rs`.
+
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    let config = cbindgen::Config::from_file("cbindgen.toml").expect("cbindgen.toml is valid");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src("src/lib.rs")
+        .generate()
+        .expect("src/lib.rs is parseable by cbindgen")
+        .write_to_file(out_dir.join("xrust_calclib.h"));
+}
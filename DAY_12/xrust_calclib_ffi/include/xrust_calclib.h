@@ -0,0 +1,252 @@
+/* C API for xrust_calclib. */
+
+#ifndef XRUST_CALCLIB_H
+#define XRUST_CALCLIB_H
+
+/* Generated by cbindgen from src/lib.rs; do not edit by hand. */
+
+#include <stddef.h>
+
+/**
+ * Result code of every fallible `xc_` function.
+ */
+typedef enum {
+  XC_STATUS_OK = 0,
+  XC_STATUS_DIVISION_BY_ZERO,
+  XC_STATUS_MODULO_BY_ZERO,
+  XC_STATUS_NEGATIVE_SQRT,
+  XC_STATUS_NEGATIVE_FACTORIAL,
+  XC_STATUS_NON_INTEGER_FACTORIAL,
+  XC_STATUS_OVERFLOW,
+  XC_STATUS_DOMAIN,
+  XC_STATUS_EMPTY_INPUT,
+  XC_STATUS_LENGTH_MISMATCH,
+  XC_STATUS_SINGULAR_MATRIX,
+  XC_STATUS_SHAPE_MISMATCH,
+  XC_STATUS_DIMENSION_MISMATCH,
+  /**
+   * The expression could not be parsed or named an unknown function.
+   */
+  XC_STATUS_SYNTAX,
+  /**
+   * A required pointer argument was NULL.
+   */
+  XC_STATUS_NULL_POINTER,
+  /**
+   * A string argument was not valid UTF-8.
+   */
+  XC_STATUS_INVALID_UTF8,
+  /**
+   * An error this version of the C API does not know about.
+   */
+  XC_STATUS_OTHER,
+} XcStatus;
+
+/**
+ * A compiled expression; see [`xc_compile`].
+ */
+typedef struct XcProgram XcProgram;
+
+/**
+ * A calculator session with variables and user functions.
+ */
+typedef struct XcSession XcSession;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The message of the last error on this thread, or NULL if there was none.
+ *
+ * The string stays valid until the next failing `xc_` call on this thread.
+ */
+const char *xc_last_error(void);
+
+/**
+ * A static description of a status code.
+ */
+const char *xc_status_message(XcStatus status);
+
+/**
+ * `a + b`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_add(double a, double b, double *out);
+
+/**
+ * `a - b`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_sub(double a, double b, double *out);
+
+/**
+ * `a * b`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_mul(double a, double b, double *out);
+
+/**
+ * `a / b`; fails with `XC_STATUS_DIVISION_BY_ZERO`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_div(double a, double b, double *out);
+
+/**
+ * `a % b`; fails with `XC_STATUS_MODULO_BY_ZERO`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_mode(double a, double b, double *out);
+
+/**
+ * `a` raised to the power `b`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_pow(double a, double b, double *out);
+
+/**
+ * Square root; fails with `XC_STATUS_NEGATIVE_SQRT`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_sqrt(double a, double *out);
+
+/**
+ * Absolute value.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_abs(double a, double *out);
+
+/**
+ * Factorial of a non-negative number up to 170; non-integers are
+ * `gamma(a + 1)`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_fact(double a, double *out);
+
+/**
+ * The gamma function.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_gamma(double a, double *out);
+
+/**
+ * The natural logarithm of `|gamma(a)|`.
+ *
+ * # Safety
+ *
+ * `out` must be NULL or valid for writes.
+ */
+XcStatus xc_lgamma(double a, double *out);
+
+/**
+ * Evaluates an infix expression such as `"sqrt(9) + 2^3"`.
+ *
+ * # Safety
+ *
+ * `expr` must be NULL or a NUL-terminated string, and `out` must be NULL or
+ * valid for writes.
+ */
+XcStatus xc_eval(const char *expr, double *out);
+
+/**
+ * Compiles an expression whose identifiers become inputs in order of first
+ * appearance. Free the program with [`xc_program_free`].
+ *
+ * # Safety
+ *
+ * `expr` must be NULL or a NUL-terminated string, and `out` must be NULL or
+ * valid for writes.
+ */
+XcStatus xc_compile(const char *expr, XcProgram **out);
+
+/**
+ * The number of inputs `program` expects, or 0 if it is NULL.
+ *
+ * # Safety
+ *
+ * `program` must be NULL or returned by [`xc_compile`] and not yet freed.
+ */
+size_t xc_program_input_count(const XcProgram *program);
+
+/**
+ * Evaluates `program` with `len` inputs, without allocating.
+ *
+ * # Safety
+ *
+ * `program` must be NULL or a live program from [`xc_compile`], `inputs`
+ * must be valid for `len` reads (or NULL when `len` is 0), and `out` must be
+ * NULL or valid for writes.
+ */
+XcStatus xc_program_eval(const XcProgram *program, const double *inputs, size_t len, double *out);
+
+/**
+ * Frees a program from [`xc_compile`]; NULL is ignored.
+ *
+ * # Safety
+ *
+ * `program` must be NULL or a live program from [`xc_compile`].
+ */
+void xc_program_free(XcProgram *program);
+
+/**
+ * Creates an empty session. Free it with [`xc_session_free`].
+ */
+XcSession *xc_session_new(void);
+
+/**
+ * Runs one line such as `"x = 3"`, `"f(x) = x^2"` or `"f(x) + 1"`.
+ *
+ * Function definitions produce no value, so `out` is set to NaN for them.
+ *
+ * # Safety
+ *
+ * `session` must be NULL or a live session from [`xc_session_new`], `line`
+ * must be NULL or a NUL-terminated string, and `out` must be NULL or valid
+ * for writes.
+ */
+XcStatus xc_session_eval(XcSession *session, const char *line, double *out);
+
+/**
+ * Frees a session from [`xc_session_new`]; NULL is ignored.
+ *
+ * # Safety
+ *
+ * `session` must be NULL or a live session from [`xc_session_new`].
+ */
+void xc_session_free(XcSession *session);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* XRUST_CALCLIB_H */
@@ -0,0 +1,435 @@
+//! C ABI for [`xrust_calclib`].
+//!
+//! Every fallible function returns an [`XcStatus`] and writes its result
+//! through an out-parameter, which is left untouched on failure. The message
+//! for the most recent failure on the calling thread is available from
+//! [`xc_last_error`]. The header `include/xrust_calclib.h` is generated from
+//! this file by `build.rs`.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::fmt::Display;
+
+use xrust_calclib::{CalcError, EvalError, EvalErrorKind, Program, Session};
+
+/// Result code of every fallible `xc_` function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XcStatus {
+    Ok = 0,
+    DivisionByZero,
+    ModuloByZero,
+    NegativeSqrt,
+    NegativeFactorial,
+    NonIntegerFactorial,
+    Overflow,
+    Domain,
+    EmptyInput,
+    LengthMismatch,
+    SingularMatrix,
+    ShapeMismatch,
+    DimensionMismatch,
+    /// The expression could not be parsed or named an unknown function.
+    Syntax,
+    /// A required pointer argument was NULL.
+    NullPointer,
+    /// A string argument was not valid UTF-8.
+    InvalidUtf8,
+    /// An error this version of the C API does not know about.
+    Other,
+}
+
+impl From<CalcError> for XcStatus {
+    fn from(err: CalcError) -> Self {
+        match err {
+            CalcError::DivisionByZero => XcStatus::DivisionByZero,
+            CalcError::ModuloByZero => XcStatus::ModuloByZero,
+            CalcError::NegativeSqrt => XcStatus::NegativeSqrt,
+            CalcError::NegativeFactorial => XcStatus::NegativeFactorial,
+            CalcError::NonIntegerFactorial => XcStatus::NonIntegerFactorial,
+            CalcError::Overflow => XcStatus::Overflow,
+            CalcError::Domain(_) => XcStatus::Domain,
+            CalcError::EmptyInput => XcStatus::EmptyInput,
+            CalcError::LengthMismatch => XcStatus::LengthMismatch,
+            CalcError::SingularMatrix => XcStatus::SingularMatrix,
+            CalcError::ShapeMismatch => XcStatus::ShapeMismatch,
+            CalcError::DimensionMismatch => XcStatus::DimensionMismatch,
+            _ => XcStatus::Other,
+        }
+    }
+}
+
+impl From<&CalcError> for XcStatus {
+    fn from(err: &CalcError) -> Self {
+        (*err).into()
+    }
+}
+
+impl From<&EvalError> for XcStatus {
+    fn from(err: &EvalError) -> Self {
+        match err.kind {
+            EvalErrorKind::Syntax(_) => XcStatus::Syntax,
+            EvalErrorKind::Calc(err) => err.into(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records `err` as this thread's last error and returns its status.
+fn fail(status: XcStatus, err: impl Display) -> XcStatus {
+    // Messages never contain NUL, but fall back to an empty string if one does.
+    let message = CString::new(err.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+/// Writes `result` to `out`, or records the error.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+unsafe fn finish<E>(result: Result<f64, E>, out: *mut f64) -> XcStatus
+where
+    E: Display,
+    for<'a> &'a E: Into<XcStatus>,
+{
+    if out.is_null() {
+        return fail(XcStatus::NullPointer, "Output pointer is NULL");
+    }
+    match result {
+        Ok(value) => {
+            // SAFETY: the caller guarantees `out` is valid for writes.
+            unsafe { *out = value };
+            XcStatus::Ok
+        }
+        Err(err) => fail((&err).into(), err),
+    }
+}
+
+/// Borrows a C string as UTF-8.
+///
+/// # Safety
+///
+/// `s` must be NULL or point to a NUL-terminated string.
+unsafe fn text<'a>(s: *const c_char) -> Result<&'a str, XcStatus> {
+    if s.is_null() {
+        return Err(fail(XcStatus::NullPointer, "String argument is NULL"));
+    }
+    // SAFETY: the caller guarantees a NUL-terminated string.
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|err| fail(XcStatus::InvalidUtf8, err))
+}
+
+/// The message of the last error on this thread, or NULL if there was none.
+///
+/// The string stays valid until the next failing `xc_` call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn xc_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |m| m.as_ptr())
+    })
+}
+
+/// A static description of a status code.
+#[unsafe(no_mangle)]
+pub extern "C" fn xc_status_message(status: XcStatus) -> *const c_char {
+    let message: &'static CStr = match status {
+        XcStatus::Ok => c"OK",
+        XcStatus::DivisionByZero => c"Division by zero",
+        XcStatus::ModuloByZero => c"Modulo by zero",
+        XcStatus::NegativeSqrt => c"Square root of a negative number",
+        XcStatus::NegativeFactorial => c"Factorial of a negative number",
+        XcStatus::NonIntegerFactorial => c"Factorial of a non-integer",
+        XcStatus::Overflow => c"Overflow",
+        XcStatus::Domain => c"Argument outside the function's domain",
+        XcStatus::EmptyInput => c"Empty input",
+        XcStatus::LengthMismatch => c"Inputs have different lengths",
+        XcStatus::SingularMatrix => c"Matrix is singular",
+        XcStatus::ShapeMismatch => c"Matrix dimensions do not match",
+        XcStatus::DimensionMismatch => c"Units have incompatible dimensions",
+        XcStatus::Syntax => c"Syntax error",
+        XcStatus::NullPointer => c"NULL pointer argument",
+        XcStatus::InvalidUtf8 => c"String is not valid UTF-8",
+        XcStatus::Other => c"Unknown error",
+    };
+    message.as_ptr()
+}
+
+/// `a + b`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_add(a: f64, b: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(Ok::<_, CalcError>(xrust_calclib::add(a, b)), out) }
+}
+
+/// `a - b`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_sub(a: f64, b: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(Ok::<_, CalcError>(xrust_calclib::sub(a, b)), out) }
+}
+
+/// `a * b`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_mul(a: f64, b: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(Ok::<_, CalcError>(xrust_calclib::mul(a, b)), out) }
+}
+
+/// `a / b`; fails with `XC_STATUS_DIVISION_BY_ZERO`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_div(a: f64, b: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(xrust_calclib::div(a, b), out) }
+}
+
+/// `a % b`; fails with `XC_STATUS_MODULO_BY_ZERO`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_mode(a: f64, b: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(xrust_calclib::mode(a, b), out) }
+}
+
+/// `a` raised to the power `b`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_pow(a: f64, b: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(Ok::<_, CalcError>(xrust_calclib::pow(a, b)), out) }
+}
+
+/// Square root; fails with `XC_STATUS_NEGATIVE_SQRT`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_sqrt(a: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(xrust_calclib::sqrt(a), out) }
+}
+
+/// Absolute value.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_abs(a: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(Ok::<_, CalcError>(xrust_calclib::abs(a)), out) }
+}
+
+/// Factorial of a non-negative number up to 170; non-integers are
+/// `gamma(a + 1)`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_fact(a: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(xrust_calclib::fact(a), out) }
+}
+
+/// The gamma function.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_gamma(a: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(xrust_calclib::gamma(a), out) }
+}
+
+/// The natural logarithm of `|gamma(a)|`.
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_lgamma(a: f64, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(xrust_calclib::lgamma(a), out) }
+}
+
+/// Evaluates an infix expression such as `"sqrt(9) + 2^3"`.
+///
+/// # Safety
+///
+/// `expr` must be NULL or a NUL-terminated string, and `out` must be NULL or
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_eval(expr: *const c_char, out: *mut f64) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    match unsafe { text(expr) } {
+        Ok(src) => unsafe { finish(xrust_calclib::eval(src), out) },
+        Err(status) => status,
+    }
+}
+
+/// A compiled expression; see [`xc_compile`].
+pub struct XcProgram(Program);
+
+/// Compiles an expression whose identifiers become inputs in order of first
+/// appearance. Free the program with [`xc_program_free`].
+///
+/// # Safety
+///
+/// `expr` must be NULL or a NUL-terminated string, and `out` must be NULL or
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_compile(expr: *const c_char, out: *mut *mut XcProgram) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    let src = match unsafe { text(expr) } {
+        Ok(src) => src,
+        Err(status) => return status,
+    };
+    if out.is_null() {
+        return fail(XcStatus::NullPointer, "Output pointer is NULL");
+    }
+    match xrust_calclib::compile(src) {
+        Ok(program) => {
+            // SAFETY: checked non-NULL; the caller guarantees it is writable.
+            unsafe { *out = Box::into_raw(Box::new(XcProgram(program))) };
+            XcStatus::Ok
+        }
+        Err(err) => fail((&err).into(), err),
+    }
+}
+
+/// The number of inputs `program` expects, or 0 if it is NULL.
+///
+/// # Safety
+///
+/// `program` must be NULL or returned by [`xc_compile`] and not yet freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_program_input_count(program: *const XcProgram) -> usize {
+    // SAFETY: forwarded from the caller.
+    unsafe { program.as_ref() }.map_or(0, |p| p.0.inputs().len())
+}
+
+/// Evaluates `program` with `len` inputs, without allocating.
+///
+/// # Safety
+///
+/// `program` must be NULL or a live program from [`xc_compile`], `inputs`
+/// must be valid for `len` reads (or NULL when `len` is 0), and `out` must be
+/// NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_program_eval(
+    program: *const XcProgram,
+    inputs: *const f64,
+    len: usize,
+    out: *mut f64,
+) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    let Some(program) = (unsafe { program.as_ref() }) else {
+        return fail(XcStatus::NullPointer, "Program pointer is NULL");
+    };
+    let inputs = match (inputs.is_null(), len) {
+        (true, 0) => &[][..],
+        (true, _) => return fail(XcStatus::NullPointer, "Inputs pointer is NULL"),
+        // SAFETY: the caller guarantees `len` readable values.
+        (false, _) => unsafe { std::slice::from_raw_parts(inputs, len) },
+    };
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(program.0.eval(inputs), out) }
+}
+
+/// Frees a program from [`xc_compile`]; NULL is ignored.
+///
+/// # Safety
+///
+/// `program` must be NULL or a live program from [`xc_compile`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_program_free(program: *mut XcProgram) {
+    if !program.is_null() {
+        // SAFETY: the pointer came from `Box::into_raw` in `xc_compile`.
+        drop(unsafe { Box::from_raw(program) });
+    }
+}
+
+/// A calculator session with variables and user functions.
+pub struct XcSession(Session);
+
+/// Creates an empty session. Free it with [`xc_session_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn xc_session_new() -> *mut XcSession {
+    Box::into_raw(Box::new(XcSession(Session::new())))
+}
+
+/// Runs one line such as `"x = 3"`, `"f(x) = x^2"` or `"f(x) + 1"`.
+///
+/// Function definitions produce no value, so `out` is set to NaN for them.
+///
+/// # Safety
+///
+/// `session` must be NULL or a live session from [`xc_session_new`], `line`
+/// must be NULL or a NUL-terminated string, and `out` must be NULL or valid
+/// for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_session_eval(
+    session: *mut XcSession,
+    line: *const c_char,
+    out: *mut f64,
+) -> XcStatus {
+    // SAFETY: forwarded from the caller.
+    let Some(session) = (unsafe { session.as_mut() }) else {
+        return fail(XcStatus::NullPointer, "Session pointer is NULL");
+    };
+    // SAFETY: forwarded from the caller.
+    let line = match unsafe { text(line) } {
+        Ok(line) => line,
+        Err(status) => return status,
+    };
+    let result = session
+        .0
+        .eval(line)
+        .map(|outcome| outcome.value().unwrap_or(f64::NAN));
+    // SAFETY: forwarded from the caller.
+    unsafe { finish(result, out) }
+}
+
+/// Frees a session from [`xc_session_new`]; NULL is ignored.
+///
+/// # Safety
+///
+/// `session` must be NULL or a live session from [`xc_session_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xc_session_free(session: *mut XcSession) {
+    if !session.is_null() {
+        // SAFETY: the pointer came from `Box::into_raw` in `xc_session_new`.
+        drop(unsafe { Box::from_raw(session) });
+    }
+}
@@ -0,0 +1,139 @@
+/* Exercises every function in xrust_calclib.h; run by tests/c_api.rs. */
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "xrust_calclib.h"
+
+static int failures = 0;
+static int checks = 0;
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        checks++;                                                     \
+        if (!(cond)) {                                                \
+            failures++;                                               \
+            fprintf(stderr, "%s:%d: CHECK(%s) failed\n", __FILE__,    \
+                    __LINE__, #cond);                                 \
+        }                                                             \
+    } while (0)
+
+#define CHECK_VALUE(call, expected)                                   \
+    do {                                                              \
+        double out_ = NAN;                                            \
+        CHECK((call) == XC_STATUS_OK);                                \
+        CHECK(fabs(out_ - (expected)) < 1e-9);                        \
+    } while (0)
+
+static int last_error_contains(const char *needle) {
+    const char *message = xc_last_error();
+    return message != NULL && strstr(message, needle) != NULL;
+}
+
+static void test_arithmetic(void) {
+    CHECK_VALUE(xc_add(2, 3, &out_), 5);
+    CHECK_VALUE(xc_sub(2, 3, &out_), -1);
+    CHECK_VALUE(xc_mul(2, 3, &out_), 6);
+    CHECK_VALUE(xc_div(3, 2, &out_), 1.5);
+    CHECK_VALUE(xc_mode(7, 4, &out_), 3);
+    CHECK_VALUE(xc_pow(2, 10, &out_), 1024);
+    CHECK_VALUE(xc_sqrt(81, &out_), 9);
+    CHECK_VALUE(xc_abs(-4.5, &out_), 4.5);
+    CHECK_VALUE(xc_fact(5, &out_), 120);
+    CHECK_VALUE(xc_fact(0.5, &out_), sqrt(acos(-1.0)) / 2);
+    CHECK_VALUE(xc_gamma(5, &out_), 24);
+    CHECK_VALUE(xc_lgamma(5, &out_), log(24.0));
+}
+
+static void test_errors(void) {
+    double out = 42;
+    CHECK(xc_div(1, 0, &out) == XC_STATUS_DIVISION_BY_ZERO);
+    CHECK(out == 42); /* untouched on failure */
+    CHECK(last_error_contains("zero"));
+    CHECK(xc_mode(1, 0, &out) == XC_STATUS_MODULO_BY_ZERO);
+    CHECK(xc_sqrt(-1, &out) == XC_STATUS_NEGATIVE_SQRT);
+    CHECK(xc_fact(-1, &out) == XC_STATUS_NEGATIVE_FACTORIAL);
+    CHECK(xc_fact(NAN, &out) == XC_STATUS_NON_INTEGER_FACTORIAL);
+    CHECK(xc_fact(171, &out) == XC_STATUS_OVERFLOW);
+    CHECK(xc_gamma(-1, &out) != XC_STATUS_OK);
+    CHECK(xc_lgamma(0, &out) != XC_STATUS_OK);
+    CHECK(xc_add(1, 2, NULL) == XC_STATUS_NULL_POINTER);
+    CHECK(xc_sub(1, 2, NULL) == XC_STATUS_NULL_POINTER);
+    CHECK(xc_mul(1, 2, NULL) == XC_STATUS_NULL_POINTER);
+    CHECK(xc_pow(1, 2, NULL) == XC_STATUS_NULL_POINTER);
+    CHECK(xc_abs(1, NULL) == XC_STATUS_NULL_POINTER);
+    CHECK(last_error_contains("NULL"));
+
+    CHECK(strcmp(xc_status_message(XC_STATUS_OK), "OK") == 0);
+    CHECK(strcmp(xc_status_message(XC_STATUS_DIVISION_BY_ZERO), "Division by zero") == 0);
+    CHECK(strlen(xc_status_message(XC_STATUS_OTHER)) > 0);
+}
+
+static void test_eval(void) {
+    double out = 0;
+    CHECK_VALUE(xc_eval("sqrt(9) + 2^3 * fact(4)", &out_), 195);
+    CHECK_VALUE(xc_eval("sin(0) + 0x10", &out_), 16);
+    CHECK(xc_eval("1 + 2 / (3 - 3)", &out) == XC_STATUS_DIVISION_BY_ZERO);
+    CHECK(last_error_contains("column 7"));
+    CHECK(xc_eval("1 +", &out) == XC_STATUS_SYNTAX);
+    CHECK(xc_eval("\xff", &out) == XC_STATUS_INVALID_UTF8);
+    CHECK(xc_eval(NULL, &out) == XC_STATUS_NULL_POINTER);
+}
+
+static void test_program(void) {
+    XcProgram *program = NULL;
+    const double row[] = {4095, 3.3};
+    CHECK(xc_compile("raw * vref / 4095", &program) == XC_STATUS_OK);
+    CHECK(program != NULL);
+    CHECK(xc_program_input_count(program) == 2);
+    CHECK(xc_program_input_count(NULL) == 0);
+    CHECK_VALUE(xc_program_eval(program, row, 2, &out_), 3.3);
+
+    double out = 0;
+    CHECK(xc_program_eval(program, row, 1, &out) == XC_STATUS_LENGTH_MISMATCH);
+    CHECK(xc_program_eval(program, NULL, 2, &out) == XC_STATUS_NULL_POINTER);
+    CHECK(xc_program_eval(NULL, row, 2, &out) == XC_STATUS_NULL_POINTER);
+    xc_program_free(program);
+    xc_program_free(NULL);
+
+    CHECK(xc_compile("2 * (3 + 4)", &program) == XC_STATUS_OK);
+    CHECK_VALUE(xc_program_eval(program, NULL, 0, &out_), 14);
+    xc_program_free(program);
+
+    CHECK(xc_compile("x + 1/0", &program) == XC_STATUS_DIVISION_BY_ZERO);
+    CHECK(xc_compile("x +", &program) == XC_STATUS_SYNTAX);
+    CHECK(xc_compile("x", NULL) == XC_STATUS_NULL_POINTER);
+}
+
+static void test_session(void) {
+    XcSession *session = xc_session_new();
+    double out = 0;
+    CHECK(session != NULL);
+    CHECK_VALUE(xc_session_eval(session, "x = 3", &out_), 3);
+    CHECK(xc_session_eval(session, "f(a) = a^2 + x", &out) == XC_STATUS_OK);
+    CHECK(isnan(out));
+    CHECK_VALUE(xc_session_eval(session, "f(2) + ans", &out_), 7 + 3);
+    CHECK_VALUE(xc_session_eval(session, "pi", &out_), 3.14159265358979);
+    CHECK(xc_session_eval(session, "pi = 3", &out) == XC_STATUS_SYNTAX);
+    CHECK(last_error_contains("constant"));
+    CHECK(xc_session_eval(session, "y", &out) == XC_STATUS_SYNTAX);
+    CHECK(xc_session_eval(NULL, "1", &out) == XC_STATUS_NULL_POINTER);
+    CHECK(xc_session_eval(session, NULL, &out) == XC_STATUS_NULL_POINTER);
+    xc_session_free(session);
+    xc_session_free(NULL);
+}
+
+int main(void) {
+    CHECK(xc_last_error() == NULL);
+    test_arithmetic();
+    test_errors();
+    test_eval();
+    test_program();
+    test_session();
+    if (failures > 0) {
+        fprintf(stderr, "%d of %d checks failed\n", failures, checks);
+        return 1;
+    }
+    printf("all %d checks passed\n", checks);
+    return 0;
+}
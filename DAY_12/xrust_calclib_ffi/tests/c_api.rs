@@ -0,0 +1,65 @@
+//! Compiles `tests/c/test_xrust_calclib.c` against the generated header and
+//! the static library, then runs it, and checks that the committed header in
+//! `include/` is up to date.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `target/<profile>`, where cargo put the static library.
+fn artifact_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("test binary path");
+    // target/<profile>/deps/c_api-<hash>
+    exe.parent()
+        .and_then(Path::parent)
+        .expect("target dir")
+        .to_path_buf()
+}
+
+/// Where `build.rs` wrote the header for this build.
+fn generated_header_dir() -> &'static Path {
+    Path::new(env!("OUT_DIR"))
+}
+
+#[test]
+fn committed_header_is_current() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let generated = generated_header_dir().join("xrust_calclib.h");
+    let committed = manifest.join("include/xrust_calclib.h");
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("could not read {}: {}", path.display(), err))
+    };
+    assert!(
+        read(&generated) == read(&committed),
+        "include/xrust_calclib.h is stale; copy {} over it",
+        generated.display()
+    );
+}
+
+#[test]
+fn c_program_passes() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir = artifact_dir();
+    let lib = dir.join("libxrust_calclib_ffi.a");
+    assert!(lib.exists(), "{} was not built", lib.display());
+    let exe = Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_xrust_calclib");
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+    let status = Command::new(&compiler)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&exe)
+        .arg(manifest.join("tests/c/test_xrust_calclib.c"))
+        .arg("-I")
+        .arg(generated_header_dir())
+        .arg(&lib)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .unwrap_or_else(|err| panic!("could not run {}: {}", compiler, err));
+    assert!(status.success(), "compiling the C test program failed");
+
+    let output = Command::new(&exe).output().expect("run the C test program");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}{}", stdout, stderr);
+    assert!(stdout.starts_with("all "), "{}", stdout);
+}
@@ -0,0 +1,2447 @@
+//! Python bindings for [`xrust_calclib`], imported as `xrust_calclib`.
+//!
+//! Every public function is exposed under its Rust name. The crate root's
+//! arithmetic functions, the expression evaluators, `Program`, `Session` and
+//! `History` are at the top level; each module is a submodule such as
+//! `xrust_calclib.stats` or `xrust_calclib.linalg`. Rust types become Python
+//! classes (`Word`, `Quantity`, `Interval`, `Format`, ...) or Python's own
+//! `complex`, `fractions.Fraction` and lists of rows for matrices, and
+//! enum arguments such as an [`AngleMode`] are passed as strings; the
+//! functions of [`numeric`](xrust_calclib::numeric), generic over the number
+//! type, are the top-level ones at `float`. `add`, `mul` and `sqrt` also accept NumPy `float64` arrays, broadcasting like NumPy
+//! does, and return a new array. Errors are raised as subclasses of
+//! `CalcError`, one per [`xrust_calclib::CalcError`] variant plus
+//! `ParseError`; errors from the expression evaluator carry the 1-based
+//! `column` attribute and errors from `batch` the 0-based `index` of the
+//! failing element.
+
+use std::collections::BTreeMap;
+
+use numpy::ndarray::{ArrayD, ArrayViewD, Zip, aview0};
+use numpy::{IntoPyArray, PyReadonlyArrayDyn};
+use pyo3::create_exception;
+use pyo3::exceptions::PyArithmeticError;
+use pyo3::prelude::*;
+use pyo3::types::{PyComplex, PyFloat};
+
+use xrust_calclib::{AngleMode, EvalError, EvalErrorKind};
+
+create_exception!(
+    xrust_calclib,
+    CalcError,
+    PyArithmeticError,
+    "Base class of every calculator error."
+);
+create_exception!(xrust_calclib, DivisionByZero, CalcError, "Division by zero.");
+create_exception!(xrust_calclib, ModuloByZero, CalcError, "Modulo by zero.");
+create_exception!(
+    xrust_calclib,
+    NegativeSqrt,
+    CalcError,
+    "Square root of a negative number."
+);
+create_exception!(
+    xrust_calclib,
+    NegativeFactorial,
+    CalcError,
+    "Factorial of a negative number."
+);
+create_exception!(
+    xrust_calclib,
+    NonIntegerFactorial,
+    CalcError,
+    "Factorial of a value with a fractional part."
+);
+create_exception!(
+    xrust_calclib,
+    Overflow,
+    CalcError,
+    "The result is too large to represent."
+);
+create_exception!(
+    xrust_calclib,
+    Domain,
+    CalcError,
+    "An argument lies outside the domain of the function."
+);
+create_exception!(xrust_calclib, EmptyInput, CalcError, "No data was given.");
+create_exception!(
+    xrust_calclib,
+    LengthMismatch,
+    CalcError,
+    "Paired data sets have different lengths."
+);
+create_exception!(
+    xrust_calclib,
+    SingularMatrix,
+    CalcError,
+    "The matrix has no inverse."
+);
+create_exception!(
+    xrust_calclib,
+    ShapeMismatch,
+    CalcError,
+    "Matrix, vector or array shapes do not fit the operation."
+);
+create_exception!(
+    xrust_calclib,
+    DimensionMismatch,
+    CalcError,
+    "Quantities with different physical dimensions were combined."
+);
+create_exception!(
+    xrust_calclib,
+    ParseError,
+    CalcError,
+    "The expression could not be parsed or named an unknown function."
+);
+
+/// The exception class for `err`, raised with `message`.
+fn calc_error_with(err: xrust_calclib::CalcError, message: String) -> PyErr {
+    use xrust_calclib::CalcError as E;
+    match err {
+        E::DivisionByZero => DivisionByZero::new_err(message),
+        E::ModuloByZero => ModuloByZero::new_err(message),
+        E::NegativeSqrt => NegativeSqrt::new_err(message),
+        E::NegativeFactorial => NegativeFactorial::new_err(message),
+        E::NonIntegerFactorial => NonIntegerFactorial::new_err(message),
+        E::Overflow => Overflow::new_err(message),
+        E::Domain(_) => Domain::new_err(message),
+        E::EmptyInput => EmptyInput::new_err(message),
+        E::LengthMismatch => LengthMismatch::new_err(message),
+        E::SingularMatrix => SingularMatrix::new_err(message),
+        E::ShapeMismatch => ShapeMismatch::new_err(message),
+        E::DimensionMismatch => DimensionMismatch::new_err(message),
+        _ => CalcError::new_err(message),
+    }
+}
+
+fn calc_error(err: xrust_calclib::CalcError) -> PyErr {
+    calc_error_with(err, err.to_string())
+}
+
+/// Parses `"rad"`, `"deg"`, `"grad"` or a full name.
+fn angle_mode(mode: &str) -> PyResult<AngleMode> {
+    mode.parse().map_err(calc_error)
+}
+
+/// Converts an evaluator error, setting `column` on the exception.
+fn eval_error(err: EvalError) -> PyErr {
+    let py_err = match err.kind {
+        EvalErrorKind::Syntax(_) => ParseError::new_err(err.to_string()),
+        EvalErrorKind::Calc(calc) => calc_error_with(calc, err.to_string()),
+    };
+    Python::attach(|py| {
+        // Setting an attribute on a fresh exception instance cannot fail.
+        let _ = py_err.value(py).setattr("column", err.column);
+    });
+    py_err
+}
+
+/// A float or a NumPy `float64` array.
+#[derive(FromPyObject)]
+enum Operand<'py> {
+    Scalar(f64),
+    Array(PyReadonlyArrayDyn<'py, f64>),
+}
+
+impl Operand<'_> {
+    fn view(&self) -> ArrayViewD<'_, f64> {
+        match self {
+            Operand::Scalar(x) => aview0(x).into_dyn(),
+            Operand::Array(array) => array.as_array(),
+        }
+    }
+}
+
+fn float(py: Python<'_>, x: f64) -> Bound<'_, PyAny> {
+    PyFloat::new(py, x).into_any()
+}
+
+fn array(py: Python<'_>, values: ArrayD<f64>) -> Bound<'_, PyAny> {
+    values.into_pyarray(py).into_any()
+}
+
+/// NumPy's broadcast shape for operands of shapes `a` and `b`, if any.
+fn broadcast_shape(a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let rank = a.len().max(b.len());
+    // Missing leading axes have length 1.
+    let axis = |shape: &[usize], i: usize| {
+        (i + shape.len())
+            .checked_sub(rank)
+            .map_or(1, |i| shape[i])
+    };
+    (0..rank)
+        .map(|i| match (axis(a, i), axis(b, i)) {
+            (m, n) if m == n => Some(m),
+            (1, n) => Some(n),
+            (m, 1) => Some(m),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Applies `f` to two operands, broadcasting arrays against each other.
+fn elementwise<'py>(
+    py: Python<'py>,
+    a: Operand<'py>,
+    b: Operand<'py>,
+    f: fn(f64, f64) -> f64,
+) -> PyResult<Bound<'py, PyAny>> {
+    if let (Operand::Scalar(a), Operand::Scalar(b)) = (&a, &b) {
+        return Ok(float(py, f(*a, *b)));
+    }
+    let (a, b) = (a.view(), b.view());
+    let shape = broadcast_shape(a.shape(), b.shape()).ok_or_else(|| {
+        let message = format!(
+            "Array shapes {:?} and {:?} cannot be broadcast together",
+            a.shape(),
+            b.shape()
+        );
+        calc_error_with(xrust_calclib::CalcError::ShapeMismatch, message)
+    })?;
+    let x = a.broadcast(shape.clone()).expect("compatible shape");
+    let y = b.broadcast(shape).expect("compatible shape");
+    let values = Zip::from(&x).and(&y).map_collect(|&x, &y| f(x, y));
+    Ok(array(py, values))
+}
+
+/// `a + b`, element-wise for arrays.
+#[pyfunction]
+fn add<'py>(py: Python<'py>, a: Operand<'py>, b: Operand<'py>) -> PyResult<Bound<'py, PyAny>> {
+    elementwise(py, a, b, xrust_calclib::add)
+}
+
+/// `a - b`.
+#[pyfunction]
+fn sub(a: f64, b: f64) -> f64 {
+    xrust_calclib::sub(a, b)
+}
+
+/// `a * b`, element-wise for arrays.
+#[pyfunction]
+fn mul<'py>(py: Python<'py>, a: Operand<'py>, b: Operand<'py>) -> PyResult<Bound<'py, PyAny>> {
+    elementwise(py, a, b, xrust_calclib::mul)
+}
+
+/// `a / b`; raises `DivisionByZero`.
+#[pyfunction]
+fn div(a: f64, b: f64) -> PyResult<f64> {
+    xrust_calclib::div(a, b).map_err(calc_error)
+}
+
+/// `a % b`; raises `ModuloByZero`.
+#[pyfunction]
+fn mode(a: f64, b: f64) -> PyResult<f64> {
+    xrust_calclib::mode(a, b).map_err(calc_error)
+}
+
+/// `a` raised to the power `b`.
+#[pyfunction]
+fn pow(a: f64, b: f64) -> f64 {
+    xrust_calclib::pow(a, b)
+}
+
+/// Square root, element-wise for arrays; raises `NegativeSqrt` if any value
+/// is negative.
+#[pyfunction]
+fn sqrt<'py>(py: Python<'py>, a: Operand<'py>) -> PyResult<Bound<'py, PyAny>> {
+    match a {
+        Operand::Scalar(a) => Ok(float(py, xrust_calclib::sqrt(a).map_err(calc_error)?)),
+        Operand::Array(a) => {
+            let a = a.as_array();
+            let values = a
+                .iter()
+                .map(|&x| xrust_calclib::sqrt(x))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(calc_error)?;
+            let values = ArrayD::from_shape_vec(a.raw_dim(), values)
+                .expect("one value per element, in logical order");
+            Ok(array(py, values))
+        }
+    }
+}
+
+/// Absolute value.
+#[pyfunction]
+fn abs(a: f64) -> f64 {
+    xrust_calclib::abs(a)
+}
+
+/// Factorial of a non-negative number up to 170; non-integers are
+/// `gamma(a + 1)`.
+#[pyfunction]
+fn fact(a: f64) -> PyResult<f64> {
+    xrust_calclib::fact(a).map_err(calc_error)
+}
+
+/// The exact factorial of `n` as a Python `int`.
+#[pyfunction]
+fn fact_big(n: u64) -> num_bigint::BigUint {
+    xrust_calclib::fact_big(n)
+}
+
+/// The gamma function.
+#[pyfunction]
+fn gamma(a: f64) -> PyResult<f64> {
+    xrust_calclib::gamma(a).map_err(calc_error)
+}
+
+/// The natural logarithm of `|gamma(a)|`.
+#[pyfunction]
+fn lgamma(a: f64) -> PyResult<f64> {
+    xrust_calclib::lgamma(a).map_err(calc_error)
+}
+
+/// Evaluates an infix expression such as `"sqrt(9) + 2^3"`.
+#[pyfunction]
+fn eval(src: &str) -> PyResult<f64> {
+    xrust_calclib::eval(src).map_err(eval_error)
+}
+
+/// Evaluates an expression exactly and returns a `fractions.Fraction`.
+#[pyfunction]
+fn eval_exact<'py>(py: Python<'py>, src: &str) -> PyResult<Bound<'py, PyAny>> {
+    let value = xrust_calclib::eval_exact(src).map_err(eval_error)?;
+    py.import("fractions")?
+        .getattr("Fraction")?
+        .call1((value.to_string(),))
+}
+
+/// Evaluates an expression over complex numbers and returns a `complex`.
+#[pyfunction]
+fn eval_complex<'py>(py: Python<'py>, src: &str) -> PyResult<Bound<'py, PyComplex>> {
+    let z = xrust_calclib::eval_complex(src).map_err(eval_error)?;
+    Ok(PyComplex::from_doubles(py, z.re, z.im))
+}
+
+/// Evaluates an expression with units such as `"3 ms + 200 us"` and returns
+/// a `units.Quantity`.
+#[pyfunction]
+fn eval_units(src: &str) -> PyResult<units::PyQuantity> {
+    xrust_calclib::eval_units(src)
+        .map(units::PyQuantity)
+        .map_err(eval_error)
+}
+
+/// An expression compiled once and evaluated many times.
+#[pyclass(name = "Program", module = "xrust_calclib", frozen)]
+struct PyProgram(xrust_calclib::Program);
+
+#[pymethods]
+impl PyProgram {
+    /// The input names, in the order `eval` expects them.
+    #[getter]
+    fn inputs(&self) -> Vec<String> {
+        self.0.inputs().to_vec()
+    }
+
+    /// Evaluates the program with `inputs[i]` bound to input `i`.
+    fn eval(&self, inputs: Vec<f64>) -> PyResult<f64> {
+        self.0.eval(&inputs).map_err(eval_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Program(inputs={:?})", self.0.inputs())
+    }
+}
+
+/// Compiles an expression whose identifiers become inputs in order of first
+/// appearance.
+#[pyfunction]
+fn compile(src: &str) -> PyResult<PyProgram> {
+    xrust_calclib::compile(src)
+        .map(PyProgram)
+        .map_err(eval_error)
+}
+
+/// A calculator with variables, `ans` and user functions.
+#[pyclass(name = "Session", module = "xrust_calclib")]
+#[derive(Default)]
+struct PySession(xrust_calclib::Session);
+
+#[pymethods]
+impl PySession {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs one line such as `"x = 3"`, `"f(x) = x^2"` or `"f(x) + 1"` and
+    /// returns its value, or `None` for a function definition.
+    fn eval(&mut self, line: &str) -> PyResult<Option<f64>> {
+        self.0
+            .eval(line)
+            .map(|outcome| outcome.value())
+            .map_err(eval_error)
+    }
+
+    /// The last value computed.
+    #[getter]
+    fn ans(&self) -> f64 {
+        self.0.ans()
+    }
+
+    /// The value of a variable, or `None` if it is not set.
+    fn variable(&self, name: &str) -> Option<f64> {
+        self.0.variable(name)
+    }
+
+    /// The angle unit for trig functions: `"radians"`, `"degrees"` or
+    /// `"gradians"`.
+    #[getter]
+    fn angle_mode(&self) -> String {
+        self.0.angle_mode().to_string()
+    }
+
+    /// Accepts `"rad"`, `"deg"`, `"grad"` or a full name.
+    #[setter]
+    fn set_angle_mode(&mut self, mode: &str) -> PyResult<()> {
+        self.0.set_angle_mode(angle_mode(mode)?);
+        Ok(())
+    }
+
+    /// Forgets all variables and functions.
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// One line recorded in a `History`.
+#[pyclass(name = "Entry", module = "xrust_calclib", frozen)]
+struct PyEntry(xrust_calclib::history::Entry);
+
+#[pymethods]
+impl PyEntry {
+    /// The line as typed.
+    #[getter]
+    fn expression(&self) -> &str {
+        &self.0.expression
+    }
+
+    /// The variables the line read, with their values before it ran.
+    #[getter]
+    fn operands(&self) -> BTreeMap<String, f64> {
+        self.0.operands.clone()
+    }
+
+    /// The value computed or assigned; `None` for definitions and errors.
+    #[getter]
+    fn value(&self) -> Option<f64> {
+        self.0.value
+    }
+
+    /// The error message, if the line failed.
+    #[getter]
+    fn error(&self) -> Option<&str> {
+        self.0.error.as_deref()
+    }
+
+    /// When the line ran, in milliseconds since the Unix epoch.
+    #[getter]
+    fn timestamp(&self) -> u64 {
+        self.0.timestamp
+    }
+
+    /// Whether the line failed.
+    fn is_error(&self) -> bool {
+        self.0.is_error()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Entry(expression={:?})", self.0.expression)
+    }
+}
+
+/// A log of the lines evaluated in a `Session`, oldest first.
+#[pyclass(name = "History", module = "xrust_calclib")]
+#[derive(Default)]
+struct PyHistory(xrust_calclib::History);
+
+#[pymethods]
+impl PyHistory {
+    /// An empty history that keeps at most `limit` entries, if given.
+    #[new]
+    #[pyo3(signature = (limit = None))]
+    fn new(limit: Option<usize>) -> Self {
+        match limit {
+            Some(limit) => PyHistory(xrust_calclib::History::with_limit(limit)),
+            None => Self::default(),
+        }
+    }
+
+    /// The entry limit, or `None` if unbounded.
+    #[getter]
+    fn limit(&self) -> Option<usize> {
+        self.0.limit()
+    }
+
+    /// Runs `line` in `session` like `Session.eval` and records it.
+    fn eval(&mut self, mut session: PyRefMut<'_, PySession>, line: &str) -> PyResult<Option<f64>> {
+        self.0
+            .eval(&mut session.0, line)
+            .map(|outcome| outcome.value())
+            .map_err(eval_error)
+    }
+
+    /// Like `eval`, recording `timestamp` (milliseconds since the Unix epoch).
+    fn eval_at(
+        &mut self,
+        mut session: PyRefMut<'_, PySession>,
+        line: &str,
+        timestamp: u64,
+    ) -> PyResult<Option<f64>> {
+        self.0
+            .eval_at(&mut session.0, line, timestamp)
+            .map(|outcome| outcome.value())
+            .map_err(eval_error)
+    }
+
+    /// Every entry, oldest first.
+    #[getter]
+    fn entries(&self) -> Vec<PyEntry> {
+        self.0.entries().iter().cloned().map(PyEntry).collect()
+    }
+
+    /// The newest entry, or `None` if the history is empty.
+    fn last(&self) -> Option<PyEntry> {
+        self.0.last().cloned().map(PyEntry)
+    }
+
+    /// The entries whose expression contains `text`, ignoring ASCII case.
+    fn search(&self, text: &str) -> Vec<PyEntry> {
+        self.0.search(text).cloned().map(PyEntry).collect()
+    }
+
+    /// Runs every recorded line again in `session` and returns the new
+    /// history.
+    fn replay(&self, mut session: PyRefMut<'_, PySession>) -> PyHistory {
+        PyHistory(self.0.replay(&mut session.0))
+    }
+
+    /// Forgets every entry.
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// The `xrust_calclib.stats` submodule.
+mod stats {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::stats;
+
+    /// The arithmetic mean.
+    #[pyfunction]
+    fn mean(data: Vec<f64>) -> PyResult<f64> {
+        stats::mean(&data).map_err(calc_error)
+    }
+
+    /// The population variance.
+    #[pyfunction]
+    fn variance_pop(data: Vec<f64>) -> PyResult<f64> {
+        stats::variance_pop(&data).map_err(calc_error)
+    }
+
+    /// The sample variance; needs two values.
+    #[pyfunction]
+    fn variance_sample(data: Vec<f64>) -> PyResult<f64> {
+        stats::variance_sample(&data).map_err(calc_error)
+    }
+
+    /// The population standard deviation.
+    #[pyfunction]
+    fn stddev_pop(data: Vec<f64>) -> PyResult<f64> {
+        stats::stddev_pop(&data).map_err(calc_error)
+    }
+
+    /// The sample standard deviation; needs two values.
+    #[pyfunction]
+    fn stddev_sample(data: Vec<f64>) -> PyResult<f64> {
+        stats::stddev_sample(&data).map_err(calc_error)
+    }
+
+    /// The smallest value.
+    #[pyfunction]
+    fn min(data: Vec<f64>) -> PyResult<f64> {
+        stats::min(&data).map_err(calc_error)
+    }
+
+    /// The largest value.
+    #[pyfunction]
+    fn max(data: Vec<f64>) -> PyResult<f64> {
+        stats::max(&data).map_err(calc_error)
+    }
+
+    /// The population covariance of paired data.
+    #[pyfunction]
+    fn covariance_pop(xs: Vec<f64>, ys: Vec<f64>) -> PyResult<f64> {
+        stats::covariance_pop(&xs, &ys).map_err(calc_error)
+    }
+
+    /// The sample covariance of paired data.
+    #[pyfunction]
+    fn covariance_sample(xs: Vec<f64>, ys: Vec<f64>) -> PyResult<f64> {
+        stats::covariance_sample(&xs, &ys).map_err(calc_error)
+    }
+
+    /// Pearson's correlation coefficient.
+    #[pyfunction]
+    fn correlation(xs: Vec<f64>, ys: Vec<f64>) -> PyResult<f64> {
+        stats::correlation(&xs, &ys).map_err(calc_error)
+    }
+
+    /// The middle value.
+    #[pyfunction]
+    fn median(data: Vec<f64>) -> PyResult<f64> {
+        stats::median(&data).map_err(calc_error)
+    }
+
+    /// The `p`-th percentile (`0..=100`), interpolating between ranks.
+    #[pyfunction]
+    fn percentile(data: Vec<f64>, p: f64) -> PyResult<f64> {
+        stats::percentile(&data, p).map_err(calc_error)
+    }
+
+    /// The most frequent value; ties go to the smallest value.
+    #[pyfunction]
+    fn mode(data: Vec<f64>) -> PyResult<f64> {
+        stats::mode(&data).map_err(calc_error)
+    }
+
+    /// A least-squares line `y = slope * x + intercept`.
+    #[pyclass(name = "Regression", module = "xrust_calclib.stats", frozen)]
+    struct PyRegression(stats::Regression);
+
+    #[pymethods]
+    impl PyRegression {
+        #[getter]
+        fn slope(&self) -> f64 {
+            self.0.slope
+        }
+
+        #[getter]
+        fn intercept(&self) -> f64 {
+            self.0.intercept
+        }
+
+        /// Coefficient of determination, `1.0` for a perfect fit.
+        #[getter]
+        fn r_squared(&self) -> f64 {
+            self.0.r_squared
+        }
+
+        /// Evaluates the fitted line at `x`.
+        fn predict(&self, x: f64) -> f64 {
+            self.0.predict(x)
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "Regression(slope={}, intercept={}, r_squared={})",
+                self.0.slope, self.0.intercept, self.0.r_squared
+            )
+        }
+    }
+
+    /// Ordinary least-squares fit of `ys` against `xs`.
+    #[pyfunction]
+    fn linear_regression(xs: Vec<f64>, ys: Vec<f64>) -> PyResult<PyRegression> {
+        stats::linear_regression(&xs, &ys)
+            .map(PyRegression)
+            .map_err(calc_error)
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "stats")?;
+        m.add_function(wrap_pyfunction!(mean, &m)?)?;
+        m.add_function(wrap_pyfunction!(variance_pop, &m)?)?;
+        m.add_function(wrap_pyfunction!(variance_sample, &m)?)?;
+        m.add_function(wrap_pyfunction!(stddev_pop, &m)?)?;
+        m.add_function(wrap_pyfunction!(stddev_sample, &m)?)?;
+        m.add_function(wrap_pyfunction!(min, &m)?)?;
+        m.add_function(wrap_pyfunction!(max, &m)?)?;
+        m.add_function(wrap_pyfunction!(covariance_pop, &m)?)?;
+        m.add_function(wrap_pyfunction!(covariance_sample, &m)?)?;
+        m.add_function(wrap_pyfunction!(correlation, &m)?)?;
+        m.add_function(wrap_pyfunction!(median, &m)?)?;
+        m.add_function(wrap_pyfunction!(percentile, &m)?)?;
+        m.add_function(wrap_pyfunction!(mode, &m)?)?;
+        m.add_function(wrap_pyfunction!(linear_regression, &m)?)?;
+        m.add_class::<PyRegression>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.scientific` submodule. The trigonometric functions take
+/// an optional angle `mode`, covering [`AngleMode`] as well.
+mod scientific {
+    use pyo3::prelude::*;
+
+    use super::{angle_mode, calc_error};
+    use xrust_calclib::CalcError;
+    use xrust_calclib::scientific::{self, Rounding, consts};
+
+    /// Sine of `angle`, in radians unless `mode` says otherwise.
+    #[pyfunction]
+    #[pyo3(signature = (angle, mode = "radians"))]
+    fn sin(angle: f64, mode: &str) -> PyResult<f64> {
+        Ok(angle_mode(mode)?.sin(angle))
+    }
+
+    /// Cosine of `angle`.
+    #[pyfunction]
+    #[pyo3(signature = (angle, mode = "radians"))]
+    fn cos(angle: f64, mode: &str) -> PyResult<f64> {
+        Ok(angle_mode(mode)?.cos(angle))
+    }
+
+    /// Tangent of `angle`; raises `Domain` at its poles.
+    #[pyfunction]
+    #[pyo3(signature = (angle, mode = "radians"))]
+    fn tan(angle: f64, mode: &str) -> PyResult<f64> {
+        angle_mode(mode)?.tan(angle).map_err(calc_error)
+    }
+
+    /// Arcsine, as an angle in `mode`.
+    #[pyfunction]
+    #[pyo3(signature = (x, mode = "radians"))]
+    fn asin(x: f64, mode: &str) -> PyResult<f64> {
+        angle_mode(mode)?.asin(x).map_err(calc_error)
+    }
+
+    /// Arccosine, as an angle in `mode`.
+    #[pyfunction]
+    #[pyo3(signature = (x, mode = "radians"))]
+    fn acos(x: f64, mode: &str) -> PyResult<f64> {
+        angle_mode(mode)?.acos(x).map_err(calc_error)
+    }
+
+    /// Arctangent, as an angle in `mode`.
+    #[pyfunction]
+    #[pyo3(signature = (x, mode = "radians"))]
+    fn atan(x: f64, mode: &str) -> PyResult<f64> {
+        Ok(angle_mode(mode)?.atan(x))
+    }
+
+    /// Four-quadrant arctangent of `y / x`, as an angle in `mode`.
+    #[pyfunction]
+    #[pyo3(signature = (y, x, mode = "radians"))]
+    fn atan2(y: f64, x: f64, mode: &str) -> PyResult<f64> {
+        Ok(angle_mode(mode)?.atan2(y, x))
+    }
+
+    /// Converts an angle in `mode` to radians.
+    #[pyfunction]
+    fn to_radians(angle: f64, mode: &str) -> PyResult<f64> {
+        Ok(angle_mode(mode)?.to_radians(angle))
+    }
+
+    /// Converts an angle in radians to `mode`.
+    #[pyfunction]
+    fn from_radians(angle: f64, mode: &str) -> PyResult<f64> {
+        Ok(angle_mode(mode)?.from_radians(angle))
+    }
+
+    /// Hyperbolic sine; raises `Overflow` for large `x`.
+    #[pyfunction]
+    fn sinh(x: f64) -> PyResult<f64> {
+        scientific::sinh(x).map_err(calc_error)
+    }
+
+    /// Hyperbolic cosine; raises `Overflow` for large `x`.
+    #[pyfunction]
+    fn cosh(x: f64) -> PyResult<f64> {
+        scientific::cosh(x).map_err(calc_error)
+    }
+
+    /// Hyperbolic tangent.
+    #[pyfunction]
+    fn tanh(x: f64) -> f64 {
+        scientific::tanh(x)
+    }
+
+    /// Inverse hyperbolic sine.
+    #[pyfunction]
+    fn asinh(x: f64) -> f64 {
+        scientific::asinh(x)
+    }
+
+    /// Inverse hyperbolic cosine; needs `x >= 1`.
+    #[pyfunction]
+    fn acosh(x: f64) -> PyResult<f64> {
+        scientific::acosh(x).map_err(calc_error)
+    }
+
+    /// Inverse hyperbolic tangent; needs `-1 < x < 1`.
+    #[pyfunction]
+    fn atanh(x: f64) -> PyResult<f64> {
+        scientific::atanh(x).map_err(calc_error)
+    }
+
+    /// Natural logarithm; needs `x > 0`.
+    #[pyfunction]
+    fn ln(x: f64) -> PyResult<f64> {
+        scientific::ln(x).map_err(calc_error)
+    }
+
+    /// Base-10 logarithm; needs `x > 0`.
+    #[pyfunction]
+    fn log10(x: f64) -> PyResult<f64> {
+        scientific::log10(x).map_err(calc_error)
+    }
+
+    /// Logarithm of `x` to `base`.
+    #[pyfunction]
+    fn log_b(x: f64, base: f64) -> PyResult<f64> {
+        scientific::log_b(x, base).map_err(calc_error)
+    }
+
+    /// `e` raised to `x`; raises `Overflow` for large `x`.
+    #[pyfunction]
+    fn exp(x: f64) -> PyResult<f64> {
+        scientific::exp(x).map_err(calc_error)
+    }
+
+    fn rounding(mode: &str) -> PyResult<Rounding> {
+        Ok(match mode {
+            "half_away_from_zero" => Rounding::HalfAwayFromZero,
+            "half_even" => Rounding::HalfEven,
+            "floor" => Rounding::Floor,
+            "ceil" => Rounding::Ceil,
+            "toward_zero" => Rounding::TowardZero,
+            _ => {
+                return Err(calc_error(CalcError::Domain(
+                    "Rounding mode must be half_away_from_zero, half_even, floor, ceil or toward_zero",
+                )));
+            }
+        })
+    }
+
+    /// Rounds `x` to `decimals` places after the point. `mode` is
+    /// `"half_away_from_zero"`, `"half_even"`, `"floor"`, `"ceil"` or
+    /// `"toward_zero"`.
+    #[pyfunction]
+    #[pyo3(signature = (x, decimals = 0, mode = "half_away_from_zero"))]
+    fn round(x: f64, decimals: i32, mode: &str) -> PyResult<f64> {
+        Ok(scientific::round(x, decimals, rounding(mode)?))
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "scientific")?;
+        m.add("E", consts::E)?;
+        m.add("PI", consts::PI)?;
+        m.add("TAU", consts::TAU)?;
+        m.add("LN_2", consts::LN_2)?;
+        m.add("LN_10", consts::LN_10)?;
+        m.add("SQRT_2", consts::SQRT_2)?;
+        m.add("PHI", consts::PHI)?;
+        m.add_function(wrap_pyfunction!(sin, &m)?)?;
+        m.add_function(wrap_pyfunction!(cos, &m)?)?;
+        m.add_function(wrap_pyfunction!(tan, &m)?)?;
+        m.add_function(wrap_pyfunction!(asin, &m)?)?;
+        m.add_function(wrap_pyfunction!(acos, &m)?)?;
+        m.add_function(wrap_pyfunction!(atan, &m)?)?;
+        m.add_function(wrap_pyfunction!(atan2, &m)?)?;
+        m.add_function(wrap_pyfunction!(to_radians, &m)?)?;
+        m.add_function(wrap_pyfunction!(from_radians, &m)?)?;
+        m.add_function(wrap_pyfunction!(sinh, &m)?)?;
+        m.add_function(wrap_pyfunction!(cosh, &m)?)?;
+        m.add_function(wrap_pyfunction!(tanh, &m)?)?;
+        m.add_function(wrap_pyfunction!(asinh, &m)?)?;
+        m.add_function(wrap_pyfunction!(acosh, &m)?)?;
+        m.add_function(wrap_pyfunction!(atanh, &m)?)?;
+        m.add_function(wrap_pyfunction!(ln, &m)?)?;
+        m.add_function(wrap_pyfunction!(log10, &m)?)?;
+        m.add_function(wrap_pyfunction!(log_b, &m)?)?;
+        m.add_function(wrap_pyfunction!(exp, &m)?)?;
+        m.add_function(wrap_pyfunction!(round, &m)?)?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.finance` submodule. Payment timing is `"end"` or
+/// `"begin"`.
+mod finance {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::CalcError;
+    use xrust_calclib::finance::{self, Timing};
+
+    fn timing(when: &str) -> PyResult<Timing> {
+        match when {
+            "end" => Ok(Timing::End),
+            "begin" => Ok(Timing::Begin),
+            _ => Err(calc_error(CalcError::Domain(
+                "Payment timing must be end or begin",
+            ))),
+        }
+    }
+
+    /// `principal` grown at `annual_rate` compounded `per_year` times a year
+    /// for `years` years.
+    #[pyfunction]
+    fn compound_interest(
+        principal: f64,
+        annual_rate: f64,
+        per_year: u32,
+        years: f64,
+    ) -> PyResult<f64> {
+        finance::compound_interest(principal, annual_rate, per_year, years).map_err(calc_error)
+    }
+
+    /// The future value of `pv` plus `pmt` each period.
+    #[pyfunction]
+    #[pyo3(signature = (rate, nper, pmt, pv, when = "end"))]
+    fn fv(rate: f64, nper: f64, pmt: f64, pv: f64, when: &str) -> PyResult<f64> {
+        finance::fv(rate, nper, pmt, pv, timing(when)?).map_err(calc_error)
+    }
+
+    /// The present value of `pmt` each period plus `fv` at the end.
+    #[pyfunction]
+    #[pyo3(signature = (rate, nper, pmt, fv, when = "end"))]
+    fn pv(rate: f64, nper: f64, pmt: f64, fv: f64, when: &str) -> PyResult<f64> {
+        finance::pv(rate, nper, pmt, fv, timing(when)?).map_err(calc_error)
+    }
+
+    /// The payment per period that turns `pv` into `fv`.
+    #[pyfunction]
+    #[pyo3(signature = (rate, nper, pv, fv, when = "end"))]
+    fn pmt(rate: f64, nper: f64, pv: f64, fv: f64, when: &str) -> PyResult<f64> {
+        finance::pmt(rate, nper, pv, fv, timing(when)?).map_err(calc_error)
+    }
+
+    /// The net present value of `cash_flows`, the first one a period from now.
+    #[pyfunction]
+    fn npv(rate: f64, cash_flows: Vec<f64>) -> PyResult<f64> {
+        finance::npv(rate, &cash_flows).map_err(calc_error)
+    }
+
+    /// The internal rate of return of `cash_flows`.
+    #[pyfunction]
+    fn irr(cash_flows: Vec<f64>) -> PyResult<f64> {
+        finance::irr(&cash_flows).map_err(calc_error)
+    }
+
+    /// One period of a loan amortization schedule.
+    #[pyclass(name = "AmortizationRow", module = "xrust_calclib.finance", frozen)]
+    struct PyAmortizationRow(finance::AmortizationRow);
+
+    #[pymethods]
+    impl PyAmortizationRow {
+        /// 1-based period number.
+        #[getter]
+        fn period(&self) -> u32 {
+            self.0.period
+        }
+
+        #[getter]
+        fn payment(&self) -> f64 {
+            self.0.payment
+        }
+
+        #[getter]
+        fn interest(&self) -> f64 {
+            self.0.interest
+        }
+
+        #[getter]
+        fn principal(&self) -> f64 {
+            self.0.principal
+        }
+
+        /// The balance remaining after this payment.
+        #[getter]
+        fn balance(&self) -> f64 {
+            self.0.balance
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "AmortizationRow(period={}, payment={}, interest={}, principal={}, balance={})",
+                self.0.period, self.0.payment, self.0.interest, self.0.principal, self.0.balance
+            )
+        }
+    }
+
+    /// The repayment schedule of a `principal` loan at `rate` per period over
+    /// `periods` end-of-period payments.
+    #[pyfunction]
+    fn amortization(principal: f64, rate: f64, periods: u32) -> PyResult<Vec<PyAmortizationRow>> {
+        let rows = finance::amortization(principal, rate, periods).map_err(calc_error)?;
+        Ok(rows.into_iter().map(PyAmortizationRow).collect())
+    }
+
+    /// Rounds half away from zero, treating decimal ties such as `1.005` as
+    /// ties.
+    #[pyfunction]
+    fn round_money(x: f64, decimals: u32) -> f64 {
+        finance::round_money(x, decimals)
+    }
+
+    /// Like `round_money` but ties go to the even digit.
+    #[pyfunction]
+    fn round_bankers(x: f64, decimals: u32) -> f64 {
+        finance::round_bankers(x, decimals)
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "finance")?;
+        m.add_function(wrap_pyfunction!(compound_interest, &m)?)?;
+        m.add_function(wrap_pyfunction!(fv, &m)?)?;
+        m.add_function(wrap_pyfunction!(pv, &m)?)?;
+        m.add_function(wrap_pyfunction!(pmt, &m)?)?;
+        m.add_function(wrap_pyfunction!(npv, &m)?)?;
+        m.add_function(wrap_pyfunction!(irr, &m)?)?;
+        m.add_function(wrap_pyfunction!(amortization, &m)?)?;
+        m.add_function(wrap_pyfunction!(round_money, &m)?)?;
+        m.add_function(wrap_pyfunction!(round_bankers, &m)?)?;
+        m.add_class::<PyAmortizationRow>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.programmer` submodule. Word sizes are given in bits.
+mod programmer {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::CalcError;
+    use xrust_calclib::programmer::{self, Word, WordSize};
+
+    fn word_size(bits: u32) -> PyResult<WordSize> {
+        match bits {
+            8 => Ok(WordSize::W8),
+            16 => Ok(WordSize::W16),
+            32 => Ok(WordSize::W32),
+            64 => Ok(WordSize::W64),
+            _ => Err(calc_error(CalcError::Domain(
+                "Word size must be 8, 16, 32 or 64 bits",
+            ))),
+        }
+    }
+
+    /// Parses a decimal, `0x`, `0b` or `0o` integer literal.
+    #[pyfunction]
+    fn parse_int(s: &str) -> PyResult<i128> {
+        programmer::parse_int(s).map_err(calc_error)
+    }
+
+    /// Like `parse_int`, but also accepts decimal fractions.
+    #[pyfunction]
+    fn parse_int_f64(s: &str) -> PyResult<f64> {
+        programmer::parse_int_f64(s).map_err(calc_error)
+    }
+
+    /// A fixed-width integer register, signed or unsigned.
+    ///
+    /// The bitwise operators keep the left operand's width and signedness.
+    /// `format(word, "x")` and the other integer presentation types `b`, `o`
+    /// and `X` show the two's-complement bit pattern; `int(word)` is the
+    /// value.
+    #[pyclass(name = "Word", module = "xrust_calclib.programmer", frozen, eq)]
+    #[derive(Clone, PartialEq)]
+    struct PyWord(Word);
+
+    #[pymethods]
+    impl PyWord {
+        /// Raises `Overflow` if `value` does not fit.
+        #[new]
+        #[pyo3(signature = (value, size = 64, signed = true))]
+        fn new(value: i128, size: u32, signed: bool) -> PyResult<Self> {
+            Word::new(value, word_size(size)?, signed)
+                .map(PyWord)
+                .map_err(calc_error)
+        }
+
+        /// Reads an integer literal into a word.
+        #[staticmethod]
+        #[pyo3(signature = (s, size = 64, signed = true))]
+        fn parse(s: &str, size: u32, signed: bool) -> PyResult<Self> {
+            Word::parse(s, word_size(size)?, signed)
+                .map(PyWord)
+                .map_err(calc_error)
+        }
+
+        /// A word holding the low `size` bits of `raw`.
+        #[staticmethod]
+        #[pyo3(signature = (raw, size = 64, signed = true))]
+        fn from_bits(raw: u64, size: u32, signed: bool) -> PyResult<Self> {
+            Ok(PyWord(Word::from_bits(raw, word_size(size)?, signed)))
+        }
+
+        /// The raw bit pattern.
+        fn to_bits(&self) -> u64 {
+            self.0.to_bits()
+        }
+
+        /// The value the bits stand for.
+        #[getter]
+        fn value(&self) -> i128 {
+            self.0.value()
+        }
+
+        /// The width in bits.
+        #[getter]
+        fn size(&self) -> u32 {
+            self.0.size().bits()
+        }
+
+        #[getter]
+        fn signed(&self) -> bool {
+            self.0.is_signed()
+        }
+
+        /// Converts like an `as` cast: widening sign-extends signed words,
+        /// narrowing truncates.
+        fn cast(&self, size: u32, signed: bool) -> PyResult<Self> {
+            Ok(PyWord(self.0.cast(word_size(size)?, signed)))
+        }
+
+        /// Shifts left, discarding bits shifted out; raises `Domain` for `n`
+        /// of the width or more.
+        fn shift_left(&self, n: u32) -> PyResult<Self> {
+            self.0.shift_left(n).map(PyWord).map_err(calc_error)
+        }
+
+        /// Shifts right, arithmetically for signed words.
+        fn shift_right(&self, n: u32) -> PyResult<Self> {
+            self.0.shift_right(n).map(PyWord).map_err(calc_error)
+        }
+
+        fn rotate_left(&self, n: u32) -> Self {
+            PyWord(self.0.rotate_left(n))
+        }
+
+        fn rotate_right(&self, n: u32) -> Self {
+            PyWord(self.0.rotate_right(n))
+        }
+
+        /// The full-width bit pattern in groups of four, e.g. `"1111 1110"`.
+        fn twos_complement(&self) -> String {
+            self.0.twos_complement().to_string()
+        }
+
+        fn __and__(&self, rhs: PyWord) -> Self {
+            PyWord(self.0 & rhs.0)
+        }
+
+        fn __or__(&self, rhs: PyWord) -> Self {
+            PyWord(self.0 | rhs.0)
+        }
+
+        fn __xor__(&self, rhs: PyWord) -> Self {
+            PyWord(self.0 ^ rhs.0)
+        }
+
+        fn __invert__(&self) -> Self {
+            PyWord(!self.0)
+        }
+
+        fn __lshift__(&self, n: u32) -> PyResult<Self> {
+            self.shift_left(n)
+        }
+
+        fn __rshift__(&self, n: u32) -> PyResult<Self> {
+            self.shift_right(n)
+        }
+
+        fn __int__(&self) -> i128 {
+            self.0.value()
+        }
+
+        fn __format__(&self, py: Python<'_>, spec: &str) -> PyResult<String> {
+            let format = py.import("builtins")?.getattr("format")?;
+            let formatted = if spec.ends_with(['b', 'o', 'x', 'X']) {
+                format.call1((self.0.to_bits(), spec))?
+            } else {
+                format.call1((self.0.value(), spec))?
+            };
+            formatted.extract()
+        }
+
+        fn __str__(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "Word({}, size={}, signed={})",
+                self.0.value(),
+                self.size(),
+                if self.0.is_signed() { "True" } else { "False" }
+            )
+        }
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "programmer")?;
+        m.add_function(wrap_pyfunction!(parse_int, &m)?)?;
+        m.add_function(wrap_pyfunction!(parse_int_f64, &m)?)?;
+        m.add_class::<PyWord>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.units` submodule.
+mod units {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::units::{self, Quantity, UNITS, Unit};
+
+    /// A unit from the conversion table: `si = value * scale + offset`.
+    #[pyclass(name = "Unit", module = "xrust_calclib.units", frozen)]
+    struct PyUnit(&'static Unit);
+
+    #[pymethods]
+    impl PyUnit {
+        #[getter]
+        fn symbol(&self) -> &'static str {
+            self.0.symbol
+        }
+
+        /// The dimension in SI base units, e.g. `"m/s^2"`.
+        #[getter]
+        fn dimension(&self) -> String {
+            self.0.dimension.to_string()
+        }
+
+        #[getter]
+        fn scale(&self) -> f64 {
+            self.0.scale
+        }
+
+        /// Non-zero only for temperature scales with a shifted zero point.
+        #[getter]
+        fn offset(&self) -> f64 {
+            self.0.offset
+        }
+
+        fn __repr__(&self) -> String {
+            format!("Unit({:?})", self.0.symbol)
+        }
+    }
+
+    /// Looks up a unit by its case-sensitive symbol.
+    #[pyfunction]
+    fn unit(symbol: &str) -> Option<PyUnit> {
+        units::unit(symbol).map(PyUnit)
+    }
+
+    /// A quantity or a plain number.
+    #[derive(FromPyObject)]
+    enum Operand {
+        Quantity(PyQuantity),
+        Number(f64),
+    }
+
+    impl Operand {
+        fn quantity(self) -> Quantity {
+            match self {
+                Operand::Quantity(q) => q.0,
+                Operand::Number(x) => Quantity::number(x),
+            }
+        }
+    }
+
+    /// A magnitude with a dimension. Arithmetic with quantities and plain
+    /// numbers checks dimensions and raises `DimensionMismatch`.
+    #[pyclass(name = "Quantity", module = "xrust_calclib.units", frozen, eq)]
+    #[derive(Clone, Copy, PartialEq)]
+    pub(crate) struct PyQuantity(pub(crate) Quantity);
+
+    #[pymethods]
+    impl PyQuantity {
+        /// `value` in the unit named `unit`.
+        #[new]
+        fn new(value: f64, unit: &str) -> PyResult<Self> {
+            Quantity::new(value, unit)
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        /// A plain number.
+        #[staticmethod]
+        fn number(value: f64) -> Self {
+            PyQuantity(Quantity::number(value))
+        }
+
+        /// The magnitude in the display unit, or in SI base units.
+        #[getter]
+        fn value(&self) -> f64 {
+            self.0.value()
+        }
+
+        /// The magnitude in SI base units (kelvin for temperatures).
+        #[getter]
+        fn si_value(&self) -> f64 {
+            self.0.si_value()
+        }
+
+        /// The display unit, if any.
+        #[getter]
+        fn unit(&self) -> Option<PyUnit> {
+            self.0.unit().map(PyUnit)
+        }
+
+        /// The dimension in SI base units; empty for plain numbers.
+        #[getter]
+        fn dimension(&self) -> String {
+            self.0.dimension().to_string()
+        }
+
+        /// The magnitude in the unit named `unit`.
+        fn value_in(&self, unit: &str) -> PyResult<f64> {
+            self.0.value_in(unit).map_err(calc_error)
+        }
+
+        /// Converts to the unit named `unit`, or to the reciprocal of an
+        /// inverse unit (`50 Hz` to `20 ms`).
+        fn to(&self, unit: &str) -> PyResult<Self> {
+            self.0.to(unit).map(PyQuantity).map_err(calc_error)
+        }
+
+        /// The square root; every exponent of the dimension must be even.
+        fn sqrt(&self) -> PyResult<Self> {
+            self.0.sqrt().map(PyQuantity).map_err(calc_error)
+        }
+
+        fn __add__(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .checked_add(rhs.quantity())
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __radd__(&self, lhs: Operand) -> PyResult<Self> {
+            lhs.quantity()
+                .checked_add(self.0)
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __sub__(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .checked_sub(rhs.quantity())
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __rsub__(&self, lhs: Operand) -> PyResult<Self> {
+            lhs.quantity()
+                .checked_sub(self.0)
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __mul__(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .checked_mul(rhs.quantity())
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __rmul__(&self, lhs: Operand) -> PyResult<Self> {
+            lhs.quantity()
+                .checked_mul(self.0)
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __truediv__(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .checked_div(rhs.quantity())
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __rtruediv__(&self, lhs: Operand) -> PyResult<Self> {
+            lhs.quantity()
+                .checked_div(self.0)
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __mod__(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .checked_rem(rhs.quantity())
+                .map(PyQuantity)
+                .map_err(calc_error)
+        }
+
+        fn __pow__(&self, n: i32, _modulo: Option<i32>) -> PyResult<Self> {
+            self.0.checked_powi(n).map(PyQuantity).map_err(calc_error)
+        }
+
+        fn __neg__(&self) -> Self {
+            PyQuantity(-self.0)
+        }
+
+        fn __str__(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn __repr__(&self) -> String {
+            format!("Quantity({:?})", self.0.to_string())
+        }
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "units")?;
+        let table: Vec<PyUnit> = UNITS.iter().map(PyUnit).collect();
+        m.add("UNITS", table)?;
+        m.add_function(wrap_pyfunction!(unit, &m)?)?;
+        m.add_class::<PyUnit>()?;
+        m.add_class::<PyQuantity>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.calculus` submodule. Every method takes a Python
+/// function of one float; `tolerance` and `max_iterations` default to the
+/// Rust `Options::default()`.
+mod calculus {
+    use std::cell::RefCell;
+
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::CalcError;
+    use xrust_calclib::calculus::{self, Estimate, MAX_REFINEMENTS, Options};
+
+    fn options(tolerance: Option<f64>, max_iterations: Option<usize>) -> Options {
+        let default = Options::default();
+        Options::new(
+            tolerance.unwrap_or(default.tolerance),
+            max_iterations.unwrap_or(default.max_iterations),
+        )
+    }
+
+    /// Calls Python functions from the Rust methods, keeping the first
+    /// exception they raise so it can be raised once the method returns.
+    #[derive(Default)]
+    struct Calls(RefCell<Option<PyErr>>);
+
+    impl Calls {
+        /// `f(x)`, or NaN once a call has failed.
+        fn call(&self, f: &Bound<'_, PyAny>, x: f64) -> f64 {
+            if self.0.borrow().is_some() {
+                return f64::NAN;
+            }
+            f.call1((x,))
+                .and_then(|y| y.extract())
+                .unwrap_or_else(|err| {
+                    *self.0.borrow_mut() = Some(err);
+                    f64::NAN
+                })
+        }
+
+        fn finish(self, result: Result<Estimate, CalcError>) -> PyResult<PyEstimate> {
+            match self.0.into_inner() {
+                Some(err) => Err(err),
+                None => result.map(PyEstimate).map_err(calc_error),
+            }
+        }
+    }
+
+    /// The result of a numerical method, with convergence diagnostics.
+    #[pyclass(name = "Estimate", module = "xrust_calclib.calculus", frozen)]
+    struct PyEstimate(Estimate);
+
+    #[pymethods]
+    impl PyEstimate {
+        /// The root, integral or derivative.
+        #[getter]
+        fn value(&self) -> f64 {
+            self.0.value
+        }
+
+        /// An estimate of the absolute error in `value`.
+        #[getter]
+        fn error(&self) -> f64 {
+            self.0.error
+        }
+
+        #[getter]
+        fn iterations(&self) -> usize {
+            self.0.iterations
+        }
+
+        /// Calls made to the function.
+        #[getter]
+        fn evaluations(&self) -> usize {
+            self.0.evaluations
+        }
+
+        /// Whether `error` met the requested tolerance.
+        #[getter]
+        fn converged(&self) -> bool {
+            self.0.converged
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "Estimate(value={}, error={}, converged={})",
+                self.0.value,
+                self.0.error,
+                if self.0.converged { "True" } else { "False" }
+            )
+        }
+    }
+
+    /// A root of `f` in `[a, b]` by repeated halving.
+    #[pyfunction]
+    #[pyo3(signature = (f, a, b, tolerance = None, max_iterations = None))]
+    fn bisection(
+        f: Bound<'_, PyAny>,
+        a: f64,
+        b: f64,
+        tolerance: Option<f64>,
+        max_iterations: Option<usize>,
+    ) -> PyResult<PyEstimate> {
+        let calls = Calls::default();
+        let opts = options(tolerance, max_iterations);
+        let result = calculus::bisection(|x| calls.call(&f, x), a, b, opts);
+        calls.finish(result)
+    }
+
+    /// A root of `f` near `x0` by Newton-Raphson steps with derivative `df`.
+    #[pyfunction]
+    #[pyo3(signature = (f, df, x0, tolerance = None, max_iterations = None))]
+    fn newton(
+        f: Bound<'_, PyAny>,
+        df: Bound<'_, PyAny>,
+        x0: f64,
+        tolerance: Option<f64>,
+        max_iterations: Option<usize>,
+    ) -> PyResult<PyEstimate> {
+        let calls = Calls::default();
+        let opts = options(tolerance, max_iterations);
+        let result = calculus::newton(|x| calls.call(&f, x), |x| calls.call(&df, x), x0, opts);
+        calls.finish(result)
+    }
+
+    /// A root of `f` in `[a, b]` by Brent's method.
+    #[pyfunction]
+    #[pyo3(signature = (f, a, b, tolerance = None, max_iterations = None))]
+    fn brent(
+        f: Bound<'_, PyAny>,
+        a: f64,
+        b: f64,
+        tolerance: Option<f64>,
+        max_iterations: Option<usize>,
+    ) -> PyResult<PyEstimate> {
+        let calls = Calls::default();
+        let opts = options(tolerance, max_iterations);
+        let result = calculus::brent(|x| calls.call(&f, x), a, b, opts);
+        calls.finish(result)
+    }
+
+    /// The integral of `f` over `[a, b]` by composite Simpson's rule.
+    #[pyfunction]
+    #[pyo3(signature = (f, a, b, tolerance = None, max_iterations = None))]
+    fn simpson(
+        f: Bound<'_, PyAny>,
+        a: f64,
+        b: f64,
+        tolerance: Option<f64>,
+        max_iterations: Option<usize>,
+    ) -> PyResult<PyEstimate> {
+        let calls = Calls::default();
+        let opts = options(tolerance, max_iterations);
+        let result = calculus::simpson(|x| calls.call(&f, x), a, b, opts);
+        calls.finish(result)
+    }
+
+    /// The integral of `f` over `[a, b]` by the composite 5-point
+    /// Gauss-Legendre rule.
+    #[pyfunction]
+    #[pyo3(signature = (f, a, b, tolerance = None, max_iterations = None))]
+    fn gauss_legendre(
+        f: Bound<'_, PyAny>,
+        a: f64,
+        b: f64,
+        tolerance: Option<f64>,
+        max_iterations: Option<usize>,
+    ) -> PyResult<PyEstimate> {
+        let calls = Calls::default();
+        let opts = options(tolerance, max_iterations);
+        let result = calculus::gauss_legendre(|x| calls.call(&f, x), a, b, opts);
+        calls.finish(result)
+    }
+
+    /// The first derivative of `f` at `x` by Ridders' method.
+    #[pyfunction]
+    #[pyo3(signature = (f, x, tolerance = None, max_iterations = None))]
+    fn derivative(
+        f: Bound<'_, PyAny>,
+        x: f64,
+        tolerance: Option<f64>,
+        max_iterations: Option<usize>,
+    ) -> PyResult<PyEstimate> {
+        let calls = Calls::default();
+        let opts = options(tolerance, max_iterations);
+        let result = calculus::derivative(|x| calls.call(&f, x), x, opts);
+        calls.finish(result)
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "calculus")?;
+        m.add("MAX_REFINEMENTS", MAX_REFINEMENTS)?;
+        m.add_function(wrap_pyfunction!(bisection, &m)?)?;
+        m.add_function(wrap_pyfunction!(newton, &m)?)?;
+        m.add_function(wrap_pyfunction!(brent, &m)?)?;
+        m.add_function(wrap_pyfunction!(simpson, &m)?)?;
+        m.add_function(wrap_pyfunction!(gauss_legendre, &m)?)?;
+        m.add_function(wrap_pyfunction!(derivative, &m)?)?;
+        m.add_class::<PyEstimate>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.linalg` submodule. Vectors are lists of floats and
+/// matrices are lists of equally long rows.
+mod linalg {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::linalg::{self, DLu, DMatrix};
+    use xrust_calclib::{CalcError, Vector};
+
+    fn matrix(rows: &[Vec<f64>]) -> PyResult<DMatrix> {
+        let rows: Vec<&[f64]> = rows.iter().map(Vec::as_slice).collect();
+        DMatrix::from_rows(&rows).map_err(calc_error)
+    }
+
+    fn rows(m: &DMatrix) -> Vec<Vec<f64>> {
+        let cols = m.cols();
+        (0..m.rows())
+            .map(|i| m.as_slice()[i * cols..(i + 1) * cols].to_vec())
+            .collect()
+    }
+
+    fn vector3(v: Vec<f64>) -> PyResult<Vector<3>> {
+        <[f64; 3]>::try_from(v)
+            .map(Vector::new)
+            .map_err(|_| calc_error(CalcError::ShapeMismatch))
+    }
+
+    /// The dot product of two equally long vectors.
+    #[pyfunction]
+    fn dot(a: Vec<f64>, b: Vec<f64>) -> PyResult<f64> {
+        linalg::dot(&a, &b).map_err(calc_error)
+    }
+
+    /// The Euclidean length of a vector.
+    #[pyfunction]
+    fn norm(v: Vec<f64>) -> PyResult<f64> {
+        let squared = linalg::dot(&v, &v).map_err(calc_error)?;
+        xrust_calclib::sqrt(squared).map_err(calc_error)
+    }
+
+    /// The cross product of two 3-vectors.
+    #[pyfunction]
+    fn cross(a: Vec<f64>, b: Vec<f64>) -> PyResult<Vec<f64>> {
+        Ok(vector3(a)?.cross(&vector3(b)?).0.to_vec())
+    }
+
+    /// The `n`×`n` identity matrix.
+    #[pyfunction]
+    fn identity(n: usize) -> Vec<Vec<f64>> {
+        rows(&DMatrix::identity(n))
+    }
+
+    /// A `rows`×`cols` matrix of zeros.
+    #[pyfunction]
+    fn zeros(rows: usize, cols: usize) -> Vec<Vec<f64>> {
+        self::rows(&DMatrix::zeros(rows, cols))
+    }
+
+    #[pyfunction]
+    fn transpose(a: Vec<Vec<f64>>) -> PyResult<Vec<Vec<f64>>> {
+        Ok(rows(&matrix(&a)?.transpose()))
+    }
+
+    /// The matrix product `a · b`.
+    #[pyfunction]
+    fn mul(a: Vec<Vec<f64>>, b: Vec<Vec<f64>>) -> PyResult<Vec<Vec<f64>>> {
+        let product = matrix(&a)?.mul(&matrix(&b)?).map_err(calc_error)?;
+        Ok(rows(&product))
+    }
+
+    /// The matrix-vector product `a · v`.
+    #[pyfunction]
+    fn mul_vec(a: Vec<Vec<f64>>, v: Vec<f64>) -> PyResult<Vec<f64>> {
+        matrix(&a)?.mul_vec(&v).map_err(calc_error)
+    }
+
+    /// The determinant of a square matrix.
+    #[pyfunction]
+    fn determinant(a: Vec<Vec<f64>>) -> PyResult<f64> {
+        matrix(&a)?.determinant().map_err(calc_error)
+    }
+
+    /// The inverse; raises `SingularMatrix`.
+    #[pyfunction]
+    fn inverse(a: Vec<Vec<f64>>) -> PyResult<Vec<Vec<f64>>> {
+        Ok(rows(&matrix(&a)?.inverse().map_err(calc_error)?))
+    }
+
+    /// Solves `a x = b`; raises `SingularMatrix`.
+    #[pyfunction]
+    fn solve(a: Vec<Vec<f64>>, b: Vec<f64>) -> PyResult<Vec<f64>> {
+        matrix(&a)?.solve(&b).map_err(calc_error)
+    }
+
+    /// An LU decomposition, reusable for several right-hand sides.
+    #[pyclass(name = "Lu", module = "xrust_calclib.linalg", frozen)]
+    struct PyLu(DLu);
+
+    #[pymethods]
+    impl PyLu {
+        /// Row `i` of `P·A` is row `permutation[i]` of `A`.
+        #[getter]
+        fn permutation(&self) -> Vec<usize> {
+            self.0.permutation().to_vec()
+        }
+
+        fn determinant(&self) -> f64 {
+            self.0.determinant()
+        }
+
+        /// Solves `A x = b`.
+        fn solve(&self, b: Vec<f64>) -> PyResult<Vec<f64>> {
+            self.0.solve(&b).map_err(calc_error)
+        }
+
+        fn inverse(&self) -> Vec<Vec<f64>> {
+            rows(&self.0.inverse())
+        }
+    }
+
+    /// Decomposes a square matrix; raises `SingularMatrix`.
+    #[pyfunction]
+    fn lu(a: Vec<Vec<f64>>) -> PyResult<PyLu> {
+        matrix(&a)?.lu().map(PyLu).map_err(calc_error)
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "linalg")?;
+        m.add_function(wrap_pyfunction!(dot, &m)?)?;
+        m.add_function(wrap_pyfunction!(norm, &m)?)?;
+        m.add_function(wrap_pyfunction!(cross, &m)?)?;
+        m.add_function(wrap_pyfunction!(identity, &m)?)?;
+        m.add_function(wrap_pyfunction!(zeros, &m)?)?;
+        m.add_function(wrap_pyfunction!(transpose, &m)?)?;
+        m.add_function(wrap_pyfunction!(mul, &m)?)?;
+        m.add_function(wrap_pyfunction!(mul_vec, &m)?)?;
+        m.add_function(wrap_pyfunction!(determinant, &m)?)?;
+        m.add_function(wrap_pyfunction!(inverse, &m)?)?;
+        m.add_function(wrap_pyfunction!(solve, &m)?)?;
+        m.add_function(wrap_pyfunction!(lu, &m)?)?;
+        m.add_class::<PyLu>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.complex` submodule, over Python's `complex`. Real
+/// arguments are accepted wherever a complex one is.
+mod complex {
+    use pyo3::prelude::*;
+    use pyo3::types::PyComplex;
+
+    use super::calc_error;
+    use xrust_calclib::Complex;
+    use xrust_calclib::complex;
+
+    /// A `complex`, or a real number.
+    #[derive(FromPyObject)]
+    enum Number<'py> {
+        Complex(Bound<'py, PyComplex>),
+        Real(f64),
+    }
+
+    impl Number<'_> {
+        fn value(&self) -> Complex {
+            match self {
+                Number::Complex(z) => Complex::new(z.real(), z.imag()),
+                Number::Real(x) => Complex::from(*x),
+            }
+        }
+    }
+
+    fn py_complex(py: Python<'_>, z: Complex) -> Bound<'_, PyComplex> {
+        PyComplex::from_doubles(py, z.re, z.im)
+    }
+
+    /// The complex number with modulus `r` and argument `theta`.
+    #[pyfunction]
+    fn from_polar(py: Python<'_>, r: f64, theta: f64) -> Bound<'_, PyComplex> {
+        py_complex(py, Complex::from_polar(r, theta))
+    }
+
+    /// `(abs(z), arg(z))`.
+    #[pyfunction]
+    fn to_polar(z: Number<'_>) -> (f64, f64) {
+        z.value().to_polar()
+    }
+
+    /// The modulus.
+    #[pyfunction]
+    fn abs(z: Number<'_>) -> f64 {
+        z.value().abs()
+    }
+
+    /// The argument in `(-pi, pi]`.
+    #[pyfunction]
+    fn arg(z: Number<'_>) -> f64 {
+        z.value().arg()
+    }
+
+    /// The complex conjugate.
+    #[pyfunction]
+    fn conj<'py>(py: Python<'py>, z: Number<'py>) -> Bound<'py, PyComplex> {
+        py_complex(py, z.value().conj())
+    }
+
+    /// Whether the imaginary part is zero.
+    #[pyfunction]
+    fn is_real(z: Number<'_>) -> bool {
+        z.value().is_real()
+    }
+
+    /// `z / w`; raises `DivisionByZero`.
+    #[pyfunction]
+    fn div<'py>(
+        py: Python<'py>,
+        z: Number<'py>,
+        w: Number<'py>,
+    ) -> PyResult<Bound<'py, PyComplex>> {
+        let q = z.value().checked_div(w.value()).map_err(calc_error)?;
+        Ok(py_complex(py, q))
+    }
+
+    /// The principal square root.
+    #[pyfunction]
+    fn sqrt<'py>(py: Python<'py>, z: Number<'py>) -> Bound<'py, PyComplex> {
+        py_complex(py, z.value().sqrt())
+    }
+
+    /// `e` raised to `z`.
+    #[pyfunction]
+    fn exp<'py>(py: Python<'py>, z: Number<'py>) -> Bound<'py, PyComplex> {
+        py_complex(py, z.value().exp())
+    }
+
+    /// The principal natural logarithm; raises `Domain` at zero.
+    #[pyfunction]
+    fn ln<'py>(py: Python<'py>, z: Number<'py>) -> PyResult<Bound<'py, PyComplex>> {
+        Ok(py_complex(py, z.value().ln().map_err(calc_error)?))
+    }
+
+    /// `z` raised to an integer power; raises `Overflow`.
+    #[pyfunction]
+    fn powi<'py>(py: Python<'py>, z: Number<'py>, n: i32) -> PyResult<Bound<'py, PyComplex>> {
+        Ok(py_complex(py, z.value().powi(n).map_err(calc_error)?))
+    }
+
+    /// `z` raised to `w` on the principal branch.
+    #[pyfunction]
+    fn pow<'py>(
+        py: Python<'py>,
+        z: Number<'py>,
+        w: Number<'py>,
+    ) -> PyResult<Bound<'py, PyComplex>> {
+        let power = z.value().pow(w.value()).map_err(calc_error)?;
+        Ok(py_complex(py, power))
+    }
+
+    /// Square root of a real number; negatives give an imaginary result.
+    #[pyfunction]
+    fn sqrt_real(py: Python<'_>, x: f64) -> Bound<'_, PyComplex> {
+        py_complex(py, complex::sqrt_real(x))
+    }
+
+    /// Natural log of a real number; negatives give `ln|x| + pi*1j`.
+    #[pyfunction]
+    fn ln_real(py: Python<'_>, x: f64) -> PyResult<Bound<'_, PyComplex>> {
+        Ok(py_complex(py, complex::ln_real(x).map_err(calc_error)?))
+    }
+
+    /// `a` raised to `b`, complex for a negative base and fractional `b`.
+    #[pyfunction]
+    fn pow_real(py: Python<'_>, a: f64, b: f64) -> PyResult<Bound<'_, PyComplex>> {
+        Ok(py_complex(py, complex::pow_real(a, b).map_err(calc_error)?))
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "complex")?;
+        m.add_function(wrap_pyfunction!(from_polar, &m)?)?;
+        m.add_function(wrap_pyfunction!(to_polar, &m)?)?;
+        m.add_function(wrap_pyfunction!(abs, &m)?)?;
+        m.add_function(wrap_pyfunction!(arg, &m)?)?;
+        m.add_function(wrap_pyfunction!(conj, &m)?)?;
+        m.add_function(wrap_pyfunction!(is_real, &m)?)?;
+        m.add_function(wrap_pyfunction!(div, &m)?)?;
+        m.add_function(wrap_pyfunction!(sqrt, &m)?)?;
+        m.add_function(wrap_pyfunction!(exp, &m)?)?;
+        m.add_function(wrap_pyfunction!(ln, &m)?)?;
+        m.add_function(wrap_pyfunction!(powi, &m)?)?;
+        m.add_function(wrap_pyfunction!(pow, &m)?)?;
+        m.add_function(wrap_pyfunction!(sqrt_real, &m)?)?;
+        m.add_function(wrap_pyfunction!(ln_real, &m)?)?;
+        m.add_function(wrap_pyfunction!(pow_real, &m)?)?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.format` submodule.
+mod format {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::CalcError;
+    use xrust_calclib::format::{Format, Locale, MAX_DIGITS};
+
+    /// How to print a number. The default prints 12 significant digits
+    /// without trailing zeros; the other notations are built with the static
+    /// methods and every method returns a new `Format`.
+    #[pyclass(name = "Format", module = "xrust_calclib.format", frozen, eq)]
+    #[derive(Clone, Copy, PartialEq, Default)]
+    struct PyFormat(Format);
+
+    #[pymethods]
+    impl PyFormat {
+        #[new]
+        fn new() -> Self {
+            Self::default()
+        }
+
+        /// `decimals` digits after the decimal point.
+        #[staticmethod]
+        fn fixed(decimals: usize) -> Self {
+            PyFormat(Format::fixed(decimals))
+        }
+
+        /// `digits` significant figures in positional form.
+        #[staticmethod]
+        fn significant(digits: usize) -> Self {
+            PyFormat(Format::significant(digits))
+        }
+
+        /// `digits` significant figures with an exponent, e.g. `1.23e4`.
+        #[staticmethod]
+        fn scientific(digits: usize) -> Self {
+            PyFormat(Format::scientific(digits))
+        }
+
+        /// Like `scientific`, with an exponent that is a multiple of three.
+        #[staticmethod]
+        fn engineering(digits: usize) -> Self {
+            PyFormat(Format::engineering(digits))
+        }
+
+        /// Engineering notation with an SI prefix, e.g. `12.3 k`.
+        #[staticmethod]
+        fn si(digits: usize) -> Self {
+            PyFormat(Format::si(digits))
+        }
+
+        /// Uses the separators for a language tag such as `"de-DE"` or a
+        /// POSIX locale name such as `"en_US.UTF-8"`.
+        fn locale(&self, tag: &str) -> PyResult<Self> {
+            let locale = Locale::from_tag(tag)
+                .ok_or_else(|| calc_error(CalcError::Domain("Unknown locale")))?;
+            Ok(PyFormat(self.0.locale(locale)))
+        }
+
+        /// Drops trailing zeros after the decimal point.
+        #[pyo3(signature = (trim = true))]
+        fn trim_zeros(&self, trim: bool) -> Self {
+            PyFormat(self.0.trim_zeros(trim))
+        }
+
+        /// `value` printed in this format.
+        fn display(&self, value: f64) -> String {
+            self.0.display(value).to_string()
+        }
+
+        /// Parses a number in any notation this format prints.
+        fn parse(&self, s: &str) -> PyResult<f64> {
+            self.0.parse(s).map_err(calc_error)
+        }
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "format")?;
+        m.add("MAX_DIGITS", MAX_DIGITS)?;
+        m.add_class::<PyFormat>()?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.interval` submodule.
+mod interval {
+    use pyo3::prelude::*;
+
+    use super::calc_error;
+    use xrust_calclib::Interval;
+
+    /// An interval or a plain number, which stands for a point.
+    #[derive(FromPyObject)]
+    enum Operand {
+        Interval(PyInterval),
+        Number(f64),
+    }
+
+    impl Operand {
+        fn interval(self) -> Interval {
+            match self {
+                Operand::Interval(i) => i.0,
+                Operand::Number(x) => Interval::point(x),
+            }
+        }
+    }
+
+    /// A closed range `[lo, hi]` guaranteed to contain the true value. The
+    /// arithmetic operators round the bounds outwards.
+    #[pyclass(name = "Interval", module = "xrust_calclib.interval", frozen, eq)]
+    #[derive(Clone, Copy, PartialEq)]
+    struct PyInterval(Interval);
+
+    #[pymethods]
+    impl PyInterval {
+        /// Raises `Domain` if `lo > hi` or either bound is NaN.
+        #[new]
+        fn new(lo: f64, hi: f64) -> PyResult<Self> {
+            Interval::new(lo, hi).map(PyInterval).map_err(calc_error)
+        }
+
+        /// The single exact value `x`.
+        #[staticmethod]
+        fn point(x: f64) -> Self {
+            PyInterval(Interval::point(x))
+        }
+
+        /// `value ± tolerance`.
+        #[staticmethod]
+        fn from_tolerance(value: f64, tolerance: f64) -> PyResult<Self> {
+            Interval::from_tolerance(value, tolerance)
+                .map(PyInterval)
+                .map_err(calc_error)
+        }
+
+        /// `value ± percent %`.
+        #[staticmethod]
+        fn from_percent(value: f64, percent: f64) -> PyResult<Self> {
+            Interval::from_percent(value, percent)
+                .map(PyInterval)
+                .map_err(calc_error)
+        }
+
+        #[getter]
+        fn lo(&self) -> f64 {
+            self.0.lo()
+        }
+
+        #[getter]
+        fn hi(&self) -> f64 {
+            self.0.hi()
+        }
+
+        #[getter]
+        fn mid(&self) -> f64 {
+            self.0.mid()
+        }
+
+        #[getter]
+        fn width(&self) -> f64 {
+            self.0.width()
+        }
+
+        #[getter]
+        fn radius(&self) -> f64 {
+            self.0.radius()
+        }
+
+        fn contains(&self, x: f64) -> bool {
+            self.0.contains(x)
+        }
+
+        fn is_bounded(&self) -> bool {
+            self.0.is_bounded()
+        }
+
+        /// The smallest interval containing both.
+        fn hull(&self, other: PyInterval) -> Self {
+            PyInterval(self.0.hull(&other.0))
+        }
+
+        /// `1 / self`; raises `DivisionByZero` if the interval contains zero.
+        fn recip(&self) -> PyResult<Self> {
+            self.0.recip().map(PyInterval).map_err(calc_error)
+        }
+
+        /// `self / rhs`, unbounded when `rhs` contains zero; raises only for
+        /// `[0, 0]`.
+        fn div_unbounded(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .div_unbounded(rhs.interval())
+                .map(PyInterval)
+                .map_err(calc_error)
+        }
+
+        fn sqrt(&self) -> PyResult<Self> {
+            self.0.sqrt().map(PyInterval).map_err(calc_error)
+        }
+
+        /// `self` raised to an integer power.
+        fn powi(&self, n: i32) -> PyResult<Self> {
+            self.0.powi(n).map(PyInterval).map_err(calc_error)
+        }
+
+        /// `self` raised to a real power; the base must be non-negative.
+        fn pow(&self, exp: f64) -> PyResult<Self> {
+            self.0.pow(exp).map(PyInterval).map_err(calc_error)
+        }
+
+        fn __add__(&self, rhs: Operand) -> Self {
+            PyInterval(self.0 + rhs.interval())
+        }
+
+        fn __radd__(&self, lhs: Operand) -> Self {
+            PyInterval(lhs.interval() + self.0)
+        }
+
+        fn __sub__(&self, rhs: Operand) -> Self {
+            PyInterval(self.0 - rhs.interval())
+        }
+
+        fn __rsub__(&self, lhs: Operand) -> Self {
+            PyInterval(lhs.interval() - self.0)
+        }
+
+        fn __mul__(&self, rhs: Operand) -> Self {
+            PyInterval(self.0 * rhs.interval())
+        }
+
+        fn __rmul__(&self, lhs: Operand) -> Self {
+            PyInterval(lhs.interval() * self.0)
+        }
+
+        /// Raises `DivisionByZero` if `rhs` contains zero.
+        fn __truediv__(&self, rhs: Operand) -> PyResult<Self> {
+            self.0
+                .checked_div(rhs.interval())
+                .map(PyInterval)
+                .map_err(calc_error)
+        }
+
+        fn __rtruediv__(&self, lhs: Operand) -> PyResult<Self> {
+            lhs.interval()
+                .checked_div(self.0)
+                .map(PyInterval)
+                .map_err(calc_error)
+        }
+
+        fn __neg__(&self) -> Self {
+            PyInterval(-self.0)
+        }
+
+        fn __contains__(&self, x: f64) -> bool {
+            self.0.contains(x)
+        }
+
+        fn __str__(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn __repr__(&self) -> String {
+            format!("Interval({}, {})", self.0.lo(), self.0.hi())
+        }
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "interval")?;
+        m.add_class::<PyInterval>()?;
+        m.add("ENTIRE", PyInterval(Interval::ENTIRE))?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.symbolic` submodule, over expressions given as text.
+/// Results are expressions printed back to infix, which `eval` and
+/// `compile` accept.
+mod symbolic {
+    use pyo3::prelude::*;
+
+    use super::{angle_mode, eval_error};
+    use xrust_calclib::expr::{Expr, parse};
+
+    fn expr(src: &str) -> PyResult<Expr> {
+        parse(src).map_err(eval_error)
+    }
+
+    /// The derivative of `src` with respect to `var`, with trig functions in
+    /// `mode`.
+    #[pyfunction]
+    #[pyo3(signature = (src, var, mode = "radians"))]
+    fn derivative(src: &str, var: &str, mode: &str) -> PyResult<String> {
+        let d = expr(src)?
+            .derivative_in(var, angle_mode(mode)?)
+            .map_err(eval_error)?;
+        Ok(d.to_string())
+    }
+
+    /// `src` with constants folded and identities such as `x * 1` removed.
+    #[pyfunction]
+    fn simplify(src: &str) -> PyResult<String> {
+        Ok(expr(src)?.simplify().to_string())
+    }
+
+    /// `src` as LaTeX.
+    #[pyfunction]
+    fn to_latex(src: &str) -> PyResult<String> {
+        Ok(expr(src)?.to_latex())
+    }
+
+    /// Whether `src` mentions the variable `var`.
+    #[pyfunction]
+    fn depends_on(src: &str, var: &str) -> PyResult<bool> {
+        Ok(expr(src)?.depends_on(var))
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "symbolic")?;
+        m.add_function(wrap_pyfunction!(derivative, &m)?)?;
+        m.add_function(wrap_pyfunction!(simplify, &m)?)?;
+        m.add_function(wrap_pyfunction!(to_latex, &m)?)?;
+        m.add_function(wrap_pyfunction!(depends_on, &m)?)?;
+        parent.add_submodule(&m)
+    }
+}
+
+/// The `xrust_calclib.batch` submodule, over one-dimensional `float64`
+/// arrays. Element-wise functions return a new array; a failing element
+/// raises its error with the 0-based `index` attribute.
+mod batch {
+    use std::borrow::Cow;
+
+    use numpy::{PyArray1, PyReadonlyArray1};
+    use pyo3::prelude::*;
+
+    use super::{calc_error, calc_error_with};
+    use xrust_calclib::BatchError;
+    use xrust_calclib::batch::{self, LANES};
+
+    /// Converts a batch error, setting `index` on the exception.
+    fn batch_error(err: BatchError) -> PyErr {
+        let py_err = calc_error_with(err.error, err.to_string());
+        Python::attach(|py| {
+            // Setting an attribute on a fresh exception instance cannot fail.
+            let _ = py_err.value(py).setattr("index", err.index);
+        });
+        py_err
+    }
+
+    /// The elements of `a`, copied only if the array is not contiguous.
+    fn values<'a>(a: &'a PyReadonlyArray1<'_, f64>) -> Cow<'a, [f64]> {
+        match a.as_slice() {
+            Ok(values) => Cow::Borrowed(values),
+            Err(_) => Cow::Owned(a.as_array().to_vec()),
+        }
+    }
+
+    /// Runs `f` into a new array as long as `a`.
+    fn collect<'py, E>(
+        py: Python<'py>,
+        a: &[f64],
+        f: impl FnOnce(&mut [f64]) -> Result<(), E>,
+        err: fn(E) -> PyErr,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let mut out = vec![0.0; a.len()];
+        f(&mut out).map_err(err)?;
+        Ok(PyArray1::from_vec(py, out))
+    }
+
+    /// `a + b`; the arrays must have the same length.
+    #[pyfunction]
+    fn add_slices<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+        b: PyReadonlyArray1<'py, f64>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let (a, b) = (values(&a), values(&b));
+        collect(py, &a, |out| batch::add_slices(&a, &b, out), calc_error)
+    }
+
+    /// `a - b`.
+    #[pyfunction]
+    fn sub_slices<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+        b: PyReadonlyArray1<'py, f64>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let (a, b) = (values(&a), values(&b));
+        collect(py, &a, |out| batch::sub_slices(&a, &b, out), calc_error)
+    }
+
+    /// `a * b`.
+    #[pyfunction]
+    fn mul_slices<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+        b: PyReadonlyArray1<'py, f64>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let (a, b) = (values(&a), values(&b));
+        collect(py, &a, |out| batch::mul_slices(&a, &b, out), calc_error)
+    }
+
+    /// `a / b`; raises `DivisionByZero` for the first zero divisor.
+    #[pyfunction]
+    fn div_slices<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+        b: PyReadonlyArray1<'py, f64>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let (a, b) = (values(&a), values(&b));
+        collect(py, &a, |out| batch::div_slices(&a, &b, out), batch_error)
+    }
+
+    /// `a + k`.
+    #[pyfunction]
+    fn add_scalar<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+        k: f64,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let a = values(&a);
+        collect(py, &a, |out| batch::add_scalar(&a, k, out), calc_error)
+    }
+
+    /// `a * k`.
+    #[pyfunction]
+    fn mul_scalar<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+        k: f64,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let a = values(&a);
+        collect(py, &a, |out| batch::mul_scalar(&a, k, out), calc_error)
+    }
+
+    /// The square root of each element; raises `NegativeSqrt` for the first
+    /// negative one.
+    #[pyfunction]
+    fn sqrt_slice<'py>(
+        py: Python<'py>,
+        a: PyReadonlyArray1<'py, f64>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let a = values(&a);
+        collect(py, &a, |out| batch::sqrt_slice(&a, out), batch_error)
+    }
+
+    /// The sum of the elements, added in `LANES` partial sums.
+    #[pyfunction]
+    fn sum(a: PyReadonlyArray1<'_, f64>) -> f64 {
+        batch::sum(&values(&a))
+    }
+
+    /// The dot product, grouped like `sum`.
+    #[pyfunction]
+    fn dot(a: PyReadonlyArray1<'_, f64>, b: PyReadonlyArray1<'_, f64>) -> PyResult<f64> {
+        batch::dot(&values(&a), &values(&b)).map_err(calc_error)
+    }
+
+    pub(crate) fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+        let m = PyModule::new(parent.py(), "batch")?;
+        m.add("LANES", LANES)?;
+        m.add_function(wrap_pyfunction!(add_slices, &m)?)?;
+        m.add_function(wrap_pyfunction!(sub_slices, &m)?)?;
+        m.add_function(wrap_pyfunction!(mul_slices, &m)?)?;
+        m.add_function(wrap_pyfunction!(div_slices, &m)?)?;
+        m.add_function(wrap_pyfunction!(add_scalar, &m)?)?;
+        m.add_function(wrap_pyfunction!(mul_scalar, &m)?)?;
+        m.add_function(wrap_pyfunction!(sqrt_slice, &m)?)?;
+        m.add_function(wrap_pyfunction!(sum, &m)?)?;
+        m.add_function(wrap_pyfunction!(dot, &m)?)?;
+        parent.add_submodule(&m)
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "xrust_calclib")]
+fn xrust_calclib_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("CalcError", py.get_type::<CalcError>())?;
+    m.add("DivisionByZero", py.get_type::<DivisionByZero>())?;
+    m.add("ModuloByZero", py.get_type::<ModuloByZero>())?;
+    m.add("NegativeSqrt", py.get_type::<NegativeSqrt>())?;
+    m.add("NegativeFactorial", py.get_type::<NegativeFactorial>())?;
+    m.add("NonIntegerFactorial", py.get_type::<NonIntegerFactorial>())?;
+    m.add("Overflow", py.get_type::<Overflow>())?;
+    m.add("Domain", py.get_type::<Domain>())?;
+    m.add("EmptyInput", py.get_type::<EmptyInput>())?;
+    m.add("LengthMismatch", py.get_type::<LengthMismatch>())?;
+    m.add("SingularMatrix", py.get_type::<SingularMatrix>())?;
+    m.add("ShapeMismatch", py.get_type::<ShapeMismatch>())?;
+    m.add("DimensionMismatch", py.get_type::<DimensionMismatch>())?;
+    m.add("ParseError", py.get_type::<ParseError>())?;
+
+    m.add_function(wrap_pyfunction!(add, m)?)?;
+    m.add_function(wrap_pyfunction!(sub, m)?)?;
+    m.add_function(wrap_pyfunction!(mul, m)?)?;
+    m.add_function(wrap_pyfunction!(div, m)?)?;
+    m.add_function(wrap_pyfunction!(mode, m)?)?;
+    m.add_function(wrap_pyfunction!(pow, m)?)?;
+    m.add_function(wrap_pyfunction!(sqrt, m)?)?;
+    m.add_function(wrap_pyfunction!(abs, m)?)?;
+    m.add_function(wrap_pyfunction!(fact, m)?)?;
+    m.add_function(wrap_pyfunction!(fact_big, m)?)?;
+    m.add_function(wrap_pyfunction!(gamma, m)?)?;
+    m.add_function(wrap_pyfunction!(lgamma, m)?)?;
+    m.add_function(wrap_pyfunction!(eval, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_exact, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_complex, m)?)?;
+    m.add_function(wrap_pyfunction!(eval_units, m)?)?;
+    m.add_function(wrap_pyfunction!(compile, m)?)?;
+    m.add_class::<PyProgram>()?;
+    m.add_class::<PySession>()?;
+    m.add_class::<PyHistory>()?;
+    m.add_class::<PyEntry>()?;
+    stats::register(m)?;
+    scientific::register(m)?;
+    finance::register(m)?;
+    programmer::register(m)?;
+    units::register(m)?;
+    calculus::register(m)?;
+    linalg::register(m)?;
+    complex::register(m)?;
+    format::register(m)?;
+    interval::register(m)?;
+    symbolic::register(m)?;
+    batch::register(m)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broadcast_shape() {
+        assert_eq!(broadcast_shape(&[3], &[3]), Some(vec![3]));
+        assert_eq!(broadcast_shape(&[], &[2, 3]), Some(vec![2, 3]));
+        assert_eq!(broadcast_shape(&[3, 1], &[4]), Some(vec![3, 4]));
+        assert_eq!(broadcast_shape(&[2, 1, 5], &[3, 1]), Some(vec![2, 3, 5]));
+        assert_eq!(broadcast_shape(&[3], &[4]), None);
+        assert_eq!(broadcast_shape(&[2, 3], &[3, 3]), None);
+    }
+}
@@ -0,0 +1,42 @@
+//! Loads the built extension module into `python3` and runs
+//! `tests/test_xrust_calclib.py`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `target/<profile>`, where cargo put the shared library.
+fn artifact_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("test binary path");
+    // target/<profile>/deps/python_api-<hash>
+    exe.parent()
+        .and_then(Path::parent)
+        .expect("target dir")
+        .to_path_buf()
+}
+
+#[test]
+fn python_tests_pass() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (built, module) = if cfg!(windows) {
+        ("xrust_calclib_py.dll", "xrust_calclib.pyd")
+    } else if cfg!(target_os = "macos") {
+        ("libxrust_calclib_py.dylib", "xrust_calclib.so")
+    } else {
+        ("libxrust_calclib_py.so", "xrust_calclib.so")
+    };
+    let lib = artifact_dir().join(built);
+    assert!(lib.exists(), "{} was not built", lib.display());
+    // Python imports the module by file name.
+    let site = Path::new(env!("CARGO_TARGET_TMPDIR")).join("python");
+    std::fs::create_dir_all(&site).expect("create module dir");
+    std::fs::copy(&lib, site.join(module)).expect("copy extension module");
+
+    let python = std::env::var("PYTHON").unwrap_or_else(|_| "python3".into());
+    let output = Command::new(&python)
+        .arg(manifest.join("tests/test_xrust_calclib.py"))
+        .env("PYTHONPATH", &site)
+        .output()
+        .unwrap_or_else(|err| panic!("could not run {}: {}", python, err));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+}
@@ -0,0 +1,459 @@
+"""Tests for the xrust_calclib extension module.
+
+Run with `cargo test`, which builds the module, or after `maturin develop`:
+
+    python -m unittest tests/test_xrust_calclib.py
+"""
+
+import math
+import unittest
+from fractions import Fraction
+
+import xrust_calclib as xc
+
+try:
+    import numpy as np
+except ImportError:
+    np = None
+
+
+class ScalarTest(unittest.TestCase):
+    def test_functions(self):
+        self.assertEqual(xc.add(2, 3), 5.0)
+        self.assertEqual(xc.sub(2, 3), -1.0)
+        self.assertEqual(xc.mul(2, 3), 6.0)
+        self.assertEqual(xc.div(3, 2), 1.5)
+        self.assertEqual(xc.mode(7, 4), 3.0)
+        self.assertEqual(xc.pow(2, 10), 1024.0)
+        self.assertEqual(xc.sqrt(9), 3.0)
+        self.assertEqual(xc.abs(-4.5), 4.5)
+        self.assertEqual(xc.fact(5), 120.0)
+        self.assertAlmostEqual(xc.fact(0.5), math.sqrt(math.pi) / 2)
+        self.assertEqual(xc.fact_big(25), math.factorial(25))
+        self.assertAlmostEqual(xc.gamma(0.5), math.sqrt(math.pi))
+        self.assertAlmostEqual(xc.lgamma(10), math.lgamma(10))
+
+    def test_errors(self):
+        with self.assertRaises(xc.DivisionByZero):
+            xc.div(1, 0)
+        with self.assertRaises(xc.ModuloByZero):
+            xc.mode(1, 0)
+        with self.assertRaises(xc.NegativeSqrt):
+            xc.sqrt(-1)
+        with self.assertRaises(xc.NegativeFactorial):
+            xc.fact(-1)
+        with self.assertRaises(xc.Overflow):
+            xc.fact(171)
+        # Every error is a CalcError, which is an ArithmeticError.
+        with self.assertRaises(xc.CalcError) as caught:
+            xc.div(1, 0)
+        self.assertIsInstance(caught.exception, ArithmeticError)
+        self.assertEqual(str(caught.exception), "Division by zero is not allowed")
+
+
+class ExpressionTest(unittest.TestCase):
+    def test_eval(self):
+        self.assertEqual(xc.eval("sqrt(9) + 2^3"), 11.0)
+        self.assertEqual(xc.eval_exact("1/3 + 1/6"), Fraction(1, 2))
+        self.assertEqual(xc.eval_complex("sqrt(-4) + 1"), complex(1, 2))
+
+    def test_error_columns(self):
+        with self.assertRaises(xc.DivisionByZero) as caught:
+            xc.eval("2 + 1 / 0")
+        self.assertEqual(caught.exception.column, 7)
+        with self.assertRaises(xc.ParseError) as caught:
+            xc.eval("1 +")
+        self.assertEqual(caught.exception.column, 4)
+        with self.assertRaises(xc.ParseError):
+            xc.eval("nope(1)")
+
+    def test_compile(self):
+        program = xc.compile("raw * vref / 4095")
+        self.assertEqual(program.inputs, ["raw", "vref"])
+        self.assertEqual(program.eval([4095, 3.3]), 3.3)
+        with self.assertRaises(xc.LengthMismatch):
+            program.eval([1])
+
+    def test_session(self):
+        session = xc.Session()
+        self.assertEqual(session.eval("x = 3"), 3.0)
+        self.assertIsNone(session.eval("f(t) = t^2 + x"))
+        self.assertEqual(session.eval("f(2) + ans"), 10.0)
+        self.assertEqual(session.ans, 10.0)
+        self.assertEqual(session.variable("x"), 3.0)
+        session.clear()
+        self.assertIsNone(session.variable("x"))
+
+    def test_session_angle_mode(self):
+        degrees = xc.Session()
+        degrees.angle_mode = "deg"
+        self.assertEqual(degrees.angle_mode, "degrees")
+        self.assertEqual(degrees.eval("sin(90)"), 1.0)
+        # Other sessions keep radians.
+        self.assertEqual(xc.Session().eval("sin(0)"), 0.0)
+        self.assertEqual(xc.Session().angle_mode, "radians")
+        with self.assertRaises(xc.Domain):
+            degrees.angle_mode = "turns"
+
+    def test_history(self):
+        session, history = xc.Session(), xc.History()
+        self.assertEqual(history.eval_at(session, "x = 4", 1000), 4.0)
+        self.assertEqual(history.eval(session, "sqrt(x) * 10"), 20.0)
+        with self.assertRaises(xc.DivisionByZero):
+            history.eval(session, "1 / 0")
+        self.assertEqual(len(history), 3)
+        self.assertEqual(history.entries[0].timestamp, 1000)
+        self.assertEqual(history.entries[1].operands, {"x": 4.0})
+        self.assertTrue(history.last().is_error())
+        self.assertEqual([e.expression for e in history.search("SQRT")], ["sqrt(x) * 10"])
+        restored = xc.Session()
+        history.replay(restored)
+        self.assertEqual(restored.variable("x"), 4.0)
+        self.assertEqual(xc.History(limit=1).limit, 1)
+
+    def test_units(self):
+        self.assertEqual(str(xc.eval_units("3 ms + 200 us")), "3.2 ms")
+        with self.assertRaises(xc.DimensionMismatch) as caught:
+            xc.eval_units("3 ms + 2 m")
+        self.assertEqual(caught.exception.column, 6)
+
+    def test_symbolic(self):
+        self.assertEqual(xc.symbolic.derivative("x^3 - 2*x - 5", "x"), "3 * x^2 - 2")
+        self.assertEqual(xc.symbolic.derivative("sin(x)", "x", "deg"),
+                         "0.017453292519943295 * cos(x)")
+        self.assertEqual(xc.symbolic.simplify("(x*1 + 0) * 2^3 - -y^1"), "8 * x + y")
+        self.assertEqual(xc.symbolic.to_latex("3*x^2 - 2"), "3 \\cdot x^{2} - 2")
+        self.assertTrue(xc.symbolic.depends_on("x + y", "y"))
+        with self.assertRaises(xc.ParseError):
+            xc.symbolic.simplify("1 +")
+
+
+class ScientificTest(unittest.TestCase):
+    def test_trig(self):
+        sci = xc.scientific
+        self.assertAlmostEqual(sci.sin(sci.PI / 6), 0.5)
+        self.assertEqual(sci.sin(90, "deg"), 1.0)
+        self.assertAlmostEqual(sci.atan2(1, -1, "degrees"), 135.0)
+        self.assertAlmostEqual(sci.to_radians(200, "grad"), math.pi)
+        self.assertAlmostEqual(sci.from_radians(math.pi, "deg"), 180.0)
+        with self.assertRaises(xc.Domain):
+            sci.tan(90, "deg")
+        with self.assertRaises(xc.Domain):
+            sci.asin(1.5)
+        with self.assertRaises(xc.Domain):
+            sci.sin(1, "turns")
+
+    def test_functions(self):
+        sci = xc.scientific
+        self.assertAlmostEqual(sci.ln(sci.E), 1.0)
+        self.assertEqual(sci.log10(1000), 3.0)
+        self.assertAlmostEqual(sci.log_b(8, 2), 3.0)
+        self.assertAlmostEqual(sci.asinh(sci.sinh(0.3)), 0.3)
+        self.assertAlmostEqual(sci.acosh(sci.cosh(2)), 2.0)
+        self.assertAlmostEqual(sci.atanh(sci.tanh(0.5)), 0.5)
+        with self.assertRaises(xc.Overflow):
+            sci.exp(1000)
+        self.assertEqual(sci.round(2.5), 3.0)
+        self.assertEqual(sci.round(2.5, mode="half_even"), 2.0)
+        self.assertEqual(sci.round(1.005, 2), 1.01)
+        self.assertEqual(sci.round(-1.25, 1, "toward_zero"), -1.2)
+        with self.assertRaises(xc.Domain):
+            sci.round(1, mode="up")
+
+
+class FinanceTest(unittest.TestCase):
+    def test_time_value(self):
+        fin = xc.finance
+        payment = fin.pmt(0.06 / 12, 360, 200_000, 0)
+        self.assertEqual(fin.round_money(payment, 2), -1199.10)
+        self.assertAlmostEqual(fin.pmt(0.06 / 12, 360, 200_000, 0, "begin"), payment / 1.005)
+        self.assertEqual(fin.fv(0, 10, -100, -1000), 2000.0)
+        self.assertAlmostEqual(fin.pv(0, 4, -250, 0), 1000.0)
+        self.assertAlmostEqual(fin.compound_interest(1000, 0.12, 12, 1), 1000 * 1.01 ** 12)
+        self.assertAlmostEqual(fin.irr([-100, 121]), 0.21)
+        self.assertAlmostEqual(fin.npv(0.21, [-100 / 1.21, 121 / 1.21]), 0.0)
+        self.assertEqual(fin.round_bankers(2.5, 0), 2.0)
+        with self.assertRaises(xc.Domain):
+            fin.fv(0.1, 1, 0, 0, "middle")
+
+    def test_amortization(self):
+        rows = xc.finance.amortization(1000, 0.01, 12)
+        self.assertEqual(len(rows), 12)
+        self.assertEqual(rows[0].period, 1)
+        self.assertAlmostEqual(rows[0].interest, 10.0)
+        self.assertEqual(rows[-1].balance, 0.0)
+        with self.assertRaises(xc.Domain):
+            xc.finance.amortization(-1, 0.01, 12)
+
+
+class ProgrammerTest(unittest.TestCase):
+    def test_words(self):
+        Word = xc.programmer.Word
+        w = Word.parse("0xFF", 8, True)
+        self.assertEqual(w.value, -1)
+        self.assertEqual(int(w), -1)
+        self.assertEqual(w.to_bits(), 0xFF)
+        self.assertEqual(format(w, "#04x"), "0xff")
+        self.assertEqual(f"{w}", "-1")
+        self.assertEqual(w.twos_complement(), "1111 1111")
+        one = Word(1, 8, False)
+        self.assertEqual(format((one << 7) | one.shift_left(1), "#04x"), "0x82")
+        self.assertEqual(Word(-16, 8).shift_right(2).value, -4)
+        self.assertEqual(Word(0b1000_0001, 8, False).rotate_left(1).value, 0b11)
+        self.assertEqual((~Word(0, 16, False)).value, 0xFFFF)
+        self.assertEqual(w.cast(16, False).value, 0xFFFF)
+        self.assertEqual(Word(0x1FF, 16).cast(8, False).value, 0xFF)
+        self.assertEqual(Word.from_bits(0x1FF, 8, False), Word(0xFF, 8, False))
+        self.assertEqual((w.size, w.signed), (8, True))
+        with self.assertRaises(xc.Overflow):
+            Word(256, 8, False)
+        with self.assertRaises(xc.Domain):
+            Word(1, 12)
+        with self.assertRaises(xc.Domain):
+            one << 8
+
+    def test_parse_int(self):
+        self.assertEqual(xc.programmer.parse_int("-0b1010_0000"), -160)
+        self.assertEqual(xc.programmer.parse_int(str(2 ** 100)), 2 ** 100)
+        self.assertEqual(xc.programmer.parse_int_f64("0x10"), 16.0)
+
+
+class UnitsTest(unittest.TestCase):
+    def test_quantities(self):
+        Quantity = xc.units.Quantity
+        pulse = Quantity(1.5, "ms")
+        period = Quantity(50, "Hz").to("ms")
+        self.assertEqual(str(period), "20 ms")
+        self.assertEqual(period.unit.symbol, "ms")
+        self.assertEqual((pulse / period).si_value, 0.075)
+        self.assertEqual(str(Quantity(3, "ms") + Quantity(200, "us")), "3.2 ms")
+        self.assertEqual(str(Quantity(20, "degC") + Quantity(5, "K")), "25 degC")
+        self.assertEqual((2 * Quantity(3, "m")).dimension, "m")
+        self.assertEqual((Quantity(3, "m") ** 2).sqrt(), Quantity(3, "m"))
+        self.assertEqual(Quantity(1, "km").value_in("m"), 1000.0)
+        self.assertEqual((-Quantity(1, "m")).value, -1.0)
+        with self.assertRaises(xc.DimensionMismatch):
+            Quantity(3, "ms") + Quantity(2, "m")
+        with self.assertRaises(xc.Domain):
+            Quantity(20, "degC") * 2
+        with self.assertRaises(xc.Domain):
+            Quantity(1, "furlong")
+
+    def test_table(self):
+        self.assertEqual(xc.units.unit("mV").scale, 1e-3)
+        self.assertEqual(xc.units.unit("degF").dimension, "K")
+        self.assertIsNone(xc.units.unit("Ms"))
+        self.assertIn("Hz", [u.symbol for u in xc.units.UNITS])
+
+
+class CalculusTest(unittest.TestCase):
+    def test_methods(self):
+        calc = xc.calculus
+        for method in (calc.bisection, calc.brent):
+            root = method(lambda x: x * x - 2, 0, 2)
+            self.assertTrue(root.converged)
+            self.assertAlmostEqual(root.value, math.sqrt(2))
+        root = calc.newton(lambda x: x * x - 2, lambda x: 2 * x, 1)
+        self.assertAlmostEqual(root.value, math.sqrt(2))
+        for method in (calc.simpson, calc.gauss_legendre):
+            self.assertAlmostEqual(method(math.sin, 0, math.pi).value, 2.0)
+        self.assertAlmostEqual(calc.derivative(math.exp, 1).value, math.e)
+        few = calc.brent(math.cos, 0, 3, tolerance=1e-300, max_iterations=2)
+        self.assertFalse(few.converged)
+        self.assertEqual(few.iterations, 2)
+        with self.assertRaises(xc.Domain):
+            calc.bisection(lambda x: x * x + 1, -1, 1)
+
+    def test_callback_errors(self):
+        with self.assertRaises(ZeroDivisionError):
+            xc.calculus.simpson(lambda x: 1 / (x - 1), 0, 2)
+        with self.assertRaises(TypeError):
+            xc.calculus.derivative(lambda x: "x", 0)
+
+
+class LinalgTest(unittest.TestCase):
+    def test_matrices(self):
+        la = xc.linalg
+        a = [[4, 3], [6, 3]]
+        self.assertAlmostEqual(la.determinant(a), -6.0)
+        self.assertEqual(la.mul(a, la.identity(2)), [[4.0, 3.0], [6.0, 3.0]])
+        self.assertEqual(la.transpose([[1, 2, 3]]), [[1.0], [2.0], [3.0]])
+        self.assertEqual(la.mul_vec(a, [1, 1]), [7.0, 9.0])
+        x = la.solve(a, [10, 12])
+        self.assertEqual(la.mul_vec(a, x), [10.0, 12.0])
+        inv = la.inverse(a)
+        for row, expected in zip(la.mul(a, inv), la.identity(2)):
+            for value, e in zip(row, expected):
+                self.assertAlmostEqual(value, e)
+        lu = la.lu(a)
+        self.assertEqual(lu.permutation, [1, 0])
+        self.assertAlmostEqual(lu.determinant(), -6.0)
+        self.assertEqual(lu.solve([10, 12]), x)
+        self.assertEqual(lu.inverse(), inv)
+        self.assertEqual(la.zeros(2, 0), [[], []])
+        with self.assertRaises(xc.SingularMatrix):
+            la.inverse([[1, 2], [2, 4]])
+        with self.assertRaises(xc.ShapeMismatch):
+            la.mul(a, [[1, 2, 3]])
+        with self.assertRaises(xc.ShapeMismatch):
+            la.determinant([[1, 2], [3]])
+
+    def test_vectors(self):
+        la = xc.linalg
+        self.assertEqual(la.dot([1, 2, 3], [4, 5, 6]), 32.0)
+        self.assertEqual(la.norm([3, 4]), 5.0)
+        self.assertEqual(la.cross([1, 0, 0], [0, 1, 0]), [0.0, 0.0, 1.0])
+        with self.assertRaises(xc.ShapeMismatch):
+            la.cross([1, 0], [0, 1])
+        with self.assertRaises(xc.ShapeMismatch):
+            la.dot([1], [1, 2])
+
+
+class ComplexTest(unittest.TestCase):
+    def test_functions(self):
+        cx = xc.complex
+        self.assertEqual(cx.sqrt_real(-4), 2j)
+        self.assertAlmostEqual(cx.ln_real(-1), complex(0, math.pi))
+        self.assertAlmostEqual(cx.pow_real(-8, 1 / 3), complex(1, math.sqrt(3)))
+        self.assertEqual(cx.sqrt(-4), 2j)
+        self.assertEqual(cx.powi(1 + 1j, 2), 2j)
+        self.assertAlmostEqual(cx.pow(1j, 1j), math.exp(-math.pi / 2))
+        self.assertAlmostEqual(cx.exp(complex(0, math.pi)), -1)
+        self.assertEqual(cx.div(2j, 1j), 2)
+        self.assertEqual(cx.conj(1 + 2j), 1 - 2j)
+        self.assertEqual(cx.abs(3 + 4j), 5.0)
+        self.assertEqual(cx.arg(-1), math.pi)
+        r, theta = cx.to_polar(1j)
+        self.assertAlmostEqual(cx.from_polar(r, theta), 1j)
+        self.assertTrue(cx.is_real(2))
+        with self.assertRaises(xc.DivisionByZero):
+            cx.div(1, 0j)
+        with self.assertRaises(xc.Domain):
+            cx.ln(0)
+        with self.assertRaises(xc.Overflow):
+            cx.powi(1e200 + 1e200j, 2)
+
+
+class FormatTest(unittest.TestCase):
+    def test_display(self):
+        Format = xc.format.Format
+        self.assertEqual(Format().display(2.3 + 2.4), "4.7")
+        self.assertEqual(Format.significant(3).display(4.699999999999999), "4.70")
+        self.assertEqual(Format.fixed(2).display(3.14159), "3.14")
+        self.assertEqual(Format.scientific(3).display(12345), "1.23e4")
+        self.assertEqual(Format.engineering(3).display(12345), "12.3e3")
+        self.assertEqual(Format.si(2).display(4700), "4.7 k")
+        self.assertEqual(Format.si(3).trim_zeros().display(220e-6), "220 µ")
+        de = Format.fixed(2).locale("de-DE")
+        self.assertEqual(de.display(1234567.891), "1.234.567,89")
+        self.assertEqual(de.parse("1.234.567,89"), 1234567.89)
+        self.assertEqual(Format.fixed(2), Format.fixed(2))
+        self.assertEqual(xc.format.MAX_DIGITS, 17)
+        with self.assertRaises(xc.Domain):
+            Format().locale("tlh")
+        with self.assertRaises(xc.Domain):
+            de.parse("1.5x")
+
+
+class IntervalTest(unittest.TestCase):
+    def test_arithmetic(self):
+        Interval = xc.interval.Interval
+        clock = Interval.from_percent(2_000_000, 1)
+        ticks = 0.8e-3 * clock
+        self.assertIn(1600, ticks)
+        self.assertTrue(ticks.lo < 1585 and ticks.hi > 1615)
+        a = Interval(1, 2)
+        self.assertTrue((a + 1).contains(2.5))
+        self.assertTrue((a - a).contains(0))
+        self.assertEqual((a.lo, a.hi, a.mid, a.width, a.radius), (1, 2, 1.5, 1, 0.5))
+        self.assertEqual(str(Interval.point(3)), "[3, 3]")
+        self.assertTrue(Interval.from_tolerance(5, 0.1).contains(5.1))
+        self.assertTrue((1 / a).contains(0.5))
+        self.assertTrue(a.sqrt().contains(math.sqrt(2)))
+        self.assertTrue(a.powi(2).contains(4))
+        self.assertTrue(a.pow(0.5).contains(1))
+        self.assertEqual(a.hull(Interval(3, 4)), Interval(1, 4))
+        self.assertEqual((-a).lo, -2)
+        self.assertFalse(a.div_unbounded(Interval(-1, 1)).is_bounded())
+        self.assertFalse(xc.interval.ENTIRE.is_bounded())
+        with self.assertRaises(xc.DivisionByZero):
+            a / Interval(-1, 1)
+        with self.assertRaises(xc.Domain):
+            Interval(2, 1)
+
+
+class StatsTest(unittest.TestCase):
+    def test_summaries(self):
+        data = [2, 4, 4, 4, 5, 5, 7, 9]
+        self.assertEqual(xc.stats.mean(data), 5.0)
+        self.assertEqual(xc.stats.stddev_pop(data), 2.0)
+        self.assertAlmostEqual(xc.stats.variance_sample(data), 32 / 7)
+        self.assertEqual((xc.stats.min(data), xc.stats.max(data)), (2.0, 9.0))
+        self.assertEqual(xc.stats.median(data), 4.5)
+        self.assertEqual(xc.stats.percentile(data, 100), 9.0)
+        self.assertEqual(xc.stats.mode(data), 4.0)
+        with self.assertRaises(xc.EmptyInput):
+            xc.stats.mean([])
+        with self.assertRaises(xc.Domain):
+            xc.stats.percentile(data, 101)
+
+    def test_paired(self):
+        xs, ys = [1, 2, 3, 4], [3, 5, 7, 9]
+        self.assertAlmostEqual(xc.stats.correlation(xs, ys), 1.0)
+        self.assertAlmostEqual(xc.stats.covariance_pop(xs, ys), 2.5)
+        self.assertAlmostEqual(xc.stats.covariance_sample(xs, ys), 10 / 3)
+        fit = xc.stats.linear_regression(xs, ys)
+        self.assertAlmostEqual(fit.slope, 2.0)
+        self.assertAlmostEqual(fit.intercept, 1.0)
+        self.assertAlmostEqual(fit.r_squared, 1.0)
+        self.assertAlmostEqual(fit.predict(10), 21.0)
+        with self.assertRaises(xc.LengthMismatch):
+            xc.stats.correlation(xs, ys[:3])
+
+
+@unittest.skipIf(np is None, "numpy is not installed")
+class ArrayTest(unittest.TestCase):
+    def test_vectorized(self):
+        a = np.array([1.0, 4.0, 9.0])
+        np.testing.assert_array_equal(xc.sqrt(a), [1.0, 2.0, 3.0])
+        np.testing.assert_array_equal(xc.add(a, 1.0), [2.0, 5.0, 10.0])
+        np.testing.assert_array_equal(xc.mul(2.0, a), [2.0, 8.0, 18.0])
+        np.testing.assert_array_equal(xc.add(a, a), [2.0, 8.0, 18.0])
+
+    def test_broadcasting(self):
+        column = np.arange(3.0).reshape(3, 1)
+        row = np.arange(4.0)
+        self.assertEqual(xc.mul(column, row).shape, (3, 4))
+        # Non-contiguous views are read in logical order.
+        np.testing.assert_array_equal(xc.sqrt(np.array([[0.0, 1.0], [4.0, 9.0]]).T),
+                                      [[0.0, 2.0], [1.0, 3.0]])
+        with self.assertRaises(xc.ShapeMismatch):
+            xc.add(np.zeros(3), np.zeros(4))
+
+    def test_negative_sqrt(self):
+        with self.assertRaises(xc.NegativeSqrt):
+            xc.sqrt(np.array([1.0, -1.0]))
+
+    def test_batch(self):
+        a, b = np.array([1.0, 4.0, 9.0]), np.array([10.0, 20.0, 30.0])
+        np.testing.assert_array_equal(xc.batch.add_slices(a, b), [11.0, 24.0, 39.0])
+        np.testing.assert_array_equal(xc.batch.sub_slices(b, a), [9.0, 16.0, 21.0])
+        np.testing.assert_array_equal(xc.batch.mul_slices(a, b), [10.0, 80.0, 270.0])
+        np.testing.assert_array_equal(xc.batch.div_slices(b, a), [10.0, 5.0, 30.0 / 9])
+        np.testing.assert_array_equal(xc.batch.add_scalar(a, 1), [2.0, 5.0, 10.0])
+        np.testing.assert_array_equal(xc.batch.mul_scalar(a, 2), [2.0, 8.0, 18.0])
+        np.testing.assert_array_equal(xc.batch.sqrt_slice(a[::2]), [1.0, 3.0])
+        self.assertEqual(xc.batch.sum(a), 14.0)
+        self.assertEqual(xc.batch.dot(a, b), 360.0)
+        with self.assertRaises(xc.LengthMismatch):
+            xc.batch.add_slices(a, b[:2])
+        with self.assertRaises(xc.NegativeSqrt) as caught:
+            xc.batch.sqrt_slice(np.array([1.0, 4.0, -1.0]))
+        self.assertEqual(caught.exception.index, 2)
+        with self.assertRaises(xc.DivisionByZero) as caught:
+            xc.batch.div_slices(a, np.array([1.0, 0.0, 0.0]))
+        self.assertEqual(caught.exception.index, 1)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,4 @@
+// Re-export the calculator's `#[wasm_bindgen]` API so it ends up in this cdylib.
+// Build for the web demo with:
+//   wasm-pack build --target web --out-dir ../web-demo/calc_pkg
+pub use xrust_calclib::wasm::*;
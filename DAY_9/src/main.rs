@@ -2,7 +2,15 @@ mod math;
 use std::env;
 use std::process;
 
+use xrust_calclib::programmer::parse_int_f64;
+
 fn parse_arg(arg: &str) -> Result<f64, String> {
+    // Integer literals in hex, binary or octal, e.g. `0xFF`, `-0b1010`, must
+    // fit an f64 exactly.
+    let body = arg.strip_prefix(['-', '+']).unwrap_or(arg);
+    if matches!(body.get(..2), Some("0x" | "0X" | "0b" | "0B" | "0o" | "0O")) {
+        return parse_int_f64(arg).map_err(|err| format!("Invalid number: {} ({})", arg, err));
+    }
     arg.parse::<f64>()
         .map_err(|_| format!("Invalid number: {}", arg))
 }